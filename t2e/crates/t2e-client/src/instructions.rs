@@ -0,0 +1,188 @@
+//! Instruction builders. Optional accounts left as `None` are omitted, as
+//! the program expects.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData};
+use t2e_leaderboard::{accounts, instruction, TraderStatsInput, VenueType};
+
+use crate::{
+    automation_schedule_address, config_address, delegate_address, market_address,
+    quest_progress_address, role_address, snapshot_address, trader_stats_address, PROGRAM_ID,
+};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Appends `remaining` as read-only accounts, e.g. the TraderStats ranked by
+/// a crank.
+fn with_remaining(mut ix: Instruction, remaining: &[Pubkey]) -> Instruction {
+    ix.accounts
+        .extend(remaining.iter().map(|key| AccountMeta::new_readonly(*key, false)));
+    ix
+}
+
+/// `init_trader_stats` for `trader`, who pays the rent.
+pub fn init_trader_stats(trader: Pubkey) -> Instruction {
+    build(
+        accounts::InitTraderStats {
+            trader_stats: trader_stats_address(&trader),
+            trader,
+            system_program: system_program::ID,
+        },
+        instruction::InitTraderStats {},
+    )
+}
+
+/// Per-trade accounts of `record_trade` beyond the trader's own stats. The
+/// registered market is derived from `market` when `registered` is set;
+/// `leaderboard` names a live-ranked board to update. `delegate` names the
+/// delegate key signing for the trader, if any.
+#[derive(Clone, Copy, Default)]
+pub struct TradeAccounts {
+    pub pair: Option<(Pubkey, Pubkey)>, // (pair, PairVolume account)
+    pub market: Option<(Pubkey, Pubkey)>, // (market, MarketStats account)
+    pub registered: bool,
+    pub season: Option<(Pubkey, Pubkey)>, // (Season, TraderSeasonStats)
+    pub leaderboard: Option<Pubkey>,
+    pub delegate: Option<Pubkey>,
+}
+
+/// `record_trade` of `trader`, signed by them or by `trade.delegate`.
+pub fn record_trade(
+    trader: Pubkey,
+    volume: u64,
+    execution_time: u64,
+    pnl: i64,
+    venue: VenueType,
+    trade: TradeAccounts,
+) -> Instruction {
+    let market = trade.market.map(|(market, _)| market);
+    build(
+        accounts::RecordTrade {
+            trader_stats: trader_stats_address(&trader),
+            pair_volume: trade.pair.map(|(_, pair_volume)| pair_volume),
+            config: config_address(),
+            season: trade.season.map(|(season, _)| season),
+            season_stats: trade.season.map(|(_, season_stats)| season_stats),
+            market_stats: trade.market.map(|(_, market_stats)| market_stats),
+            registered_market: market.filter(|_| trade.registered).map(|m| market_address(&m)),
+            leaderboard: trade.leaderboard,
+            delegate: trade.delegate.map(|signer| delegate_address(&trader, &signer)),
+            trader: trade.delegate.unwrap_or(trader),
+        },
+        instruction::RecordTrade {
+            volume,
+            execution_time,
+            pnl,
+            venue,
+            pair: trade.pair.map(|(pair, _)| pair),
+            market,
+        },
+    )
+}
+
+/// Appends the `quests` whose progress a `record_trade` of `trader` should
+/// advance, each followed by the trader's QuestProgress.
+pub fn with_quests(mut ix: Instruction, trader: Pubkey, quests: &[Pubkey]) -> Instruction {
+    for quest in quests {
+        ix.accounts.push(AccountMeta::new_readonly(*quest, false));
+        ix.accounts.push(AccountMeta::new(quest_progress_address(quest, &trader), false));
+    }
+    ix
+}
+
+/// `update_leaderboard` posted by an operator. `role` is set when the
+/// authority acts through a Role grant; `banned` lists TraderStats of banned
+/// traders to drop from the ranking.
+pub fn update_leaderboard(
+    leaderboard: Pubkey,
+    authority: Pubkey,
+    role: bool,
+    trader_stats_list: Vec<TraderStatsInput>,
+    banned: &[Pubkey],
+) -> Instruction {
+    with_remaining(
+        build(
+            accounts::UpdateLeaderboard {
+                leaderboard,
+                config: config_address(),
+                role: role.then(|| role_address(&authority)),
+                authority,
+            },
+            instruction::UpdateLeaderboard { trader_stats_list },
+        ),
+        banned,
+    )
+}
+
+/// `crank_update_leaderboard` over the TraderStats in `trader_stats` (or
+/// (TraderStats, MarketStats) pairs for a market-scoped board). `keeper` is
+/// set when `thread` cranks through a keeper Role grant.
+pub fn crank_update_leaderboard(
+    leaderboard: Pubkey,
+    thread: Pubkey,
+    keeper: bool,
+    trader_stats: &[Pubkey],
+) -> Instruction {
+    with_remaining(
+        build(
+            accounts::CrankUpdateLeaderboard {
+                automation_schedule: automation_schedule_address(&leaderboard),
+                role: keeper.then(|| role_address(&thread)),
+                leaderboard,
+                config: config_address(),
+                thread,
+            },
+            instruction::CrankUpdateLeaderboard {},
+        ),
+        trader_stats,
+    )
+}
+
+/// `crank_snapshot` writing the snapshot at PDA index `snapshot_index`,
+/// i.e. `config.snapshot_slot(leaderboard.history_count)`.
+pub fn crank_snapshot(
+    leaderboard: Pubkey,
+    thread: Pubkey,
+    keeper: bool,
+    snapshot_index: u64,
+) -> Instruction {
+    build(
+        accounts::CrankSnapshot {
+            automation_schedule: automation_schedule_address(&leaderboard),
+            role: keeper.then(|| role_address(&thread)),
+            leaderboard,
+            config: config_address(),
+            snapshot: snapshot_address(&leaderboard, snapshot_index),
+            thread,
+            system_program: system_program::ID,
+        },
+        instruction::CrankSnapshot {},
+    )
+}
+
+/// Permissionless `accrue_staking_rewards` for `trader`.
+pub fn accrue_staking_rewards(trader: Pubkey) -> Instruction {
+    build(
+        accounts::AccrueStakingRewards {
+            trader_stats: trader_stats_address(&trader),
+            config: config_address(),
+        },
+        instruction::AccrueStakingRewards {},
+    )
+}
+
+/// `get_trader_rank` of `trader` on `leaderboard`; simulate it and decode
+/// the return data as `TraderRank`.
+pub fn get_trader_rank(leaderboard: Pubkey, trader: Pubkey) -> Instruction {
+    build(
+        accounts::GetTraderRank { leaderboard },
+        instruction::GetTraderRank { trader },
+    )
+}
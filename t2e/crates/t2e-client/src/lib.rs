@@ -0,0 +1,156 @@
+//! Off-chain client for the `t2e-leaderboard` program: PDA derivation,
+//! instruction builders and account decoders, so bots, keepers and crank
+//! services can integrate without hand-rolling Borsh layouts.
+//!
+//! Builders return plain `Instruction`s, which `solana-sdk` and
+//! `anchor-client` transactions accept as-is. Account decoders check the
+//! Anchor discriminator of the raw account data fetched over RPC.
+
+pub mod instructions;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use t2e_leaderboard::{
+    AutomationSchedule, Board, Config, Leaderboard, LeaderboardSnapshot, TraderStats,
+    VAULT_AUTHORITY_SEED,
+};
+
+pub use t2e_leaderboard::ID as PROGRAM_ID;
+
+/// Global config PDA.
+pub fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &PROGRAM_ID).0
+}
+
+/// TraderStats PDA of `trader`.
+pub fn trader_stats_address(trader: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"trader_stats", trader.as_ref()], &PROGRAM_ID).0
+}
+
+/// History snapshot PDA of `leaderboard` at PDA index `index`; see
+/// `Config::snapshot_slot`.
+pub fn snapshot_address(leaderboard: &Pubkey, index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"snapshot", leaderboard.as_ref(), &index.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+/// AutomationSchedule PDA of `leaderboard`.
+pub fn automation_schedule_address(leaderboard: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"automation", leaderboard.as_ref()], &PROGRAM_ID).0
+}
+
+/// Role PDA of `grantee`.
+pub fn role_address(grantee: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"role", grantee.as_ref()], &PROGRAM_ID).0
+}
+
+/// StakePosition PDA of `trader` with ID `position_id`.
+pub fn stake_position_address(trader: &Pubkey, position_id: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"stake_position", trader.as_ref(), &position_id.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+/// Registered Market PDA of `market`.
+pub fn market_address(market: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"market", market.as_ref()], &PROGRAM_ID).0
+}
+
+/// Reward vault PDA of `mint`.
+pub fn reward_vault_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reward_vault", mint.as_ref()], &PROGRAM_ID).0
+}
+
+/// Quest PDA of `quest_id`.
+pub fn quest_address(quest_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"quest", &quest_id.to_le_bytes()], &PROGRAM_ID).0
+}
+
+/// QuestProgress PDA of `trader` on `quest`.
+pub fn quest_progress_address(quest: &Pubkey, trader: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"quest_progress", quest.as_ref(), trader.as_ref()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+/// Delegate PDA authorizing `signer` to record trades of `trader`.
+pub fn delegate_address(trader: &Pubkey, signer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"delegate", trader.as_ref(), signer.as_ref()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+/// PDA signing transfers out of the program's vaults.
+pub fn vault_authority_address() -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED], &PROGRAM_ID).0
+}
+
+/// Decodes the global config.
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+    Config::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a trader's stats.
+pub fn decode_trader_stats(data: &[u8]) -> Result<TraderStats> {
+    TraderStats::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a leaderboard history snapshot.
+pub fn decode_snapshot(data: &[u8]) -> Result<LeaderboardSnapshot> {
+    LeaderboardSnapshot::try_deserialize(&mut &data[..])
+}
+
+/// Decodes an automation schedule.
+pub fn decode_automation_schedule(data: &[u8]) -> Result<AutomationSchedule> {
+    AutomationSchedule::try_deserialize(&mut &data[..])
+}
+
+/// Account data copied into 8-byte aligned storage, as zero-copy accounts
+/// require.
+pub struct AlignedData {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl AlignedData {
+    pub fn new(data: &[u8]) -> Self {
+        let mut aligned = AlignedData {
+            words: vec![0; data.len().div_ceil(8)],
+            len: data.len(),
+        };
+        aligned.copy_from_slice(data);
+        aligned
+    }
+}
+
+impl std::ops::Deref for AlignedData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &bytemuck::cast_slice(&self.words)[..self.len]
+    }
+}
+
+impl std::ops::DerefMut for AlignedData {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut bytemuck::cast_slice_mut(&mut self.words)[..self.len]
+    }
+}
+
+/// Decodes a zero-copy leaderboard with its entries, which dereferences to
+/// the `Leaderboard` header.
+pub fn decode_leaderboard(data: &[u8]) -> Result<Board<AlignedData>> {
+    if !data.starts_with(&Leaderboard::DISCRIMINATOR) {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    Board::new(AlignedData::new(data))
+}
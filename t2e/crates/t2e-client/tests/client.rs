@@ -0,0 +1,198 @@
+//! Checks that the builders lay out accounts as the program expects and that
+//! the decoders round-trip account data.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use t2e_client::instructions::{self, TradeAccounts};
+use t2e_client::{
+    config_address, decode_leaderboard, decode_trader_stats, delegate_address, market_address,
+    quest_address, quest_progress_address, trader_stats_address, AlignedData, PROGRAM_ID,
+};
+use t2e_leaderboard::{Board, Leaderboard, RankedTrader, TieBreak, TraderStats, VenueType};
+
+#[test]
+fn record_trade_omits_unused_optional_accounts() {
+    let trader = Pubkey::new_unique();
+    let market = Pubkey::new_unique();
+    let market_stats = Pubkey::new_unique();
+    let ix = instructions::record_trade(
+        trader,
+        5_000,
+        100,
+        -20,
+        VenueType::Spot,
+        TradeAccounts {
+            market: Some((market, market_stats)),
+            registered: true,
+            ..Default::default()
+        },
+    );
+
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        keys,
+        [
+            trader_stats_address(&trader),
+            PROGRAM_ID, // No pair volume.
+            config_address(),
+            PROGRAM_ID, // No season.
+            PROGRAM_ID,
+            market_stats,
+            market_address(&market),
+            PROGRAM_ID, // No live leaderboard.
+            PROGRAM_ID, // Signed by the trader.
+            trader,
+        ]
+    );
+    assert!(ix.accounts.last().unwrap().is_signer);
+    assert_eq!(ix.data[..8], t2e_leaderboard::instruction::RecordTrade::DISCRIMINATOR);
+}
+
+#[test]
+fn delegates_sign_for_the_trader() {
+    let trader = Pubkey::new_unique();
+    let hot_key = Pubkey::new_unique();
+    let ix = instructions::record_trade(
+        trader,
+        5_000,
+        100,
+        0,
+        VenueType::Spot,
+        TradeAccounts {
+            delegate: Some(hot_key),
+            ..Default::default()
+        },
+    );
+    assert_eq!(ix.accounts[0].pubkey, trader_stats_address(&trader));
+    assert_eq!(ix.accounts[8].pubkey, delegate_address(&trader, &hot_key));
+    let signer = ix.accounts.last().unwrap();
+    assert_eq!(signer.pubkey, hot_key);
+    assert!(signer.is_signer);
+}
+
+#[test]
+fn quests_follow_the_trade_accounts_in_pairs() {
+    let trader = Pubkey::new_unique();
+    let quests = [quest_address(1), quest_address(2)];
+    let ix = instructions::with_quests(
+        instructions::record_trade(trader, 5_000, 100, 0, VenueType::Spot, TradeAccounts::default()),
+        trader,
+        &quests,
+    );
+    let remaining = &ix.accounts[10..];
+    assert_eq!(remaining.len(), 4);
+    assert_eq!(remaining[2].pubkey, quests[1]);
+    assert!(!remaining[2].is_writable);
+    assert_eq!(remaining[3].pubkey, quest_progress_address(&quests[1], &trader));
+    assert!(remaining[3].is_writable);
+}
+
+#[test]
+fn crank_passes_trader_stats_as_remaining_accounts() {
+    let leaderboard = Pubkey::new_unique();
+    let stats = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let ix = instructions::crank_update_leaderboard(leaderboard, Pubkey::new_unique(), false, &stats);
+    assert_eq!(ix.accounts.len(), 5 + stats.len());
+    assert_eq!(ix.accounts[5].pubkey, stats[0]);
+    assert!(!ix.accounts[5].is_writable);
+}
+
+#[test]
+fn decoders_round_trip_account_data() {
+    let trader = Pubkey::new_unique();
+    let stats = TraderStats {
+        trader,
+        total_volume: 42,
+        ..Default::default()
+    };
+    let mut data = Vec::new();
+    stats.try_serialize(&mut data).unwrap();
+    let decoded = decode_trader_stats(&data).unwrap();
+    assert_eq!(decoded.trader, trader);
+    assert_eq!(decoded.total_volume, 42);
+
+    let mut data = vec![0; Leaderboard::space(2)];
+    data[..8].copy_from_slice(&Leaderboard::DISCRIMINATOR);
+    let mut board = Board::new(AlignedData::new(&data)).unwrap();
+    board.capacity = 2;
+    board.set_entries(&[RankedTrader {
+        trader,
+        score: 7,
+        tiebreak: TieBreak {
+            reputation: 0,
+            volume: 0,
+            first_trade: 0,
+        },
+    }]);
+    let data = board.into_inner().to_vec();
+    let mut decoded = decode_leaderboard(&data).unwrap();
+    assert_eq!(decoded.capacity, 2);
+    assert_eq!(decoded.traders(), [trader]);
+    assert_eq!(decoded.ranking_scores(), [7]);
+
+    assert!(decode_leaderboard(&data[8..]).is_err());
+    // A header claiming more slots than the data holds is rejected.
+    decoded.capacity = 3;
+    assert!(decode_leaderboard(&decoded.into_inner()).is_err());
+}
+
+#[test]
+fn growing_a_board_keeps_its_entries() {
+    let mut data = vec![0; Leaderboard::space(3)];
+    data[..8].copy_from_slice(&Leaderboard::DISCRIMINATOR);
+    let mut board = Board::new(AlignedData::new(&data)).unwrap();
+    board.capacity = 2;
+    let ranked: Vec<RankedTrader> = (0..2)
+        .map(|i| RankedTrader {
+            trader: Pubkey::new_unique(),
+            score: 10 - i,
+            tiebreak: TieBreak {
+                reputation: i,
+                volume: 0,
+                first_trade: 0,
+            },
+        })
+        .collect();
+    board.set_entries(&ranked);
+
+    board.grow(3);
+    assert_eq!(board.capacity, 3);
+    assert_eq!(board.traders(), [ranked[0].trader, ranked[1].trader]);
+    assert_eq!(board.ranking_scores(), [10, 9]);
+    assert_eq!(board.tiebreaks()[1].reputation, 1);
+}
+
+#[test]
+fn upsert_keeps_the_board_sorted_and_bounded() {
+    let mut data = vec![0; Leaderboard::space(3)];
+    data[..8].copy_from_slice(&Leaderboard::DISCRIMINATOR);
+    let mut board = Board::new(AlignedData::new(&data)).unwrap();
+    board.capacity = 3;
+    let entry = |trader, score| RankedTrader {
+        trader,
+        score,
+        tiebreak: TieBreak {
+            reputation: 0,
+            volume: 0,
+            first_trade: 0,
+        },
+    };
+    let traders: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    for (trader, score) in traders.iter().zip([5, 9, 7, 1]) {
+        board.upsert(entry(*trader, score), 3);
+    }
+    // The lowest score fell off the full board.
+    assert_eq!(board.traders(), [traders[1], traders[2], traders[0]]);
+    assert_eq!(board.ranking_scores(), [9, 7, 5]);
+
+    // A re-scored trader moves instead of appearing twice.
+    board.upsert(entry(traders[0], 8), 3);
+    assert_eq!(board.traders(), [traders[1], traders[0], traders[2]]);
+    board.upsert(entry(traders[1], 2), 3);
+    assert_eq!(board.traders(), [traders[0], traders[2], traders[1]]);
+
+    // Traders below the last kept entry stay off the board.
+    board.upsert(entry(traders[3], 1), 2);
+    assert_eq!(board.len, 3);
+    assert!(!board.traders().contains(&traders[3]));
+}
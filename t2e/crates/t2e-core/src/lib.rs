@@ -0,0 +1,470 @@
+//! Scoring, halving and reward-split math shared by the `t2e-leaderboard`
+//! program and off-chain keepers/indexers, so simulations and UIs match
+//! on-chain results bit-for-bit.
+//!
+//! `no_std` and allocation-free; all functions are pure. The `simulation`
+//! feature adds an allocating, deterministic replay harness in [`sim`].
+#![no_std]
+
+#[cfg(feature = "simulation")]
+extern crate alloc;
+
+#[cfg(feature = "simulation")]
+pub mod sim;
+
+/// Basis-point denominator.
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Length of an emission epoch (30 days).
+pub const EMISSION_EPOCH_LENGTH: i64 = 30 * 24 * 60 * 60;
+
+/// Emission epochs between reward halvings.
+pub const HALVING_INTERVAL_EPOCHS: i64 = 6;
+
+/// Staked $T2E per point of staking bonus.
+pub const STAKING_BONUS_UNIT: u64 = 1000;
+
+/// Weight of the volume component in a market maker's score.
+pub const MARKET_MAKER_VOLUME_WEIGHT_BPS: u64 = 15_000;
+
+/// Weight of the P&L component in a market maker's score.
+pub const MARKET_MAKER_PNL_WEIGHT_BPS: u64 = 2_500;
+
+/// Buckets of an execution-time histogram. Bucket 0 counts zero times,
+/// bucket `i` times in `[2^(i-1), 2^i)` and the last bucket everything above.
+pub const EXECUTION_TIME_BUCKETS: usize = 24;
+
+/// Components of a composite ranking score, for explaining how it was derived.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    pub volume_score: u64, // Weighted total_volume / (average_execution_time + 1).
+    pub pnl_score: u64, // Weighted positive P&L, per `PnlScoring`.
+    pub staking_bonus: u64, // Weighted staked_amount / staking divisor.
+    pub win_rate_bonus: u64, // Weighted win rate in bps.
+    pub streak_bonus: u64, // Share of the other components earned by a day streak.
+    pub volume_weight_bps: u64, // Multiplier applied to the volume component.
+    pub pnl_weight_bps: u64, // Multiplier applied to the P&L component.
+}
+
+impl ScoreBreakdown {
+    /// Sum of the components; 0 on overflow.
+    pub fn total(&self) -> u64 {
+        self.volume_score
+            .checked_add(self.pnl_score)
+            .unwrap_or(0)
+            .checked_add(self.staking_bonus)
+            .unwrap_or(0)
+            .checked_add(self.win_rate_bonus)
+            .unwrap_or(0)
+            .checked_add(self.streak_bonus)
+            .unwrap_or(0)
+    }
+}
+
+/// Tunable weights and divisors of the composite score. Weights are in basis
+/// points, so `BPS_DENOMINATOR` leaves a component unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScoringWeights {
+    pub volume_weight_bps: u64,
+    pub execution_time_weight_bps: u64, // Scales the execution-time penalty.
+    pub pnl_weight_bps: u64,
+    pub staking_weight_bps: u64,
+    pub volume_divisor: u64,
+    pub staking_divisor: u64, // Staked units per point of staking bonus.
+    pub pnl_scoring: PnlScoring,
+    pub win_rate_weight_bps: u64, // Bonus per bps of win rate; 0 = not scored.
+    pub streak_bonus_bps: u64, // Score multiplier added per streak day; 0 = not scored.
+    pub max_streak_days: u64, // Streak days counted at most.
+}
+
+/// How the P&L component of a score is derived.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PnlScoring {
+    /// Positive P&L as is.
+    #[default]
+    Absolute,
+    /// Positive P&L in basis points of total volume; see `return_on_volume_bps`.
+    ReturnOnVolume,
+    /// Sharpe-like ratio of per-trade P&L in basis points; see `sharpe_bps`.
+    RiskAdjusted,
+}
+
+/// Per-trade P&L statistics of a trader: the sum and sum of squares for
+/// risk-adjusted scoring, and the winning and losing trade counts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PnlMoments {
+    pub trade_count: u64,
+    pub sum: i64,
+    pub sum_squares: u128,
+    pub wins: u64, // Trades with positive P&L.
+    pub losses: u64, // Trades with negative P&L.
+}
+
+impl PnlMoments {
+    /// Adds one trade's P&L; the sum of squares saturates.
+    pub fn record(&mut self, pnl: i64) -> Option<()> {
+        self.trade_count = self.trade_count.checked_add(1)?;
+        match pnl.signum() {
+            1 => self.wins = self.wins.checked_add(1)?,
+            -1 => self.losses = self.losses.checked_add(1)?,
+            _ => {}
+        }
+        self.sum = self.sum.checked_add(pnl)?;
+        self.sum_squares = self
+            .sum_squares
+            .saturating_add(pnl.unsigned_abs() as u128 * pnl.unsigned_abs() as u128);
+        Some(())
+    }
+}
+
+impl ScoringWeights {
+    /// The original fixed formula.
+    pub const DEFAULT: ScoringWeights = ScoringWeights {
+        volume_weight_bps: BPS_DENOMINATOR as u64,
+        execution_time_weight_bps: BPS_DENOMINATOR as u64,
+        pnl_weight_bps: BPS_DENOMINATOR as u64,
+        staking_weight_bps: BPS_DENOMINATOR as u64,
+        volume_divisor: 1,
+        staking_divisor: STAKING_BONUS_UNIT,
+        pnl_scoring: PnlScoring::Absolute,
+        win_rate_weight_bps: 0,
+        streak_bonus_bps: 0,
+        max_streak_days: 0,
+    };
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        ScoringWeights::DEFAULT
+    }
+}
+
+/// `value * weight_bps / BPS_DENOMINATOR / divisor`, saturating at `u64::MAX`;
+/// 0 for a zero divisor.
+fn weighted(value: u64, weight_bps: u64, divisor: u64) -> u64 {
+    (value as u128 * weight_bps as u128 / BPS_DENOMINATOR)
+        .checked_div(divisor as u128)
+        .unwrap_or(0)
+        .min(u64::MAX as u128) as u64
+}
+
+/// `profit` in basis points of `total_volume`, saturating at `u64::MAX`; 0
+/// without volume.
+pub fn return_on_volume_bps(profit: u64, total_volume: u64) -> u64 {
+    (profit as u128 * BPS_DENOMINATOR)
+        .checked_div(total_volume as u128)
+        .unwrap_or(0)
+        .min(u64::MAX as u128) as u64
+}
+
+/// Share of decided (non-zero P&L) trades that were profitable, in basis
+/// points; 0 before any.
+pub fn win_rate_bps(moments: &PnlMoments) -> u64 {
+    let decided = moments.wins as u128 + moments.losses as u128;
+    (moments.wins as u128 * BPS_DENOMINATOR)
+        .checked_div(decided)
+        .unwrap_or(0) as u64
+}
+
+/// Mean per-trade P&L over its standard deviation, in basis points, with the
+/// deviation floored at one P&L unit. 0 for a non-positive mean or fewer than
+/// two trades.
+pub fn sharpe_bps(moments: &PnlMoments) -> u64 {
+    if moments.trade_count < 2 || moments.sum <= 0 {
+        return 0;
+    }
+    let (n, sum) = (moments.trade_count as u128, moments.sum as u128);
+    // mean / stddev = sum / sqrt(n * sum_squares - sum^2).
+    let ratio = match n.checked_mul(moments.sum_squares) {
+        Some(scaled_squares) => {
+            let spread = scaled_squares.saturating_sub(sum * sum).isqrt().max(n);
+            sum * BPS_DENOMINATOR / spread
+        }
+        None => {
+            let mean = sum / n;
+            let variance = (moments.sum_squares / n).saturating_sub(mean * mean);
+            mean * BPS_DENOMINATOR / variance.isqrt().max(1)
+        }
+    };
+    ratio.min(u64::MAX as u128) as u64
+}
+
+/// Per-component breakdown of `score`.
+pub fn score_breakdown(
+    total_volume: u64,
+    average_execution_time: u64,
+    pnl: i64,
+    staked_amount: u64,
+    market_maker: bool,
+) -> ScoreBreakdown {
+    score_breakdown_with_weights(
+        total_volume,
+        average_execution_time,
+        pnl,
+        staked_amount,
+        market_maker,
+        &ScoringWeights::DEFAULT,
+    )
+}
+
+/// Per-component breakdown of `score_with_weights`:
+/// - Base score: `total_volume * volume_weight / volume_divisor` divided by
+///   `average_execution_time * execution_time_weight + 1`
+/// - Bonus: weighted positive P&L and `staked_amount * staking_weight / staking_divisor`
+///
+/// With `PnlScoring::ReturnOnVolume`, the P&L bonus is computed from the
+/// return on volume, `pnl * 10_000 / total_volume`, so it does not grow with
+/// size. `PnlScoring::RiskAdjusted` needs per-trade moments and scores no P&L
+/// here; see `score_breakdown_with_moments`.
+///
+/// Market-maker reweighting applies on top of the configured weights.
+pub fn score_breakdown_with_weights(
+    total_volume: u64,
+    average_execution_time: u64,
+    pnl: i64,
+    staked_amount: u64,
+    market_maker: bool,
+    weights: &ScoringWeights,
+) -> ScoreBreakdown {
+    score_breakdown_with_moments(
+        total_volume,
+        average_execution_time,
+        pnl,
+        &PnlMoments::default(),
+        staked_amount,
+        market_maker,
+        weights,
+    )
+}
+
+/// `score_breakdown_with_weights` with the per-trade P&L `moments` that
+/// `PnlScoring::RiskAdjusted` scores instead of the total `pnl`, and from
+/// which the win-rate bonus is computed.
+pub fn score_breakdown_with_moments(
+    total_volume: u64,
+    average_execution_time: u64,
+    pnl: i64,
+    moments: &PnlMoments,
+    staked_amount: u64,
+    market_maker: bool,
+    weights: &ScoringWeights,
+) -> ScoreBreakdown {
+    let execution_penalty = weighted(average_execution_time, weights.execution_time_weight_bps, 1)
+        .saturating_add(1);
+    let mut volume_score = weighted(total_volume, weights.volume_weight_bps, weights.volume_divisor)
+        / execution_penalty;
+    let profit = if pnl > 0 { pnl as u64 } else { 0 };
+    let pnl_value = match weights.pnl_scoring {
+        PnlScoring::Absolute => profit,
+        PnlScoring::ReturnOnVolume => return_on_volume_bps(profit, total_volume),
+        PnlScoring::RiskAdjusted => sharpe_bps(moments),
+    };
+    let mut pnl_score = weighted(pnl_value, weights.pnl_weight_bps, 1);
+    let (mut volume_weight_bps, mut pnl_weight_bps) = (weights.volume_weight_bps, weights.pnl_weight_bps);
+    if market_maker {
+        volume_score = weighted(volume_score, MARKET_MAKER_VOLUME_WEIGHT_BPS, 1);
+        pnl_score = weighted(pnl_score, MARKET_MAKER_PNL_WEIGHT_BPS, 1);
+        volume_weight_bps = weighted(volume_weight_bps, MARKET_MAKER_VOLUME_WEIGHT_BPS, 1);
+        pnl_weight_bps = weighted(pnl_weight_bps, MARKET_MAKER_PNL_WEIGHT_BPS, 1);
+    }
+    ScoreBreakdown {
+        volume_score,
+        pnl_score,
+        staking_bonus: weighted(staked_amount, weights.staking_weight_bps, weights.staking_divisor),
+        win_rate_bonus: weighted(win_rate_bps(moments), weights.win_rate_weight_bps, 1),
+        streak_bonus: 0, // Added by `with_streak_bonus`.
+        volume_weight_bps,
+        pnl_weight_bps,
+    }
+}
+
+/// Adds the day-streak bonus to `breakdown`: `streak_bonus_bps` of the
+/// other components per streak day, up to `max_streak_days` days.
+pub fn with_streak_bonus(breakdown: ScoreBreakdown, streak_days: u64, weights: &ScoringWeights) -> ScoreBreakdown {
+    let bonus_bps = streak_days
+        .min(weights.max_streak_days)
+        .saturating_mul(weights.streak_bonus_bps);
+    ScoreBreakdown {
+        streak_bonus: weighted(breakdown.total(), bonus_bps, 1),
+        ..breakdown
+    }
+}
+
+/// Composite ranking score:
+/// - Base score: total_volume / (average_execution_time + 1)
+/// - Bonus: positive P&L and a staking bonus (staked_amount / 1000)
+///
+/// Market makers have the base score and P&L bonus reweighted by
+/// `MARKET_MAKER_VOLUME_WEIGHT_BPS` and `MARKET_MAKER_PNL_WEIGHT_BPS`.
+pub fn score(
+    total_volume: u64,
+    average_execution_time: u64,
+    pnl: i64,
+    staked_amount: u64,
+    market_maker: bool,
+) -> u64 {
+    score_breakdown(total_volume, average_execution_time, pnl, staked_amount, market_maker).total()
+}
+
+/// Composite ranking score under configurable `weights`; equal to `score`
+/// for `ScoringWeights::DEFAULT`.
+pub fn score_with_weights(
+    total_volume: u64,
+    average_execution_time: u64,
+    pnl: i64,
+    staked_amount: u64,
+    market_maker: bool,
+    weights: &ScoringWeights,
+) -> u64 {
+    score_breakdown_with_weights(total_volume, average_execution_time, pnl, staked_amount, market_maker, weights)
+        .total()
+}
+
+/// Emission epoch containing `current_time`.
+pub fn emission_epoch(current_time: i64) -> i64 {
+    current_time / EMISSION_EPOCH_LENGTH
+}
+
+/// Reward pool after the halving schedule: halved every 6 emission epochs.
+pub fn halved_reward(reward_amount: u64, current_time: i64) -> u64 {
+    halved_reward_with_period(
+        reward_amount,
+        current_time,
+        EMISSION_EPOCH_LENGTH * HALVING_INTERVAL_EPOCHS,
+    )
+}
+
+/// Reward pool halved once per `halving_period` seconds since the Unix epoch;
+/// 0 for a non-positive period.
+pub fn halved_reward_with_period(reward_amount: u64, current_time: i64, halving_period: i64) -> u64 {
+    if halving_period <= 0 {
+        return 0;
+    }
+    let halving_periods = current_time / halving_period;
+    // Fully halved away once the shift exceeds the width of u64.
+    u32::try_from(halving_periods.max(0))
+        .ok()
+        .and_then(|periods| reward_amount.checked_shr(periods))
+        .unwrap_or(0)
+}
+
+/// `score` after `elapsed` seconds of inactivity: halved once per full
+/// `half_life`, and scaled linearly from 1 to 1/2 within the current one.
+/// Unchanged for a non-positive `half_life` or `elapsed`.
+pub fn decayed_score(score: u64, elapsed: i64, half_life: i64) -> u64 {
+    if half_life <= 0 || elapsed <= 0 {
+        return score;
+    }
+    let halved = match u32::try_from(elapsed / half_life)
+        .ok()
+        .and_then(|halvings| score.checked_shr(halvings))
+    {
+        Some(halved) => halved,
+        None => return 0,
+    };
+    let period = 2 * half_life as u128;
+    let remainder = (elapsed % half_life) as u128;
+    (halved as u128 * (period - remainder) / period) as u64
+}
+
+/// Portion of `total` unlocked `elapsed` seconds into a vesting schedule:
+/// nothing before the `cliff`, then linear until all of it after `duration`.
+/// All of it for a non-positive `duration`.
+pub fn vested_amount(total: u64, elapsed: i64, cliff: i64, duration: i64) -> u64 {
+    if duration <= 0 || elapsed >= duration {
+        return total;
+    }
+    if elapsed <= 0 || elapsed < cliff {
+        return 0;
+    }
+    (total as u128 * elapsed as u128 / duration as u128) as u64
+}
+
+/// Penalty in basis points for unstaking with `remaining` seconds of a
+/// `duration`-second lock left: rising linearly from `min_bps` just before
+/// expiry to `max_bps` right after locking. Nothing once the lock expired.
+pub fn early_unstake_penalty_bps(min_bps: u16, max_bps: u16, remaining: i64, duration: i64) -> u16 {
+    if remaining <= 0 || duration <= 0 {
+        return 0;
+    }
+    let span = max_bps.saturating_sub(min_bps) as i128;
+    let scaled = span * remaining.min(duration) as i128 / duration as i128;
+    min_bps + scaled as u16
+}
+
+/// Average execution time after one more trade taking `execution_time`,
+/// given the current average over `trade_count` trades.
+pub fn average_execution_time(
+    average_execution_time: u64,
+    trade_count: u64,
+    execution_time: u64,
+) -> Option<u64> {
+    let new_count = trade_count.checked_add(1)?;
+    (average_execution_time as u128)
+        .checked_mul(trade_count as u128)?
+        .checked_add(execution_time as u128)
+        .map(|total| (total / new_count as u128) as u64)
+}
+
+/// Histogram bucket of `execution_time`; see `EXECUTION_TIME_BUCKETS`.
+pub fn execution_time_bucket(execution_time: u64) -> usize {
+    ((u64::BITS - execution_time.leading_zeros()) as usize).min(EXECUTION_TIME_BUCKETS - 1)
+}
+
+/// Counts one more trade taking `execution_time`, saturating per bucket.
+pub fn record_execution_time(histogram: &mut [u32; EXECUTION_TIME_BUCKETS], execution_time: u64) {
+    let bucket = &mut histogram[execution_time_bucket(execution_time)];
+    *bucket = bucket.saturating_add(1);
+}
+
+/// Estimated execution time at `quantile_bps` (5_000 = median) of the
+/// trades counted in `histogram`: the upper bound of the bucket holding it,
+/// or the lower bound of the open-ended last bucket. 0 for no trades.
+pub fn execution_time_quantile(histogram: &[u32; EXECUTION_TIME_BUCKETS], quantile_bps: u64) -> u64 {
+    let total: u64 = histogram.iter().map(|count| *count as u64).sum();
+    // 1-based rank of the quantile trade, rounded up.
+    let rank = (total as u128 * quantile_bps.min(BPS_DENOMINATOR as u64) as u128)
+        .div_ceil(BPS_DENOMINATOR)
+        .max(1) as u64;
+    let mut seen = 0;
+    for (bucket, count) in histogram.iter().enumerate() {
+        seen += *count as u64;
+        if seen >= rank {
+            return if bucket == EXECUTION_TIME_BUCKETS - 1 {
+                1 << (bucket - 1)
+            } else {
+                (1 << bucket) - 1
+            };
+        }
+    }
+    0
+}
+
+/// Reward weight of a score boosted by `boost_bps`.
+pub fn boosted_weight(score: u64, boost_bps: u16) -> Option<u128> {
+    (score as u128)
+        .checked_mul(BPS_DENOMINATOR + boost_bps as u128)
+        .map(|w| w / BPS_DENOMINATOR)
+}
+
+/// Splits `pool` pro rata to `weights`, writing each share into `shares`
+/// (same length as `weights`) and returning the rounding remainder.
+///
+/// Returns `None` on overflow, a zero total weight or mismatched lengths.
+pub fn split_rewards(weights: &[u128], pool: u64, shares: &mut [u64]) -> Option<u64> {
+    if weights.len() != shares.len() {
+        return None;
+    }
+    let total_weight = weights
+        .iter()
+        .try_fold(0u128, |acc, w| acc.checked_add(*w))?;
+    if total_weight == 0 {
+        return None;
+    }
+    let mut distributed: u64 = 0;
+    for (weight, share) in weights.iter().zip(shares.iter_mut()) {
+        *share = (weight.checked_mul(pool as u128)? / total_weight) as u64;
+        distributed = distributed.checked_add(*share)?;
+    }
+    pool.checked_sub(distributed)
+}
@@ -0,0 +1,202 @@
+//! Deterministic replay of trades, stakes, ranking updates and reward
+//! distributions using the same math as the on-chain program.
+//!
+//! A [`Simulation`] applies a sequence of [`Action`]s and fails with a
+//! [`SimError`] wherever the program would reject the instruction, so
+//! sequences can be replayed to check conservation and rounding invariants.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{
+    average_execution_time, boosted_weight, halved_reward, score, split_rewards,
+};
+
+/// One step of a simulated run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// `record_trade` for trader `trader`.
+    Trade {
+        trader: usize,
+        volume: u64,
+        execution_time: u64,
+        pnl: i64,
+    },
+    /// `stake_tokens`.
+    Stake { trader: usize, amount: u64 },
+    /// `unstake_tokens`.
+    Unstake { trader: usize, amount: u64 },
+    /// Sets a trader's reward boost (season pass, loyalty).
+    SetBoost { trader: usize, boost_bps: u16 },
+    /// `update_leaderboard` over all simulated traders.
+    Update,
+    /// `distribute_rewards(top_n, reward_amount)`.
+    Distribute { top_n: usize, reward_amount: u64 },
+    /// Advances the clock by the given number of seconds.
+    Advance(i64),
+}
+
+/// Why a simulated action was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimError {
+    Overflow,
+    UnknownTrader,
+    InsufficientStake,
+    InsufficientVault,
+    EmptyRanking,
+}
+
+/// Simulated TraderStats.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimTrader {
+    pub total_volume: u64,
+    pub average_execution_time: u64,
+    pub trade_count: u64,
+    pub pnl: i64,
+    pub staked_amount: u64,
+    pub market_maker: bool,
+    pub boost_bps: u16,
+    pub rewards_received: u64,
+}
+
+/// State of a simulated deployment: traders, ranking and reward vault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Simulation {
+    pub now: i64,
+    pub traders: Vec<SimTrader>,
+    /// `(trader index, score)` in rank order, as of the last `Update`.
+    pub ranking: Vec<(usize, u64)>,
+    pub vault: u64,
+    pub staking_vault: u64,
+    pub distributed: u64,
+}
+
+impl Simulation {
+    /// A run with `traders` fresh traders and a reward vault of `vault`.
+    pub fn new(traders: usize, vault: u64, now: i64) -> Self {
+        Simulation {
+            now,
+            traders: vec![SimTrader::default(); traders],
+            ranking: Vec::new(),
+            vault,
+            staking_vault: 0,
+            distributed: 0,
+        }
+    }
+
+    /// Applies every action in order, stopping at the first rejection.
+    pub fn run(&mut self, actions: &[Action]) -> Result<(), SimError> {
+        actions.iter().try_for_each(|action| self.apply(action))
+    }
+
+    /// Applies one action; the state is unchanged if it is rejected.
+    pub fn apply(&mut self, action: &Action) -> Result<(), SimError> {
+        match *action {
+            Action::Trade {
+                trader,
+                volume,
+                execution_time,
+                pnl,
+            } => {
+                let t = self.traders.get_mut(trader).ok_or(SimError::UnknownTrader)?;
+                let total_volume = t.total_volume.checked_add(volume).ok_or(SimError::Overflow)?;
+                let average = average_execution_time(
+                    t.average_execution_time,
+                    t.trade_count,
+                    execution_time,
+                )
+                .ok_or(SimError::Overflow)?;
+                let trade_count = t.trade_count.checked_add(1).ok_or(SimError::Overflow)?;
+                let pnl = t.pnl.checked_add(pnl).ok_or(SimError::Overflow)?;
+                t.total_volume = total_volume;
+                t.average_execution_time = average;
+                t.trade_count = trade_count;
+                t.pnl = pnl;
+            }
+            Action::Stake { trader, amount } => {
+                let t = self.traders.get_mut(trader).ok_or(SimError::UnknownTrader)?;
+                let staked = t.staked_amount.checked_add(amount).ok_or(SimError::Overflow)?;
+                self.staking_vault = self
+                    .staking_vault
+                    .checked_add(amount)
+                    .ok_or(SimError::Overflow)?;
+                t.staked_amount = staked;
+            }
+            Action::Unstake { trader, amount } => {
+                let t = self.traders.get_mut(trader).ok_or(SimError::UnknownTrader)?;
+                t.staked_amount = t
+                    .staked_amount
+                    .checked_sub(amount)
+                    .ok_or(SimError::InsufficientStake)?;
+                self.staking_vault -= amount;
+            }
+            Action::SetBoost { trader, boost_bps } => {
+                self.traders
+                    .get_mut(trader)
+                    .ok_or(SimError::UnknownTrader)?
+                    .boost_bps = boost_bps;
+            }
+            Action::Update => {
+                let mut ranking: Vec<(usize, u64)> = self
+                    .traders
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| {
+                        (
+                            i,
+                            score(
+                                t.total_volume,
+                                t.average_execution_time,
+                                t.pnl,
+                                t.staked_amount,
+                                t.market_maker,
+                            ),
+                        )
+                    })
+                    .collect();
+                ranking.sort_by_key(|(_, score)| core::cmp::Reverse(*score));
+                self.ranking = ranking;
+            }
+            Action::Distribute {
+                top_n,
+                reward_amount,
+            } => self.distribute(top_n, reward_amount)?,
+            Action::Advance(seconds) => {
+                self.now = self.now.checked_add(seconds).ok_or(SimError::Overflow)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pays the halved `reward_amount` to the top `top_n` ranked traders pro
+    /// rata to their boosted scores, sending the rounding remainder to the
+    /// first trader with a nonzero weight.
+    fn distribute(&mut self, top_n: usize, reward_amount: u64) -> Result<(), SimError> {
+        let pool = halved_reward(reward_amount, self.now);
+        if pool > self.vault {
+            return Err(SimError::InsufficientVault);
+        }
+        let winners = &self.ranking[..top_n.min(self.ranking.len())];
+        let weights = winners
+            .iter()
+            .map(|(i, score)| boosted_weight(*score, self.traders[*i].boost_bps))
+            .collect::<Option<Vec<u128>>>()
+            .ok_or(SimError::Overflow)?;
+        let mut shares = vec![0u64; weights.len()];
+        let remainder =
+            split_rewards(&weights, pool, &mut shares).ok_or(SimError::EmptyRanking)?;
+        let top = weights
+            .iter()
+            .position(|w| *w > 0)
+            .ok_or(SimError::EmptyRanking)?;
+        shares[top] = shares[top].checked_add(remainder).ok_or(SimError::Overflow)?;
+
+        // Cumulative payouts are bounded by the vault, so these cannot overflow.
+        for ((i, _), share) in winners.iter().zip(shares.iter()) {
+            self.traders[*i].rewards_received += *share;
+        }
+        self.vault -= pool;
+        self.distributed += pool;
+        Ok(())
+    }
+}
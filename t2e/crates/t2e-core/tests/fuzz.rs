@@ -0,0 +1,391 @@
+//! Randomized property tests of the score, halving, decay, vesting and
+//! reward-split math and of the replay harness. Cases come from a fixed-seed generator, so every
+//! failure reproduces exactly.
+
+use t2e_core::sim::{Action, SimError, Simulation};
+use t2e_core::{
+    average_execution_time, boosted_weight, decayed_score, early_unstake_penalty_bps,
+    execution_time_quantile, halved_reward,
+    record_execution_time, return_on_volume_bps, score, sharpe_bps, win_rate_bps, with_streak_bonus, PnlMoments, PnlScoring, score_breakdown, score_breakdown_with_weights, score_with_weights,
+    split_rewards, vested_amount, ScoringWeights, EMISSION_EPOCH_LENGTH, EXECUTION_TIME_BUCKETS,
+};
+
+const CASES: usize = 10_000;
+
+/// xorshift64* generator.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// Mostly small values, with the edges (0, max) drawn often enough to
+    /// exercise overflow paths.
+    fn edgy_u64(&mut self) -> u64 {
+        match self.below(8) {
+            0 => 0,
+            1 => u64::MAX,
+            2 => u64::MAX - self.below(1_000),
+            3 => self.next(),
+            _ => self.below(1_000_000_000),
+        }
+    }
+
+    fn edgy_i64(&mut self) -> i64 {
+        match self.below(8) {
+            0 => i64::MIN,
+            1 => i64::MAX,
+            2 => self.next() as i64,
+            _ => self.below(2_000_000_000) as i64 - 1_000_000_000,
+        }
+    }
+}
+
+#[test]
+fn score_matches_breakdown_and_never_panics() {
+    let mut rng = Rng(0x7e2e_0001);
+    for _ in 0..CASES {
+        let (volume, exec, pnl, staked) =
+            (rng.edgy_u64(), rng.edgy_u64(), rng.edgy_i64(), rng.edgy_u64());
+        let market_maker = rng.below(2) == 1;
+        let breakdown = score_breakdown(volume, exec, pnl, staked, market_maker);
+        assert_eq!(score(volume, exec, pnl, staked, market_maker), breakdown.total());
+        if pnl <= 0 {
+            assert_eq!(breakdown.pnl_score, 0);
+        }
+    }
+}
+
+#[test]
+fn weighted_score_defaults_to_score_and_zero_weights_drop_components() {
+    let mut rng = Rng(0x7e2e_0007);
+    for _ in 0..CASES {
+        let (volume, exec, pnl, staked) =
+            (rng.edgy_u64(), rng.edgy_u64(), rng.edgy_i64(), rng.edgy_u64());
+        let market_maker = rng.below(2) == 1;
+        assert_eq!(
+            score_with_weights(volume, exec, pnl, staked, market_maker, &ScoringWeights::DEFAULT),
+            score(volume, exec, pnl, staked, market_maker)
+        );
+
+        let weights = ScoringWeights {
+            volume_weight_bps: rng.below(3) * rng.below(50_000),
+            execution_time_weight_bps: rng.edgy_u64(),
+            pnl_weight_bps: rng.below(3) * rng.below(50_000),
+            staking_weight_bps: rng.below(3) * rng.below(50_000),
+            volume_divisor: rng.below(1_000),
+            staking_divisor: rng.below(10_000),
+            pnl_scoring: [PnlScoring::Absolute, PnlScoring::ReturnOnVolume, PnlScoring::RiskAdjusted]
+                [rng.below(3) as usize],
+            win_rate_weight_bps: rng.below(3) * rng.below(50_000),
+            streak_bonus_bps: rng.below(1_000),
+            max_streak_days: rng.below(30),
+        };
+        let breakdown =
+            score_breakdown_with_weights(volume, exec, pnl, staked, market_maker, &weights);
+        if weights.volume_weight_bps == 0 || weights.volume_divisor == 0 {
+            assert_eq!(breakdown.volume_score, 0);
+        }
+        if weights.pnl_weight_bps == 0
+            || weights.pnl_scoring == PnlScoring::RiskAdjusted
+            || (weights.pnl_scoring == PnlScoring::ReturnOnVolume && volume == 0)
+        {
+            assert_eq!(breakdown.pnl_score, 0);
+        }
+        if weights.staking_weight_bps == 0 || weights.staking_divisor == 0 {
+            assert_eq!(breakdown.staking_bonus, 0);
+        }
+        // No trade moments passed, so no win rate.
+        assert_eq!(breakdown.win_rate_bonus, 0);
+        assert_eq!(breakdown.streak_bonus, 0);
+
+        let streak_days = rng.below(60);
+        let boosted = with_streak_bonus(breakdown, streak_days, &weights);
+        assert!(boosted.total() >= breakdown.total() || boosted.total() == 0);
+        if streak_days == 0 || weights.max_streak_days == 0 || weights.streak_bonus_bps == 0 {
+            assert_eq!(boosted, breakdown);
+        }
+        // Days beyond the cap earn nothing more.
+        assert_eq!(
+            boosted,
+            with_streak_bonus(breakdown, streak_days.min(weights.max_streak_days), &weights)
+        );
+    }
+}
+
+#[test]
+fn return_on_volume_does_not_scale_with_size() {
+    let mut rng = Rng(0x7e2e_0009);
+    let weights = ScoringWeights {
+        pnl_scoring: PnlScoring::ReturnOnVolume,
+        ..ScoringWeights::DEFAULT
+    };
+    for _ in 0..CASES {
+        let (volume, profit) = (1 + rng.below(1_000_000_000), rng.below(1_000_000_000));
+        let factor = 1 + rng.below(1_000);
+        let small = score_breakdown_with_weights(volume, 0, profit as i64, 0, false, &weights);
+        let large =
+            score_breakdown_with_weights(volume * factor, 0, (profit * factor) as i64, 0, false, &weights);
+        assert_eq!(small.pnl_score, large.pnl_score);
+        assert_eq!(small.pnl_score, return_on_volume_bps(profit, volume));
+    }
+}
+
+#[test]
+fn risk_adjusted_pnl_rewards_consistency() {
+    let mut rng = Rng(0x7e2e_000a);
+    for _ in 0..CASES {
+        let (pnl, trades) = (1 + rng.below(1_000_000) as i64, 2 + rng.below(100));
+        let mut steady = PnlMoments::default();
+        let mut streaky = PnlMoments::default();
+        for i in 0..trades {
+            steady.record(pnl).unwrap();
+            // Same total, earned in one lucky trade.
+            streaky.record(if i == 0 { pnl * trades as i64 } else { 0 }).unwrap();
+        }
+        assert_eq!(steady.sum, streaky.sum);
+        assert!(sharpe_bps(&steady) > sharpe_bps(&streaky));
+        assert_eq!(sharpe_bps(&steady), pnl as u64 * 10_000);
+
+        let mut losing = PnlMoments::default();
+        for _ in 0..trades {
+            losing.record(-pnl).unwrap();
+        }
+        assert_eq!(sharpe_bps(&losing), 0);
+
+        assert_eq!(win_rate_bps(&steady), 10_000);
+        assert_eq!(win_rate_bps(&streaky), 10_000); // Zero-P&L trades are not losses.
+        assert_eq!(win_rate_bps(&losing), 0);
+        let mut mixed = steady;
+        for _ in 0..trades {
+            mixed.record(-pnl).unwrap();
+        }
+        assert_eq!(win_rate_bps(&mixed), 5_000);
+    }
+
+    // Extreme P&L overflows the exact form but still scores.
+    let mut whale = PnlMoments::default();
+    for pnl in [i64::MAX / 2, -i64::MAX / 2, i64::MAX / 2, -i64::MAX / 2, i64::MAX / 2] {
+        whale.record(pnl).unwrap();
+    }
+    assert!(sharpe_bps(&whale) > 0);
+}
+
+#[test]
+fn halving_never_increases_rewards() {
+    let mut rng = Rng(0x7e2e_0002);
+    for _ in 0..CASES {
+        let reward = rng.edgy_u64();
+        let time = rng.below(40 * 365 * 24 * 60 * 60) as i64;
+        let halved = halved_reward(reward, time);
+        assert!(halved <= reward.max(1));
+        assert!(halved_reward(reward, time + EMISSION_EPOCH_LENGTH) <= halved);
+    }
+}
+
+#[test]
+fn decay_never_increases_score_and_halves_per_half_life() {
+    let mut rng = Rng(0x7e2e_0008);
+    for _ in 0..CASES {
+        let score = rng.edgy_u64();
+        let half_life = 1 + rng.below(365 * 24 * 60 * 60) as i64;
+        let elapsed = rng.below(40 * 365 * 24 * 60 * 60) as i64;
+        let decayed = decayed_score(score, elapsed, half_life);
+        assert!(decayed <= score);
+        assert!(decayed_score(score, elapsed + 1, half_life) <= decayed);
+        assert_eq!(decayed_score(score, half_life, half_life), score / 2);
+        assert_eq!(decayed_score(score, elapsed, 0), score);
+    }
+}
+
+#[test]
+fn vesting_is_bounded_and_monotonic() {
+    let mut rng = Rng(0x7e2e_0009);
+    for _ in 0..CASES {
+        let total = rng.edgy_u64();
+        let duration = 1 + rng.below(4 * 365 * 24 * 60 * 60) as i64;
+        let cliff = rng.below(duration as u64 + 1) as i64;
+        let elapsed = rng.below(5 * 365 * 24 * 60 * 60) as i64;
+        let vested = vested_amount(total, elapsed, cliff, duration);
+        assert!(vested <= total);
+        assert!(vested_amount(total, elapsed + 1, cliff, duration) >= vested);
+        assert_eq!(vested_amount(total, duration, cliff, duration), total);
+        if elapsed < cliff {
+            assert_eq!(vested, 0);
+        }
+    }
+}
+
+#[test]
+fn early_unstake_penalty_is_bounded_and_shrinks_with_the_lock() {
+    let mut rng = Rng(0x7e2e_000b);
+    for _ in 0..CASES {
+        let min_bps = rng.below(10_001) as u16;
+        let max_bps = min_bps + rng.below(10_001 - min_bps as u64) as u16;
+        let duration = 1 + rng.below(365 * 24 * 60 * 60) as i64;
+        let remaining = rng.below(duration as u64 + 1) as i64;
+        let penalty = early_unstake_penalty_bps(min_bps, max_bps, remaining, duration);
+        assert!(penalty <= max_bps);
+        assert!(early_unstake_penalty_bps(min_bps, max_bps, remaining - 1, duration) <= penalty);
+        if remaining > 0 {
+            assert!(penalty >= min_bps);
+        } else {
+            assert_eq!(penalty, 0);
+        }
+        assert_eq!(early_unstake_penalty_bps(min_bps, max_bps, duration, duration), max_bps);
+    }
+}
+
+#[test]
+fn boost_never_reduces_weight() {
+    let mut rng = Rng(0x7e2e_0003);
+    for _ in 0..CASES {
+        let score = rng.edgy_u64();
+        let boost = rng.below(u16::MAX as u64 + 1) as u16;
+        let weight = boosted_weight(score, boost).unwrap();
+        assert!(weight >= score as u128);
+    }
+}
+
+#[test]
+fn average_execution_time_stays_within_bounds() {
+    let mut rng = Rng(0x7e2e_0004);
+    for _ in 0..CASES {
+        let (average, count, exec) = (rng.edgy_u64(), rng.edgy_u64(), rng.edgy_u64());
+        match average_execution_time(average, count, exec) {
+            Some(new_average) => {
+                assert!(new_average <= average.max(exec));
+                assert!(new_average >= average.min(exec) || count == u64::MAX);
+            }
+            None => assert_eq!(count, u64::MAX),
+        }
+    }
+}
+
+#[test]
+fn execution_time_quantiles_bracket_the_exact_quantile() {
+    let mut rng = Rng(0x7e2e_0008);
+    for _ in 0..CASES / 10 {
+        let mut histogram = [0; EXECUTION_TIME_BUCKETS];
+        let mut times: Vec<u64> = (0..1 + rng.below(50))
+            .map(|_| rng.below(1 << (EXECUTION_TIME_BUCKETS - 2)))
+            .collect();
+        for time in &times {
+            record_execution_time(&mut histogram, *time);
+        }
+        times.sort_unstable();
+        let mut previous = 0;
+        for quantile_bps in [0, 5_000, 9_500, 10_000] {
+            let exact = times[((times.len() as u64 * quantile_bps).div_ceil(10_000).max(1) - 1) as usize];
+            let estimate = execution_time_quantile(&histogram, quantile_bps);
+            assert!(estimate >= exact && estimate <= exact.saturating_mul(2));
+            assert!(estimate >= previous);
+            previous = estimate;
+        }
+    }
+    assert_eq!(execution_time_quantile(&[0; EXECUTION_TIME_BUCKETS], 5_000), 0);
+}
+
+#[test]
+fn split_conserves_pool_and_bounds_remainder() {
+    let mut rng = Rng(0x7e2e_0005);
+    for _ in 0..CASES {
+        let n = 1 + rng.below(20) as usize;
+        let weights: Vec<u128> = (0..n)
+            .map(|_| boosted_weight(rng.edgy_u64(), rng.below(5_000) as u16).unwrap())
+            .collect();
+        let pool = rng.edgy_u64();
+        let mut shares = vec![0u64; n];
+        let Some(remainder) = split_rewards(&weights, pool, &mut shares) else {
+            // Only a zero total weight or an overflowing weight sum is rejected.
+            let total = weights.iter().try_fold(0u128, |acc, w| acc.checked_add(*w));
+            assert!(total.is_none_or(|t| t == 0 || t.checked_mul(pool as u128).is_none()));
+            continue;
+        };
+        let paid: u128 = shares.iter().map(|s| *s as u128).sum();
+        assert_eq!(paid + remainder as u128, pool as u128);
+        // Each share rounds down by less than one unit.
+        assert!((remainder as usize) < n);
+    }
+}
+
+fn random_action(rng: &mut Rng, traders: usize) -> Action {
+    let trader = rng.below(traders as u64) as usize;
+    match rng.below(7) {
+        0 | 1 => Action::Trade {
+            trader,
+            volume: rng.edgy_u64() >> rng.below(64),
+            execution_time: rng.below(10_000),
+            pnl: rng.edgy_i64() >> rng.below(64),
+        },
+        2 => Action::Stake {
+            trader,
+            amount: rng.below(1_000_000),
+        },
+        3 => Action::Unstake {
+            trader,
+            amount: rng.below(1_000_000),
+        },
+        4 => Action::SetBoost {
+            trader,
+            boost_bps: rng.below(5_000) as u16,
+        },
+        5 => Action::Update,
+        _ => match rng.below(2) {
+            0 => Action::Distribute {
+                top_n: 1 + rng.below(10) as usize,
+                reward_amount: rng.below(1_000_000),
+            },
+            _ => Action::Advance(rng.below(EMISSION_EPOCH_LENGTH as u64) as i64),
+        },
+    }
+}
+
+#[test]
+fn replay_conserves_vault_and_is_deterministic() {
+    let mut rng = Rng(0x7e2e_0006);
+    for _ in 0..200 {
+        let traders = 1 + rng.below(12) as usize;
+        let initial_vault = rng.below(1_000_000_000);
+        let mut sim = Simulation::new(traders, initial_vault, 0);
+        let mut replay = sim.clone();
+        let mut staked = 0u64;
+
+        for _ in 0..200 {
+            let action = random_action(&mut rng, traders);
+            let before = sim.clone();
+            match sim.apply(&action) {
+                Ok(()) => {
+                    if let Action::Stake { amount, .. } = action {
+                        staked += amount;
+                    }
+                    if let Action::Unstake { amount, .. } = action {
+                        staked -= amount;
+                    }
+                }
+                Err(
+                    SimError::InsufficientStake
+                    | SimError::InsufficientVault
+                    | SimError::EmptyRanking
+                    | SimError::Overflow,
+                ) => assert_eq!(sim, before, "rejected {action:?} mutated state"),
+                Err(SimError::UnknownTrader) => unreachable!(),
+            }
+            let _ = replay.apply(&action);
+
+            let received: u64 = sim.traders.iter().map(|t| t.rewards_received).sum();
+            assert_eq!(received, sim.distributed);
+            assert_eq!(sim.vault + sim.distributed, initial_vault);
+            assert_eq!(sim.staking_vault, staked);
+        }
+        assert_eq!(sim, replay);
+    }
+}
@@ -0,0 +1,66 @@
+//! CPI interface of the `t2e-leaderboard` program for DEX and other venue
+//! programs, built on its Anchor `cpi` feature.
+//!
+//! A registered partner program signs `record_partner_trade` and
+//! `collect_trading_fee` as its `PARTNER_SIGNER_SEED` PDA; the helpers here
+//! add that signature, so callers only supply the accounts and the PDA's
+//! bump. Venues whose users sign the trade themselves can call `record_trade`
+//! through the re-exported `cpi` module instead.
+
+use anchor_lang::prelude::*;
+
+pub use t2e_leaderboard::cpi::accounts::{CollectTradingFee, RecordPartnerTrade, RecordTrade};
+pub use t2e_leaderboard::cpi::record_trade;
+pub use t2e_leaderboard::program::T2eLeaderboard;
+pub use t2e_leaderboard::{VenueType, ID, PARTNER_SIGNER_SEED};
+
+/// The `PARTNER_SIGNER_SEED` PDA of `partner_program` and its bump, which
+/// must be registered as the partner's signer.
+pub fn partner_signer(partner_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PARTNER_SIGNER_SEED], partner_program)
+}
+
+/// Records a trade for one of the calling program's users via
+/// `record_partner_trade`, signing as the partner signer PDA with
+/// `partner_signer_bump`.
+#[allow(clippy::too_many_arguments)]
+pub fn record_partner_trade<'info>(
+    t2e_program: AccountInfo<'info>,
+    accounts: RecordPartnerTrade<'info>,
+    partner_signer_bump: u8,
+    volume: u64,
+    execution_time: u64,
+    pnl: i64,
+    venue: VenueType,
+    pair: Option<Pubkey>,
+    market: Option<Pubkey>,
+) -> Result<()> {
+    let bump = [partner_signer_bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[PARTNER_SIGNER_SEED, &bump]];
+    t2e_leaderboard::cpi::record_partner_trade(
+        CpiContext::new_with_signer(t2e_program, accounts, signer_seeds),
+        volume,
+        execution_time,
+        pnl,
+        venue,
+        pair,
+        market,
+    )
+}
+
+/// Charges a trader's trading fee via `collect_trading_fee`, signing as the
+/// partner signer PDA with `partner_signer_bump`. The trader must sign the
+/// calling transaction.
+pub fn collect_trading_fee<'info>(
+    t2e_program: AccountInfo<'info>,
+    accounts: CollectTradingFee<'info>,
+    partner_signer_bump: u8,
+    gross_fee: u64,
+) -> Result<()> {
+    let bump = [partner_signer_bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[PARTNER_SIGNER_SEED, &bump]];
+    t2e_leaderboard::cpi::collect_trading_fee(
+        CpiContext::new_with_signer(t2e_program, accounts, signer_seeds),
+        gross_fee,
+    )
+}
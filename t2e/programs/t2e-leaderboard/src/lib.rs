@@ -1,5 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    non_transferable::NonTransferable, transfer_fee::TransferFeeConfig,
+    transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_interface::{self, Mint, TokenInterface};
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
 
 declare_id!("GHTyeny1bNPntWknAJwxu2YWJ9GUyRL57PjtGeaapS9h");
 
@@ -8,343 +17,11208 @@ pub mod t2e_leaderboard {
     use super::*;
 
     /// Initializes the leaderboard state.
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        let leaderboard = &mut ctx.accounts.leaderboard;
+    ///
+    /// `market` scopes the leaderboard to one market; `Pubkey::default()`
+    /// ranks trading across all markets. The board holds as many entries as
+    /// the client allocated space for (see `Leaderboard::space`), up to
+    /// `MAX_LEADERBOARD_ENTRIES`; `grow_leaderboard` adds more later.
+    pub fn initialize(ctx: Context<Initialize>, market: Pubkey) -> Result<()> {
+        let capacity = Leaderboard::capacity_for(ctx.accounts.leaderboard.as_ref().data_len());
+        // The account is allocated zeroed by the client; all other fields start at zero.
+        let leaderboard = &mut ctx.accounts.leaderboard.load_init()?;
+        leaderboard.market = market;
         leaderboard.last_update = Clock::get()?.unix_timestamp;
-        leaderboard.traders = Vec::new();
-        leaderboard.ranking_scores = Vec::new();
-        leaderboard.emergency_pause = false;
+        leaderboard.capacity = capacity;
+        Ok(())
+    }
+
+    /// Grows a leaderboard by `additional` entry slots, up to
+    /// `MAX_LEADERBOARD_GROWTH` per call and `MAX_LEADERBOARD_ENTRIES` in
+    /// total, with the rent paid by `payer`. Admin or operator only.
+    pub fn grow_leaderboard(ctx: Context<GrowLeaderboard>, additional: u32) -> Result<()> {
+        let capacity = ctx.accounts.leaderboard.load()?.capacity;
+        let new_capacity = capacity
+            .checked_add(additional)
+            .filter(|c| additional > 0 && additional <= MAX_LEADERBOARD_GROWTH && *c <= MAX_LEADERBOARD_ENTRIES)
+            .ok_or(ErrorCode::InvalidLeaderboardGrowth)?;
+
+        let info = ctx.accounts.leaderboard.to_account_info();
+        let size = Leaderboard::space(new_capacity);
+        let rent = Rent::get()?.minimum_balance(size).saturating_sub(info.lamports());
+        if rent > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                rent,
+            )?;
+        }
+        info.realloc(size, true)?;
+        load_board_mut(&ctx.accounts.leaderboard)?.grow(new_capacity);
+        Ok(())
+    }
+
+    /// Creates the global leaderboard registry. Admin only.
+    pub fn init_leaderboard_registry(ctx: Context<InitLeaderboardRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.entries = Vec::new();
+        registry.bump = ctx.bumps.registry;
+        Ok(())
+    }
+
+    /// Lists a leaderboard in the global registry under a unique name, so
+    /// clients can discover every board from one account. Admin only.
+    pub fn register_leaderboard(
+        ctx: Context<RegisterLeaderboard>,
+        name: [u8; 32],
+        market: Pubkey,
+        period: i64,
+    ) -> Result<()> {
+        if name.iter().all(|b| *b == 0) {
+            return Err(ErrorCode::InvalidLeaderboardName.into());
+        }
+        let leaderboard = ctx.accounts.leaderboard.key();
+        let registry = &mut ctx.accounts.registry;
+        if registry.entries.len() >= MAX_REGISTERED_LEADERBOARDS {
+            return Err(ErrorCode::RegistryFull.into());
+        }
+        if registry
+            .entries
+            .iter()
+            .any(|entry| entry.name == name || entry.leaderboard == leaderboard)
+        {
+            return Err(ErrorCode::LeaderboardAlreadyRegistered.into());
+        }
+        registry.entries.push(RegistryEntry {
+            leaderboard,
+            name,
+            market,
+            authority: ctx.accounts.admin.key(),
+            period,
+        });
+        Ok(())
+    }
+
+    /// Creates the global config account holding the admin key and the
+    /// fraud-proof window applied between a leaderboard update and its payout.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        distribution_delay: i64,
+        min_dispute_bond: u64,
+        dispute_reward: u64,
+    ) -> Result<()> {
+        if distribution_delay < 0 {
+            return Err(ErrorCode::InvalidDistributionDelay.into());
+        }
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.co_admins = Vec::new();
+        config.distribution_delay = distribution_delay;
+        config.swap_escrow = Pubkey::default();
+        config.treasury = Pubkey::default();
+        config.min_dispute_bond = min_dispute_bond;
+        config.dispute_reward = dispute_reward;
+        config.max_trades_per_epoch = 0;
+        config.trade_epoch_length = 24 * 60 * 60;
+        config.zero_weight_excess_trades = false;
+        config.pause_flags = 0;
+        config.current_season = 0;
+        config.season_pass_price = 0;
+        config.season_pass_boost_bps = 0;
+        config.season_pass_treasury_bps = 0;
+        config.loyalty_points_per_epoch = 0;
+        config.fee_credit_per_point = 0;
+        config.points_per_boost_bps = 0;
+        config.leader_share_bps = 0;
+        config.max_ranking_age = 0;
+        config.arbiter = Pubkey::default();
+        config.insurance_fund = Pubkey::default();
+        config.min_appeal_bond = 0;
+        config.inactivity_horizon = 0;
+        config.referral_code_fee = 0;
+        config.max_price_age = 0;
+        config.max_price_confidence_bps = 0;
+        config.emit_score_breakdowns = false;
+        config.unstake_cooldown = DEFAULT_UNSTAKE_COOLDOWN;
+        config.trade_cooldown = DEFAULT_TRADE_COOLDOWN;
+        config.update_interval = DEFAULT_UPDATE_INTERVAL;
+        config.halving_period = DEFAULT_HALVING_PERIOD;
+        config.set_fee_discount_tiers(&DEFAULT_FEE_DISCOUNT_TIERS);
+        config.max_leaderboard_size = DEFAULT_MAX_LEADERBOARD_SIZE;
+        config.operator = Pubkey::default();
+        config.season_count = 0;
+        config.active_season = 0;
+        config.scoring = ScoringConfig::DEFAULT;
+        config.score_half_life = 0;
+        config.vesting_cliff = 0;
+        config.vesting_duration = 0;
+        config.trade_oracle = Pubkey::default();
+        config.snapshot_retention = 0;
+        config.referral_reward_bps = 0;
+        config.badge_mints = [Pubkey::default(); ACHIEVEMENT_COUNT];
+        config.treasury_burn_bps = 0;
+        config.total_burned = 0;
+        config.emission_schedule = Pubkey::default();
+        config.staking_apr_bps = 0;
+        config.staking_reward_index = 0;
+        config.staking_index_updated = Clock::get()?.unix_timestamp;
+        config.voting_period = 0;
+        config.quorum_votes = 0;
+        config.proposal_min_stake = 0;
+        config.proposal_count = 0;
+        config.config_change_count = 0;
+        config.admin_set = Pubkey::default();
+        config.min_trade_volume = 0;
+        config.rate_window_trades = 0;
+        config.rate_window = 0;
+        config.min_stake_for_ranking = 0;
+        config.registered_markets = 0;
+        config.early_unstake_min_penalty_bps = 0;
+        config.early_unstake_max_penalty_bps = 0;
+        config.early_unstake_penalty_to_treasury = false;
+        config.reward_pool = 0;
+        config.burn_epoch = 0;
+        config.epoch_burned = 0;
+        config.bump = ctx.bumps.config;
+        Ok(())
+    }
+
+    /// Updates the fraud-proof window (in seconds). Admin only.
+    pub fn set_distribution_delay(ctx: Context<UpdateConfig>, distribution_delay: i64) -> Result<()> {
+        if distribution_delay < 0 {
+            return Err(ErrorCode::InvalidDistributionDelay.into());
+        }
+        ctx.accounts.config.distribution_delay = distribution_delay;
+        Ok(())
+    }
+
+    /// Sets the pool paid out by each `distribute_rewards` or `finalize_epoch`
+    /// before halvings and any emission cap. Admin only.
+    pub fn set_reward_pool(ctx: Context<UpdateConfig>, reward_pool: u64) -> Result<()> {
+        ctx.accounts.config.reward_pool = reward_pool;
+        Ok(())
+    }
+
+    /// Sets the maximum age (in seconds) of a ranking that can still be paid
+    /// out; 0 disables the check. Admin only.
+    pub fn set_max_ranking_age(ctx: Context<UpdateConfig>, max_ranking_age: i64) -> Result<()> {
+        if max_ranking_age < 0 {
+            return Err(ErrorCode::InvalidMaxRankingAge.into());
+        }
+        ctx.accounts.config.max_ranking_age = max_ranking_age;
+        Ok(())
+    }
+
+    /// Updates the protocol's tunable parameters. Admin only.
+    ///
+    /// - `trade_cooldown`: minimum seconds between trades (market makers use
+    ///   `MARKET_MAKER_TRADE_INTERVAL`).
+    /// - `update_interval`: minimum seconds between leaderboard updates.
+    /// - `halving_period`: seconds between reward halvings.
+    /// - `max_leaderboard_size`: entries kept per ranking.
+    pub fn set_protocol_params(
+        ctx: Context<UpdateConfig>,
+        trade_cooldown: i64,
+        update_interval: i64,
+        halving_period: i64,
+        max_leaderboard_size: u32,
+    ) -> Result<()> {
+        if trade_cooldown < 0
+            || update_interval < 0
+            || halving_period <= 0
+            || max_leaderboard_size == 0
+            || max_leaderboard_size > MAX_LEADERBOARD_ENTRIES
+        {
+            return Err(ErrorCode::InvalidProtocolParams.into());
+        }
+        let config = &mut ctx.accounts.config;
+        config.trade_cooldown = trade_cooldown;
+        config.update_interval = update_interval;
+        config.halving_period = halving_period;
+        config.max_leaderboard_size = max_leaderboard_size;
+        Ok(())
+    }
+
+    /// Replaces the fee discount tier table. Thresholds must strictly increase
+    /// and discounts must not decrease or exceed 100%. Admin only.
+    pub fn set_fee_discount_tiers(
+        ctx: Context<UpdateConfig>,
+        tiers: Vec<FeeDiscountTier>,
+    ) -> Result<()> {
+        let ordered = tiers
+            .windows(2)
+            .all(|pair| pair[0].min_stake < pair[1].min_stake && pair[0].discount <= pair[1].discount);
+        if tiers.len() > MAX_FEE_DISCOUNT_TIERS
+            || !ordered
+            || tiers.iter().any(|tier| tier.discount > 100)
+        {
+            return Err(ErrorCode::InvalidFeeDiscountTiers.into());
+        }
+        ctx.accounts.config.set_fee_discount_tiers(&tiers);
+        Ok(())
+    }
+
+    /// Sets the cooldown between an unstake request and the withdrawal. Admin only.
+    pub fn set_unstake_cooldown(ctx: Context<UpdateConfig>, unstake_cooldown: i64) -> Result<()> {
+        if unstake_cooldown < 0 {
+            return Err(ErrorCode::InvalidUnstakeCooldown.into());
+        }
+        ctx.accounts.config.unstake_cooldown = unstake_cooldown;
+        Ok(())
+    }
+
+    /// Allows unstaking locked positions early for a penalty of `min_bps`
+    /// (lock nearly over) up to `max_bps` (lock just started) of the amount,
+    /// scaled by the lock time remaining. The penalty is withheld from the
+    /// withdrawal and sent to the treasury or, by default, the reward vault.
+    /// A `max_bps` of 0 disables early unstaking. Admin only.
+    pub fn set_early_unstake_penalty(
+        ctx: Context<UpdateConfig>,
+        min_bps: u16,
+        max_bps: u16,
+        to_treasury: bool,
+    ) -> Result<()> {
+        if min_bps > max_bps || max_bps > 10_000 {
+            return Err(ErrorCode::InvalidBps.into());
+        }
+        let config = &mut ctx.accounts.config;
+        if to_treasury && config.treasury == Pubkey::default() {
+            return Err(ErrorCode::TreasuryNotProvided.into());
+        }
+        config.early_unstake_min_penalty_bps = min_bps;
+        config.early_unstake_max_penalty_bps = max_bps;
+        config.early_unstake_penalty_to_treasury = to_treasury;
+        Ok(())
+    }
+
+    /// Enables or disables `ScoreBreakdownRecorded` events on ranking
+    /// updates. Admin only.
+    pub fn set_score_breakdowns(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        ctx.accounts.config.emit_score_breakdowns = enabled;
+        Ok(())
+    }
+
+    /// Sets the weights and divisors of the composite ranking score used by
+    /// all ranking updates, previews and disputes. Admin only.
+    pub fn set_scoring_config(ctx: Context<UpdateConfig>, scoring: ScoringConfig) -> Result<()> {
+        if scoring.volume_divisor == 0 || scoring.staking_divisor == 0 {
+            return Err(ErrorCode::InvalidScoringConfig.into());
+        }
+        ctx.accounts.config.scoring = scoring;
+        Ok(())
+    }
+
+    /// Sets how many history snapshots are kept per leaderboard before the
+    /// oldest is overwritten; 0 keeps all of them. Admin only.
+    pub fn set_snapshot_retention(ctx: Context<UpdateConfig>, retention: u64) -> Result<()> {
+        ctx.accounts.config.snapshot_retention = retention;
+        Ok(())
+    }
+
+    /// Sets the half-life of the decay applied to the scores of inactive
+    /// traders, in seconds since their last trade; 0 disables decay. Admin only.
+    pub fn set_score_half_life(ctx: Context<UpdateConfig>, half_life: i64) -> Result<()> {
+        if half_life < 0 {
+            return Err(ErrorCode::InvalidScoringConfig.into());
+        }
+        ctx.accounts.config.score_half_life = half_life;
+        Ok(())
+    }
+
+    /// Sets the vesting of rewards credited by `finalize_epoch`: nothing
+    /// unlocks before `cliff` seconds, then linearly until `duration` seconds
+    /// after the credit. A zero `duration` makes rewards claimable at once.
+    /// Admin only.
+    pub fn set_vesting_params(ctx: Context<UpdateConfig>, cliff: i64, duration: i64) -> Result<()> {
+        if cliff < 0 || duration < 0 || cliff > duration {
+            return Err(ErrorCode::InvalidVestingParams.into());
+        }
+        let config = &mut ctx.accounts.config;
+        config.vesting_cliff = cliff;
+        config.vesting_duration = duration;
+        Ok(())
+    }
+
+    /// Sets the maximum age and confidence interval accepted from oracle
+    /// prices; a zero `max_price_age` disables oracle normalization. Admin only.
+    pub fn set_oracle_params(
+        ctx: Context<UpdateConfig>,
+        max_price_age: i64,
+        max_price_confidence_bps: u16,
+    ) -> Result<()> {
+        if max_price_age < 0
+            || max_price_confidence_bps > 10_000
+            || (max_price_age > 0 && max_price_confidence_bps == 0)
+        {
+            return Err(ErrorCode::InvalidOracleParams.into());
+        }
+        let config = &mut ctx.accounts.config;
+        config.max_price_age = max_price_age;
+        config.max_price_confidence_bps = max_price_confidence_bps;
+        Ok(())
+    }
+
+    /// Maps a token mint to the Pyth price feed used to normalize its volume
+    /// to USD. Admin only.
+    pub fn set_price_feed(ctx: Context<SetPriceFeed>, feed_id: [u8; 32]) -> Result<()> {
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.mint = ctx.accounts.mint.key();
+        price_feed.feed_id = feed_id;
+        price_feed.bump = ctx.bumps.price_feed;
+        Ok(())
+    }
+
+    /// Sets how long a trader may go without trading before their entry can be
+    /// pruned from the board; 0 disables pruning. Admin only.
+    pub fn set_inactivity_horizon(ctx: Context<UpdateConfig>, inactivity_horizon: i64) -> Result<()> {
+        if inactivity_horizon < 0 {
+            return Err(ErrorCode::InvalidInactivityHorizon.into());
+        }
+        ctx.accounts.config.inactivity_horizon = inactivity_horizon;
+        Ok(())
+    }
+
+    /// Updates the minimum score-dispute bond and the reward paid to
+    /// successful challengers. Admin only.
+    pub fn set_dispute_params(
+        ctx: Context<UpdateConfig>,
+        min_dispute_bond: u64,
+        dispute_reward: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.min_dispute_bond = min_dispute_bond;
+        config.dispute_reward = dispute_reward;
+        Ok(())
+    }
+
+    /// Configures the per-epoch trade cap. Admin only.
+    ///
+    /// - `max_trades_per_epoch`: trades per trader per epoch; 0 disables the cap.
+    /// - `trade_epoch_length`: epoch length in seconds.
+    /// - `zero_weight_excess_trades`: record excess trades at zero score weight
+    ///   instead of rejecting them.
+    pub fn set_trade_rate_limit(
+        ctx: Context<UpdateConfig>,
+        max_trades_per_epoch: u32,
+        trade_epoch_length: i64,
+        zero_weight_excess_trades: bool,
+    ) -> Result<()> {
+        if trade_epoch_length <= 0 {
+            return Err(ErrorCode::InvalidTradeEpochLength.into());
+        }
+        let config = &mut ctx.accounts.config;
+        config.max_trades_per_epoch = max_trades_per_epoch;
+        config.trade_epoch_length = trade_epoch_length;
+        config.zero_weight_excess_trades = zero_weight_excess_trades;
+        Ok(())
+    }
+
+    /// Sets the stake an identity needs to appear in ranking updates, so
+    /// unstaked sybil wallets cannot farm the leaderboard. Admin only.
+    pub fn set_min_stake_for_ranking(
+        ctx: Context<UpdateConfig>,
+        min_stake_for_ranking: u64,
+    ) -> Result<()> {
+        ctx.accounts.config.min_stake_for_ranking = min_stake_for_ranking;
+        Ok(())
+    }
+
+    /// Replaces the `trade_cooldown` gate with a sliding window allowing
+    /// `max_trades` trades per `window` seconds, so bursts of legitimate
+    /// trading pass while sustained spam is rejected. `max_trades` of 0
+    /// restores the cooldown. Admin only.
+    pub fn set_trade_rate_window(
+        ctx: Context<UpdateConfig>,
+        max_trades: u8,
+        window: i64,
+    ) -> Result<()> {
+        if max_trades as usize > MAX_RATE_WINDOW_TRADES || window < 0 || (max_trades > 0 && window == 0) {
+            return Err(ErrorCode::InvalidRateWindow.into());
+        }
+        let config = &mut ctx.accounts.config;
+        config.rate_window_trades = max_trades;
+        config.rate_window = window;
+        Ok(())
+    }
+
+    /// Sets the minimum volume of a recorded trade, so `trade_count` cannot
+    /// be farmed with dust trades; 0 accepts any volume. Admin only.
+    pub fn set_min_trade_volume(ctx: Context<UpdateConfig>, min_trade_volume: u64) -> Result<()> {
+        ctx.accounts.config.min_trade_volume = min_trade_volume;
+        Ok(())
+    }
+
+    /// Creates the signer's TraderStats PDA with all stats zeroed.
+    pub fn init_trader_stats(ctx: Context<InitTraderStats>) -> Result<()> {
+        ctx.accounts.trader_stats.set_inner(TraderStats {
+            trader: ctx.accounts.trader.key(),
+            bump: ctx.bumps.trader_stats,
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Starts season `index`, which must follow the last started season.
+    /// Only one season runs at a time. Admin only.
+    pub fn start_season(ctx: Context<StartSeason>, index: u32, start_ts: i64, end_ts: i64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if config.active_season != 0 {
+            return Err(ErrorCode::SeasonAlreadyActive.into());
+        }
+        if Some(index) != config.season_count.checked_add(1) || start_ts >= end_ts {
+            return Err(ErrorCode::InvalidSeasonParams.into());
+        }
+        config.season_count = index;
+        config.active_season = index;
+        ctx.accounts.season.set_inner(Season {
+            index,
+            start_ts,
+            end_ts,
+            ended: false,
+            bump: ctx.bumps.season,
+        });
+        Ok(())
+    }
+
+    /// Ends the active season once its end timestamp has passed, freezing
+    /// its TraderSeasonStats for ranking and rewards. Admin only.
+    pub fn end_season(ctx: Context<EndSeason>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let season = &mut ctx.accounts.season;
+        if season.index != config.active_season {
+            return Err(ErrorCode::InvalidSeasonParams.into());
+        }
+        if Clock::get()?.unix_timestamp < season.end_ts {
+            return Err(ErrorCode::SeasonNotOver.into());
+        }
+        season.ended = true;
+        config.active_season = 0;
+        Ok(())
+    }
+
+    /// Creates the signer's TraderSeasonStats PDA for a season that has not ended.
+    pub fn init_trader_season_stats(ctx: Context<InitTraderSeasonStats>) -> Result<()> {
+        ctx.accounts.season_stats.set_inner(TraderSeasonStats {
+            trader: ctx.accounts.trader.key(),
+            season: ctx.accounts.season.index,
+            bump: ctx.bumps.season_stats,
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Rolls a trader's stats over after a season has ended: archives the
+    /// working volume, execution time, P&L and trade counters into a
+    /// TraderSeasonArchive and zeroes them, so the next season starts fresh.
+    /// Permissionless; each trader can be rolled over once per season.
+    pub fn reset_trader_stats(ctx: Context<ResetTraderStats>) -> Result<()> {
+        let season = &ctx.accounts.season;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        if !season.ended || season.index <= trader_stats.archived_season {
+            return Err(ErrorCode::InvalidSeasonParams.into());
+        }
+        ctx.accounts.archive.set_inner(TraderSeasonArchive {
+            trader: trader_stats.trader,
+            season: season.index,
+            total_volume: trader_stats.total_volume,
+            average_execution_time: trader_stats.average_execution_time,
+            trade_count: trader_stats.trade_count,
+            pnl: trader_stats.pnl,
+            spot_volume: trader_stats.spot_volume,
+            spot_pnl: trader_stats.spot_pnl,
+            perp_volume: trader_stats.perp_volume,
+            perp_pnl: trader_stats.perp_pnl,
+            archived_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.archive,
+        });
+        trader_stats.total_volume = 0;
+        trader_stats.average_execution_time = 0;
+        trader_stats.execution_time_histogram = [0; t2e_core::EXECUTION_TIME_BUCKETS];
+        trader_stats.trade_pnl_sum = 0;
+        trader_stats.trade_pnl_sum_squares = 0;
+        trader_stats.winning_trades = 0;
+        trader_stats.losing_trades = 0;
+        trader_stats.trading_streak = 0;
+        trader_stats.profitable_streak = 0;
+        trader_stats.streak_day_pnl = 0;
+        trader_stats.trade_count = 0;
+        trader_stats.pnl = 0;
+        trader_stats.spot_volume = 0;
+        trader_stats.spot_pnl = 0;
+        trader_stats.perp_volume = 0;
+        trader_stats.perp_pnl = 0;
+        trader_stats.archived_season = season.index;
         Ok(())
     }
 
     /// Records a trade by updating the trader's stats.
     ///
     /// - Updates total volume, weighted average execution time, and P&L.
-    /// - Prevents rapid-fire trades to mitigate spam (enforcing a 10-second gap).
-    pub fn record_trade(
-        ctx: Context<RecordTrade>,
+    /// - Prevents rapid-fire trades to mitigate spam (enforcing the sliding
+    ///   rate window if configured, else `config.trade_cooldown`).
+    /// - Rejects trades below `config.min_trade_volume`.
+    /// - Enforces the optional per-epoch trade cap; excess trades are rejected or,
+    ///   if configured, recorded at zero score weight (no volume, losses only).
+    /// - Tracks spot and perp volume/P&L separately according to `venue`.
+    /// - If `pair` is given, also updates the trader's `PairVolume` account for that pair.
+    /// - If `market` is given, also accumulates into the trader's `MarketStats` for that market.
+    /// - Once any market is registered, `market` must name a registered
+    ///   market, whose `Market` account is passed, and the trade's volume is
+    ///   scaled by the market's multiplier.
+    /// - Accrues loyalty points for the first trade in each epoch.
+    /// - While a season is active, the trader's `TraderSeasonStats` and the
+    ///   `Season` must be passed; trades inside the season window accumulate into it.
+    /// - If a live-ranked `leaderboard` is passed, re-scores the trader and
+    ///   moves them to their place on it; see `update_live_ranking`.
+    /// - Advances the trader's progress on the quests passed via
+    ///   `remaining_accounts` as (Quest, QuestProgress) pairs. Trades
+    ///   recorded at zero score weight do not count.
+    /// - May be signed by a delegate key of the trader's, whose Delegate
+    ///   account is then passed; see `authorize_delegate`.
+    pub fn record_trade<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecordTrade<'info>>,
         volume: u64,
         execution_time: u64,
         pnl: i64,
+        venue: VenueType,
+        pair: Option<Pubkey>,
+        market: Option<Pubkey>,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_TRADING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+
+        let accounts = ctx.accounts;
+        book_trade(
+            &accounts.config,
+            &mut accounts.trader_stats,
+            accounts.pair_volume.as_deref_mut(),
+            accounts.market_stats.as_deref_mut(),
+            accounts.registered_market.as_deref(),
+            accounts.season.as_deref(),
+            accounts.season_stats.as_deref_mut(),
+            accounts.leaderboard.as_ref(),
+            ctx.remaining_accounts,
+            volume,
+            execution_time,
+            pnl,
+            venue,
+            pair,
+            market,
+        )
+    }
+
+    /// Turns live ranking of a leaderboard on or off. A live-ranked board is
+    /// kept sorted by `record_trade`, which re-scores the trading wallet from
+    /// its on-chain stats, so small boards need no `update_leaderboard` posts.
+    /// The board's capacity (and `config.max_leaderboard_size`) bounds it to
+    /// the top K traders. Admin only.
+    pub fn set_live_ranking(ctx: Context<SetLiveRanking>, enabled: bool) -> Result<()> {
+        ctx.accounts.leaderboard.load_mut()?.live_ranking = enabled as u8;
+        Ok(())
+    }
+
+    /// Registers `market` as a sanctioned venue, or updates its multiplier.
+    /// Once any market is registered, `record_trade` only accepts trades on
+    /// registered markets, crediting `volume_multiplier_bps` of their volume.
+    /// Admin only.
+    pub fn register_market(
+        ctx: Context<RegisterMarket>,
+        market: Pubkey,
+        volume_multiplier_bps: u16,
+    ) -> Result<()> {
+        if volume_multiplier_bps == 0 || volume_multiplier_bps > MAX_MARKET_MULTIPLIER_BPS {
+            return Err(ErrorCode::InvalidBps.into());
+        }
+        let registered = &mut ctx.accounts.registered_market;
+        if registered.registered_at == 0 {
+            let config = &mut ctx.accounts.config;
+            config.registered_markets = config
+                .registered_markets
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+            registered.registered_at = Clock::get()?.unix_timestamp;
+        }
+        registered.market = market;
+        registered.volume_multiplier_bps = volume_multiplier_bps;
+        registered.bump = ctx.bumps.registered_market;
+        Ok(())
+    }
+
+    /// Removes a market from the registry and refunds its rent. Deregistering
+    /// the last market lifts the registry requirement of `record_trade`.
+    /// Admin only.
+    pub fn deregister_market(ctx: Context<DeregisterMarket>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.registered_markets = config.registered_markets.saturating_sub(1);
+        Ok(())
+    }
+
+    /// Registers an external trading program allowed to record trades via
+    /// CPI, with an optional per-call $T2E integration fee. Admin only.
+    pub fn register_partner(
+        ctx: Context<RegisterPartner>,
+        program_id: Pubkey,
+        integration_fee: u64,
     ) -> Result<()> {
+        if integration_fee > 0 && ctx.accounts.config.treasury == Pubkey::default() {
+            return Err(ErrorCode::TreasuryNotProvided.into());
+        }
+        let partner = &mut ctx.accounts.partner;
+        partner.program_id = program_id;
+        partner.signer =
+            Pubkey::find_program_address(&[PARTNER_SIGNER_SEED], &program_id).0;
+        partner.integration_fee = integration_fee;
+        partner.active = true;
+        partner.trades_recorded = 0;
+        partner.bump = ctx.bumps.partner;
+        Ok(())
+    }
+
+    /// Updates a partner's integration fee and enables or disables it. Admin only.
+    pub fn update_partner(
+        ctx: Context<UpdatePartner>,
+        integration_fee: u64,
+        active: bool,
+    ) -> Result<()> {
+        if integration_fee > 0 && ctx.accounts.config.treasury == Pubkey::default() {
+            return Err(ErrorCode::TreasuryNotProvided.into());
+        }
+        let partner = &mut ctx.accounts.partner;
+        partner.integration_fee = integration_fee;
+        partner.active = active;
+        Ok(())
+    }
+
+    /// Creates the protocol fee vault of a fee mint, owned by the vault
+    /// authority PDA. Admin only.
+    pub fn init_fee_vault(_ctx: Context<InitFeeVault>) -> Result<()> {
+        Ok(())
+    }
+
+    /// CPI endpoint for registered partner venues to charge a trader's
+    /// trading fee, signed as in `record_partner_trade`; the trader must
+    /// also sign.
+    ///
+    /// The trader's stored `fee_discount` is applied to `gross_fee`, then any
+    /// fee credits are used up, and the rest is transferred from the trader
+    /// into the fee vault of the fee mint. The trader's FollowLink address is
+    /// always passed; if the link exists, the leader's LeaderRevenue for the
+    /// current trade epoch must be passed too, and the leader accrues
+    /// `config.leader_share_bps` of the fee paid.
+    pub fn collect_trading_fee(ctx: Context<CollectTradingFee>, gross_fee: u64) -> Result<()> {
+        let config = &ctx.accounts.config;
+        if config.is_paused(PAUSE_TRADING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        if !ctx.accounts.partner.active {
+            return Err(ErrorCode::PartnerInactive.into());
+        }
+
         let trader_stats = &mut ctx.accounts.trader_stats;
+        let discount = (gross_fee as u128 * trader_stats.fee_discount.min(100) as u128 / 100) as u64;
+        let discounted = gross_fee - discount;
+        let credits_used = discounted.min(trader_stats.fee_credits);
+        trader_stats.fee_credits -= credits_used;
+        let net_fee = discounted - credits_used;
+        trader_stats.fees_paid = trader_stats
+            .fees_paid
+            .checked_add(net_fee)
+            .ok_or(ErrorCode::Overflow)?;
+        trader_stats.fee_savings = trader_stats
+            .fee_savings
+            .checked_add(gross_fee - net_fee)
+            .ok_or(ErrorCode::Overflow)?;
+
         let current_time = Clock::get()?.unix_timestamp;
-        
-        // Prevent trade spam: if a trade was made less than 10 seconds ago, reject.
-        if trader_stats.trade_count > 0 && current_time - trader_stats.last_trade < 10 {
-            return Err(ErrorCode::TradeSpamDetected.into());
+        // The follow link is bound to its PDA, so an empty account proves the
+        // trader follows no one.
+        let follow_link = ctx.accounts.follow_link.to_account_info();
+        let leader = if follow_link.data_is_empty() {
+            None
+        } else {
+            if follow_link.owner != &crate::ID {
+                return Err(ErrorCode::LeaderRevenueMismatch.into());
+            }
+            let data = follow_link.try_borrow_data()?;
+            Some(FollowLink::try_deserialize(&mut &data[..])?.leader)
+        };
+        let leader_share = match (leader, ctx.accounts.leader_revenue.as_mut()) {
+            (Some(leader), Some(leader_revenue)) => {
+                if leader_revenue.leader != leader
+                    || leader_revenue.epoch != current_time / config.trade_epoch_length
+                {
+                    return Err(ErrorCode::LeaderRevenueMismatch.into());
+                }
+                leader_revenue.accrue_share(net_fee, config.leader_share_bps)?
+            }
+            (None, None) => 0,
+            _ => return Err(ErrorCode::LeaderRevenueMismatch.into()),
+        };
+
+        if net_fee > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.trader_token_account.to_account_info(),
+                        to: ctx.accounts.fee_vault.to_account_info(),
+                        authority: ctx.accounts.trader.to_account_info(),
+                    },
+                ),
+                net_fee,
+            )?;
         }
+        emit!(TradingFeeCollected {
+            trader: trader_stats.trader,
+            partner: ctx.accounts.partner.program_id,
+            gross_fee,
+            discount,
+            credits_used,
+            net_fee,
+            leader_share,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
 
-        // Update trading volume.
-        trader_stats.total_volume = trader_stats
-            .total_volume
-            .checked_add(volume)
+    /// CPI endpoint for registered partner programs to record a verified
+    /// trade for one of their users.
+    ///
+    /// The partner program signs as its PDA derived from
+    /// `[PARTNER_SIGNER_SEED]` under its own program id (`partner_signer`),
+    /// and pays the integration fee, if any, from `fee_payer_token_account`
+    /// to the treasury. The trade is applied exactly as in `record_trade`,
+    /// including rate limits, market registration and multipliers, and the
+    /// pair, market, season, quest and live-ranking accounts it takes.
+    pub fn record_partner_trade<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecordPartnerTrade<'info>>,
+        volume: u64,
+        execution_time: u64,
+        pnl: i64,
+        venue: VenueType,
+        pair: Option<Pubkey>,
+        market: Option<Pubkey>,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_TRADING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let partner = &mut ctx.accounts.partner;
+        if !partner.active {
+            return Err(ErrorCode::PartnerInactive.into());
+        }
+
+        if partner.integration_fee > 0 {
+            let fee_payer_token_account = ctx
+                .accounts
+                .fee_payer_token_account
+                .as_ref()
+                .ok_or(ErrorCode::TraderTokenAccountNotFound)?;
+            let treasury = ctx
+                .accounts
+                .treasury
+                .as_ref()
+                .ok_or(ErrorCode::TreasuryNotProvided)?;
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: fee_payer_token_account.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: ctx.accounts.partner_signer.to_account_info(),
+                    },
+                ),
+                partner.integration_fee,
+            )?;
+        }
+
+        let accounts = ctx.accounts;
+        book_trade(
+            &accounts.config,
+            &mut accounts.trader_stats,
+            accounts.pair_volume.as_deref_mut(),
+            accounts.market_stats.as_deref_mut(),
+            accounts.registered_market.as_deref(),
+            accounts.season.as_deref(),
+            accounts.season_stats.as_deref_mut(),
+            accounts.leaderboard.as_ref(),
+            ctx.remaining_accounts,
+            volume,
+            execution_time,
+            pnl,
+            venue,
+            pair,
+            market,
+        )?;
+        let partner = &mut accounts.partner;
+        partner.trades_recorded = partner
+            .trades_recorded
+            .checked_add(1)
             .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
 
-        // Update average execution time using a simple weighted average.
-        let current_count = trader_stats.trade_count;
-        let new_count = current_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
-        trader_stats.average_execution_time = ((trader_stats.average_execution_time * current_count)
-            .checked_add(execution_time)
-            .ok_or(ErrorCode::Overflow)?)
-            .checked_div(new_count)
+    /// Records a trade attested by the configured trade oracle, such as an
+    /// off-chain matcher. Anyone may submit the attestation.
+    ///
+    /// The instruction immediately before this one must be an Ed25519
+    /// signature verification of the oracle over the Borsh-serialized
+    /// `attestation`. Attestations of a trader must be submitted in strictly
+    /// increasing `attested_at` order, which prevents replays.
+    pub fn record_attested_trade(
+        ctx: Context<RecordAttestedTrade>,
+        attestation: TradeAttestation,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        if config.is_paused(PAUSE_TRADING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        if config.trade_oracle == Pubkey::default() {
+            return Err(ErrorCode::AttestationsDisabled.into());
+        }
+
+        let instructions = ctx.accounts.instructions.to_account_info();
+        let current_index = load_current_index_checked(&instructions)?;
+        if current_index == 0 {
+            return Err(ErrorCode::InvalidAttestation.into());
+        }
+        let signature_ix = load_instruction_at_checked(current_index as usize - 1, &instructions)?;
+        verify_ed25519_instruction(&signature_ix, &config.trade_oracle, &attestation.try_to_vec()?)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        if attestation.attested_at <= trader_stats.last_attestation
+            || attestation.attested_at > current_time
+        {
+            return Err(ErrorCode::InvalidAttestation.into());
+        }
+        trader_stats.last_attestation = attestation.attested_at;
+        trader_stats.apply_trade(
+            config,
+            attestation.venue,
+            attestation.volume,
+            attestation.execution_time,
+            attestation.pnl,
+            current_time,
+        )?;
+        Ok(())
+    }
+
+    /// Opens an AMM swap receipt: snapshots the balance of the trader's source
+    /// token account before a Raydium/Orca swap in the same transaction.
+    pub fn begin_amm_swap(ctx: Context<BeginAmmSwap>) -> Result<()> {
+        let receipt = &mut ctx.accounts.amm_swap_receipt;
+        receipt.trader = ctx.accounts.trader.key();
+        receipt.source_token_account = ctx.accounts.source_token_account.key();
+        receipt.source_balance_before = ctx.accounts.source_token_account.amount;
+        receipt.begin_index =
+            load_current_index_checked(&ctx.accounts.instructions.to_account_info())?;
+        receipt.bump = ctx.bumps.amm_swap_receipt;
+        Ok(())
+    }
+
+    /// Credits the notional of a verified Raydium/Orca swap to TraderStats.
+    ///
+    /// Must follow `begin_amm_swap` for the same receipt in the same
+    /// transaction, with exactly one swap instruction of a supported AMM in
+    /// between; no other instruction in between may touch the snapshotted
+    /// source token account. The notional is the amount debited from the
+    /// source account, in whole source tokens with `NORMALIZED_VOLUME_DECIMALS`
+    /// decimals, or in USD with the same decimals when oracle normalization is
+    /// enabled, in which case the mint's price feed and a fresh Pyth price
+    /// update must be passed. Closes the receipt.
+    pub fn record_amm_swap(ctx: Context<RecordAmmSwap>, execution_time: u64) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_TRADING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+
+        let receipt = &ctx.accounts.amm_swap_receipt;
+        let instructions = ctx.accounts.instructions.to_account_info();
+        let current_index = load_current_index_checked(&instructions)?;
+        if current_index <= receipt.begin_index {
+            return Err(ErrorCode::AmmSwapNotFound.into());
+        }
+
+        // The receipt must have been opened by `begin_amm_swap` in this
+        // transaction, not left over from an earlier one.
+        let begin_ix = load_instruction_at_checked(receipt.begin_index as usize, &instructions)?;
+        if begin_ix.program_id != crate::ID
+            || !begin_ix
+                .data
+                .starts_with(&<instruction::BeginAmmSwap as anchor_lang::Discriminator>::DISCRIMINATOR)
+            || !begin_ix
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == ctx.accounts.amm_swap_receipt.key())
+        {
+            return Err(ErrorCode::AmmSwapReceiptMismatch.into());
+        }
+
+        // Exactly one supported AMM swap may touch the source account between
+        // the begin and record instructions; anything else touching it could
+        // debit the account and inflate the notional.
+        let mut swap_found = false;
+        for index in (receipt.begin_index + 1)..current_index {
+            let ix = load_instruction_at_checked(index as usize, &instructions)?;
+            if !ix
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == receipt.source_token_account)
+            {
+                continue;
+            }
+            let supported = ix.program_id == raydium_amm_v4::ID || ix.program_id == orca_whirlpool::ID;
+            if !supported || swap_found {
+                return Err(ErrorCode::UnexpectedSourceAccountUse.into());
+            }
+            swap_found = true;
+        }
+        if !swap_found {
+            return Err(ErrorCode::AmmSwapNotFound.into());
+        }
+
+        let notional = receipt
+            .source_balance_before
+            .checked_sub(ctx.accounts.source_token_account.amount)
+            .ok_or(ErrorCode::AmmSwapNotFound)?;
+        if notional == 0 {
+            return Err(ErrorCode::AmmSwapNotFound.into());
+        }
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let decimals = ctx.accounts.source_mint.decimals;
+        let notional = if config.max_price_age > 0 {
+            let (Some(price_feed), Some(price_update)) = (
+                ctx.accounts.price_feed.as_ref(),
+                ctx.accounts.price_update.as_ref(),
+            ) else {
+                return Err(ErrorCode::InvalidOracleAccount.into());
+            };
+            let price = read_pyth_price(price_update, &price_feed.feed_id)?;
+            price.validate(config, current_time)?;
+            price.normalize(notional, decimals)?
+        } else {
+            normalize_decimals(notional, decimals)?
+        };
+        ctx.accounts.trader_stats.apply_trade(
+            &ctx.accounts.config,
+            VenueType::Spot,
+            notional,
+            execution_time,
+            0,
+            current_time,
+        )?;
+        Ok(())
+    }
+
+    /// Creates a funding cursor for the trader's Drift user account, baselined
+    /// at the account's current cumulative perp funding.
+    pub fn init_funding_cursor(ctx: Context<InitFundingCursor>) -> Result<()> {
+        let trader = ctx.accounts.trader.key();
+        let cursor = &mut ctx.accounts.funding_cursor;
+        cursor.trader = trader;
+        cursor.venue_user = ctx.accounts.venue_user.key();
+        cursor.last_cumulative_funding =
+            read_drift_cumulative_funding(&ctx.accounts.venue_user, &trader)?;
+        cursor.bump = ctx.bumps.funding_cursor;
+        Ok(())
+    }
+
+    /// Applies perp funding received (positive) or paid (negative) since the
+    /// last sync to the trader's ranked P&L.
+    ///
+    /// `pnl_delta` must equal the change in the venue user account's cumulative
+    /// perp funding since the cursor was last updated.
+    pub fn record_funding(ctx: Context<RecordFunding>, pnl_delta: i64) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_TRADING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+
+        let cursor = &mut ctx.accounts.funding_cursor;
+        let cumulative_funding =
+            read_drift_cumulative_funding(&ctx.accounts.venue_user, &cursor.trader)?;
+        let delta = cumulative_funding
+            .checked_sub(cursor.last_cumulative_funding)
             .ok_or(ErrorCode::Overflow)?;
-        trader_stats.trade_count = new_count;
+        if delta != pnl_delta {
+            return Err(ErrorCode::FundingMismatch.into());
+        }
+        cursor.last_cumulative_funding = cumulative_funding;
 
-        // Update profit & loss.
+        let trader_stats = &mut ctx.accounts.trader_stats;
         trader_stats.pnl = trader_stats
             .pnl
-            .checked_add(pnl)
+            .checked_add(delta)
+            .ok_or(ErrorCode::Overflow)?;
+        trader_stats.perp_pnl = trader_stats
+            .perp_pnl
+            .checked_add(delta)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Creates the per-pair volume breakdown account for a trader.
+    pub fn init_pair_volume(ctx: Context<InitPairVolume>, pair: Pubkey) -> Result<()> {
+        let pair_volume = &mut ctx.accounts.pair_volume;
+        pair_volume.trader = ctx.accounts.trader.key();
+        pair_volume.pair = pair;
+        pair_volume.volume = 0;
+        pair_volume.trade_count = 0;
+        pair_volume.pnl = 0;
+        pair_volume.last_trade = 0;
+        pair_volume.bump = ctx.bumps.pair_volume;
+        Ok(())
+    }
+
+    /// Creates a trader's per-market stats, which rank the trader on
+    /// leaderboards scoped to `market`.
+    pub fn init_market_stats(ctx: Context<InitMarketStats>, market: Pubkey) -> Result<()> {
+        ctx.accounts.market_stats.set_inner(MarketStats {
+            trader: ctx.accounts.trader.key(),
+            market,
+            bump: ctx.bumps.market_stats,
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Links a secondary wallet to a primary trader identity. Both wallets sign.
+    ///
+    /// The primary wallet must not itself be linked to another identity, and
+    /// the secondary wallet must be neither linked nor a primary of others,
+    /// so identities never chain.
+    pub fn link_wallet(ctx: Context<LinkWallet>) -> Result<()> {
+        let primary = ctx.accounts.primary.key();
+        let secondary = ctx.accounts.secondary.key();
+        if primary == secondary {
+            return Err(ErrorCode::CannotLinkSelf.into());
+        }
+        if ctx.accounts.primary_stats.identity != Pubkey::default()
+            || ctx.accounts.secondary_stats.identity != Pubkey::default()
+        {
+            return Err(ErrorCode::WalletAlreadyLinked.into());
+        }
+        if ctx.accounts.secondary_stats.linked_wallets > 0 {
+            return Err(ErrorCode::WalletIsPrimary.into());
+        }
+        ctx.accounts.secondary_stats.identity = primary;
+        let primary_stats = &mut ctx.accounts.primary_stats;
+        primary_stats.linked_wallets = primary_stats
+            .linked_wallets
+            .checked_add(1)
             .ok_or(ErrorCode::Overflow)?;
 
-        trader_stats.last_trade = current_time;
+        let wallet_link = &mut ctx.accounts.wallet_link;
+        wallet_link.primary = primary;
+        wallet_link.secondary = secondary;
+        wallet_link.linked_at = Clock::get()?.unix_timestamp;
+        wallet_link.bump = ctx.bumps.wallet_link;
+        Ok(())
+    }
+
+    /// Unlinks a secondary wallet from its primary identity. Both wallets sign.
+    pub fn unlink_wallet(ctx: Context<UnlinkWallet>) -> Result<()> {
+        ctx.accounts.secondary_stats.identity = Pubkey::default();
+        let primary_stats = &mut ctx.accounts.primary_stats;
+        primary_stats.linked_wallets = primary_stats.linked_wallets.saturating_sub(1);
+        Ok(())
+    }
+
+    /// Authorizes `signer`, e.g. a bot's hot key, to sign `record_trade` on
+    /// the trader's behalf. Staking, claiming and other instructions still
+    /// need the trader's own wallet.
+    pub fn authorize_delegate(ctx: Context<AuthorizeDelegate>, signer: Pubkey) -> Result<()> {
+        ctx.accounts.delegate.set_inner(Delegate {
+            trader: ctx.accounts.trader.key(),
+            signer,
+            authorized_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.delegate,
+        });
+        Ok(())
+    }
 
+    /// Revokes a delegate key and refunds its rent to the trader.
+    pub fn revoke_delegate(_ctx: Context<RevokeDelegate>) -> Result<()> {
         Ok(())
     }
 
     /// Updates the leaderboard ranking based on trader stats.
     ///
-    /// Uses a composite score calculation:
-    /// - Base score: total_volume / (average_execution_time + 1)
+    /// Uses a composite score calculation, weighted by `config.scoring`:
+    /// - Base score: total_volume / (execution_time + 1), where the execution
+    ///   time is the average, median or p95 per `config.scoring`
     /// - Bonus: positive P&L and a staking bonus (staked_amount / 1000)
     ///
-    /// Enforces a minimum 10-minute interval between updates.
+    /// Entries sharing a trader key are aggregated into one, so stats of
+    /// linked wallets submitted under their primary wallet rank as one entity.
+    ///
+    /// Enforces `config.update_interval` between updates. Identities staking
+    /// less than `config.min_stake_for_ranking` are left unranked, with a
+    /// `TraderExcludedFromRanking` event. The TraderStats of banned traders
+    /// may be passed via `remaining_accounts` to drop their identities from
+    /// the ranking; bans also zero their reward weight at distribution.
+    /// If enabled in the config, emits a `ScoreBreakdownRecorded` event per
+    /// ranked trader. Only an admin or the configured operator may post
+    /// rankings.
     pub fn update_leaderboard(
         ctx: Context<UpdateLeaderboard>,
         trader_stats_list: Vec<TraderStatsInput>,
     ) -> Result<()> {
-        let leaderboard = &mut ctx.accounts.leaderboard;
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
         let current_time = Clock::get()?.unix_timestamp;
 
-        if current_time - leaderboard.last_update < 600 {
+        let config = &ctx.accounts.config;
+        if current_time - leaderboard.last_update < config.update_interval {
             return Err(ErrorCode::UpdateTooSoon.into());
         }
 
-        // Compute boosted score for each trader.
-        let mut ranked_traders: Vec<RankedTrader> = trader_stats_list
-            .into_iter()
-            .map(|ts| {
-                let base_score = ts.total_volume
-                    .checked_div(ts.average_execution_time.checked_add(1).unwrap())
-                    .unwrap_or(0);
-                let pnl_score = if ts.pnl > 0 { ts.pnl as u64 } else { 0 };
-                let staking_bonus = ts.staked_amount / 1000; // Bonus per 1000 $T2E staked
-                let score = base_score
-                    .checked_add(pnl_score)
-                    .unwrap_or(0)
-                    .checked_add(staking_bonus)
-                    .unwrap_or(0);
-                RankedTrader {
-                    trader: ts.trader,
-                    score,
-                }
+        let identities = exclude_understaked(
+            ctx.accounts.leaderboard.key(),
+            exclude_banned(ctx.remaining_accounts, merge_identities(trader_stats_list)?)?,
+            config.min_stake_for_ranking,
+            current_time,
+        );
+        if config.emit_score_breakdowns {
+            emit_score_breakdowns(
+                ctx.accounts.leaderboard.key(),
+                &identities,
+                &config.scoring.weights(),
+                current_time,
+            );
+        }
+        let ranked_traders = rank_traders(
+            identities,
+            &config.scoring.weights(),
+            config.score_half_life,
+            current_time,
+        )?;
+        leaderboard.apply_ranking(&ranked_traders, config, current_time);
+        emit_leaderboard_updated(ctx.accounts.leaderboard.key(), leaderboard, current_time);
+        Ok(())
+    }
+
+    /// Starts a staged ranking update of a leaderboard, for rankings too large
+    /// to submit in one transaction. Admin or operator only; only one staged
+    /// update per leaderboard can be open.
+    pub fn begin_leaderboard_update(ctx: Context<BeginLeaderboardUpdate>) -> Result<()> {
+        let pending = &mut ctx.accounts.pending_update.load_init()?;
+        pending.leaderboard = ctx.accounts.leaderboard.key();
+        pending.authority = ctx.accounts.authority.key();
+        pending.started_at = Clock::get()?.unix_timestamp;
+        pending.len = 0;
+        pending.next_page = 0;
+        Ok(())
+    }
+
+    /// Scores one page of trader stats into the staged update. Pages must be
+    /// submitted in order starting at 0, and every entry of an identity must
+    /// be in the same page. Identities below `config.min_stake_for_ranking`
+    /// are skipped. The account grows by the page's entries, with the rent
+    /// paid by the authority.
+    pub fn submit_ranking_page(
+        ctx: Context<SubmitRankingPage>,
+        page_index: u32,
+        entries: Vec<TraderStatsInput>,
+    ) -> Result<()> {
+        let identities = exclude_understaked(
+            ctx.accounts.pending_update.load()?.leaderboard,
+            merge_identities(entries)?,
+            ctx.accounts.config.min_stake_for_ranking,
+            Clock::get()?.unix_timestamp,
+        );
+        let (start, leaderboard, started_at) = {
+            let pending = &mut ctx.accounts.pending_update.load_mut()?;
+            if page_index != pending.next_page {
+                return Err(ErrorCode::InvalidPageIndex.into());
+            }
+            let start = pending.len as usize;
+            let len = start
+                .checked_add(identities.len())
+                .filter(|len| *len <= MAX_LEADERBOARD_ENTRIES as usize)
+                .ok_or(ErrorCode::LeaderboardCapacityExceeded)?;
+            pending.len = len as u32;
+            pending.next_page = pending.next_page.checked_add(1).ok_or(ErrorCode::Overflow)?;
+            (start, pending.leaderboard, pending.started_at)
+        };
+
+        let info = ctx.accounts.pending_update.to_account_info();
+        let len = start + identities.len();
+        let size = PendingUpdate::ENTRIES_OFFSET + len * std::mem::size_of::<PendingEntry>();
+        let rent = Rent::get()?.minimum_balance(size).saturating_sub(info.lamports());
+        if rent > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                rent,
+            )?;
+        }
+        info.realloc(size, false)?;
+
+        let config = &ctx.accounts.config;
+        let weights = config.scoring.weights();
+        let mut data = info.try_borrow_mut_data()?;
+        let staged = pending_entries(&mut data, len)?;
+        for (entry, ts) in staged[start..].iter_mut().zip(identities.iter()) {
+            *entry = PendingEntry {
+                trader: ts.trader,
+                // Every page decays as of the start of the update.
+                score: ts.decayed_score(&weights, config.score_half_life, started_at),
+                tiebreak: ts.tiebreak(),
+            };
+        }
+
+        if config.emit_score_breakdowns {
+            emit_score_breakdowns(leaderboard, &identities, &weights, Clock::get()?.unix_timestamp);
+        }
+        Ok(())
+    }
+
+    /// Sorts the staged entries and replaces the leaderboard ranking with
+    /// them, then closes the staged update. Enforces `config.update_interval`
+    /// and rejects identities submitted in more than one page.
+    pub fn commit_leaderboard_update(ctx: Context<CommitLeaderboardUpdate>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        if current_time - leaderboard.last_update < config.update_interval {
+            return Err(ErrorCode::UpdateTooSoon.into());
+        }
+
+        let len = ctx.accounts.pending_update.load()?.len as usize;
+        let info = ctx.accounts.pending_update.to_account_info();
+        let mut data = info.try_borrow_mut_data()?;
+        let staged = pending_entries(&mut data, len)?;
+        // Sorting in place keeps large rankings off the heap.
+        staged.sort_unstable_by_key(|entry| entry.trader);
+        if staged.windows(2).any(|pair| pair[0].trader == pair[1].trader) {
+            return Err(ErrorCode::DuplicateRankingEntry.into());
+        }
+        staged.sort_unstable_by_key(|entry| rank_key(&entry.trader, entry.score, &entry.tiebreak));
+        leaderboard.apply_staged_ranking(staged, config, current_time);
+        emit_leaderboard_updated(ctx.accounts.leaderboard.key(), leaderboard, current_time);
+        Ok(())
+    }
+
+    /// Discards a staged update, refunding its rent to the authority.
+    pub fn cancel_leaderboard_update(_ctx: Context<CancelLeaderboardUpdate>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Permissionlessly removes board entries of traders whose last trade is
+    /// older than the configured inactivity horizon.
+    ///
+    /// Expects the TraderStats of the traders to prune via `remaining_accounts`;
+    /// accounts of active or unranked traders are ignored.
+    pub fn prune_inactive(ctx: Context<PruneInactive>) -> Result<()> {
+        let horizon = ctx.accounts.config.inactivity_horizon;
+        if horizon == 0 {
+            return Err(ErrorCode::PruningDisabled.into());
+        }
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        // Pruning shifts ranks, which open flags and disputes refer to.
+        if leaderboard.pending_flags > 0 || leaderboard.pending_disputes > 0 {
+            return Err(ErrorCode::RankingFlagged.into());
+        }
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let cutoff = current_time - horizon;
+        let len_before = leaderboard.len;
+        for acc in ctx.remaining_accounts.iter() {
+            if acc.owner != &crate::ID {
+                return Err(ErrorCode::TraderStatsNotFound.into());
+            }
+            let data = acc.try_borrow_data()?;
+            let stats = TraderStats::try_deserialize(&mut &data[..])
+                .map_err(|_| ErrorCode::TraderStatsNotFound)?;
+            if stats.last_trade >= cutoff {
+                continue;
+            }
+            if let Some(index) = leaderboard.traders().iter().position(|t| *t == stats.trader) {
+                leaderboard.remove(index);
+            }
+        }
+        if leaderboard.len != len_before {
+            emit_leaderboard_updated(ctx.accounts.leaderboard.key(), leaderboard, current_time);
+        }
+        Ok(())
+    }
+
+    /// Creates the automation schedule of a leaderboard. `thread` is the
+    /// automation signer (e.g. a Clockwork thread PDA) allowed to crank it;
+    /// an interval of 0 disables that job. Admin only.
+    pub fn init_automation_schedule(
+        ctx: Context<InitAutomationSchedule>,
+        thread: Pubkey,
+        update_interval: i64,
+        snapshot_interval: i64,
+    ) -> Result<()> {
+        if update_interval < 0 || snapshot_interval < 0 {
+            return Err(ErrorCode::InvalidAutomationInterval.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        let schedule = &mut ctx.accounts.automation_schedule;
+        schedule.leaderboard = ctx.accounts.leaderboard.key();
+        schedule.thread = thread;
+        schedule.update_interval = update_interval;
+        schedule.snapshot_interval = snapshot_interval;
+        schedule.next_update = current_time;
+        schedule.next_snapshot = current_time;
+        schedule.bump = ctx.bumps.automation_schedule;
+        Ok(())
+    }
+
+    /// Scheduled leaderboard update, callable by the schedule's thread or a
+    /// keeper once due.
+    ///
+    /// Unlike `update_leaderboard`, the ranking is computed from the
+    /// TraderStats accounts passed via `remaining_accounts` rather than from
+    /// caller-supplied stats. Market-scoped leaderboards expect consecutive
+    /// (TraderStats, MarketStats) pairs instead.
+    pub fn crank_update_leaderboard(ctx: Context<CrankUpdateLeaderboard>) -> Result<()> {
+        let schedule = &mut ctx.accounts.automation_schedule;
+        let current_time = Clock::get()?.unix_timestamp;
+        if schedule.update_interval == 0 || current_time < schedule.next_update {
+            return Err(ErrorCode::AutomationNotDue.into());
+        }
+
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        let config = &ctx.accounts.config;
+        if current_time - leaderboard.last_update < config.update_interval {
+            return Err(ErrorCode::UpdateTooSoon.into());
+        }
+
+        let inputs = if leaderboard.market == Pubkey::default() {
+            load_trader_stats_inputs(ctx.remaining_accounts, &config.scoring, current_time)?
+        } else {
+            load_market_stats_inputs(ctx.remaining_accounts, &leaderboard.market, &config.scoring, current_time)?
+        };
+        let identities = exclude_understaked(
+            ctx.accounts.leaderboard.key(),
+            merge_identities(inputs)?,
+            config.min_stake_for_ranking,
+            current_time,
+        );
+        if config.emit_score_breakdowns {
+            emit_score_breakdowns(
+                ctx.accounts.leaderboard.key(),
+                &identities,
+                &config.scoring.weights(),
+                current_time,
+            );
+        }
+        let ranked_traders = rank_traders(
+            identities,
+            &config.scoring.weights(),
+            config.score_half_life,
+            current_time,
+        )?;
+        leaderboard.apply_ranking(&ranked_traders, config, current_time);
+        emit_leaderboard_updated(ctx.accounts.leaderboard.key(), leaderboard, current_time);
+        schedule.next_update = current_time
+            .checked_add(schedule.update_interval)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Scheduled leaderboard snapshot, callable by the schedule's thread or a
+    /// keeper once due.
+    pub fn crank_snapshot(ctx: Context<CrankSnapshot>) -> Result<()> {
+        let schedule = &mut ctx.accounts.automation_schedule;
+        let current_time = Clock::get()?.unix_timestamp;
+        if schedule.snapshot_interval == 0 || current_time < schedule.next_snapshot {
+            return Err(ErrorCode::AutomationNotDue.into());
+        }
+
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        ctx.accounts.snapshot.set_inner(LeaderboardSnapshot::capture(
+            ctx.accounts.leaderboard.key(),
+            leaderboard,
+            current_time,
+            ctx.bumps.snapshot,
+        ));
+        ctx.accounts.snapshot.emit_taken(ctx.accounts.snapshot.key());
+        leaderboard.history_count = leaderboard
+            .history_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        schedule.next_snapshot = current_time
+            .checked_add(schedule.snapshot_interval)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Dry-runs a ranking over the TraderStats accounts passed via
+    /// `remaining_accounts`, using the configured scoring weights, and returns
+    /// the top `MAX_PAGE_SIZE` entries. Does not touch the leaderboard.
+    pub fn preview_scores(ctx: Context<PreviewScores>) -> Result<LeaderboardPage> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let inputs = load_trader_stats_inputs(ctx.remaining_accounts, &config.scoring, current_time)?;
+        let ranked_traders = rank_traders(
+            inputs,
+            &config.scoring.weights(),
+            config.score_half_life,
+            current_time,
+        )?;
+        let entries = ranked_traders
+            .iter()
+            .enumerate()
+            .take(MAX_PAGE_SIZE as usize)
+            .map(|(rank, rt)| LeaderboardEntry {
+                rank: rank as u32,
+                trader: rt.trader,
+                score: rt.score,
             })
             .collect();
+        Ok(LeaderboardPage {
+            total: ranked_traders.len() as u32,
+            offset: 0,
+            entries,
+        })
+    }
+
+    /// Projects a trader's reward if `distribute_rewards(top_n)` ran now
+    /// against the current ranking.
+    ///
+    /// The trader's own season pass and loyalty boosts are applied; other
+    /// winners are assumed unboosted, so the result is an estimate.
+    pub fn preview_payout(
+        ctx: Context<PreviewPayout>,
+        top_n: u64,
+    ) -> Result<PayoutPreview> {
+        let leaderboard = &load_board(&ctx.accounts.leaderboard)?;
+        let trader_stats = &ctx.accounts.trader_stats;
+        let mut preview = PayoutPreview {
+            trader: trader_stats.trader,
+            ranked: false,
+            rank: 0,
+            share_bps: 0,
+            estimated_amount: 0,
+        };
+
+        let Some(rank) = leaderboard.traders().iter().position(|t| *t == trader_stats.identity()) else {
+            return Ok(preview);
+        };
+        preview.ranked = true;
+        preview.rank = rank as u32;
+        let current_time = Clock::get()?.unix_timestamp;
+        let num_traders = leaderboard.traders().len().min(top_n as usize);
+        if rank >= num_traders || trader_stats.is_excluded(current_time) {
+            return Ok(preview);
+        }
+
+        let own_weight = t2e_core::boosted_weight(
+            leaderboard.ranking_scores()[rank],
+            trader_stats.reward_boost_bps(&ctx.accounts.config),
+        )
+        .ok_or(ErrorCode::Overflow)?;
+        let mut total_weight = own_weight;
+        for (i, score) in leaderboard.ranking_scores().iter().take(num_traders).enumerate() {
+            if i != rank {
+                total_weight = total_weight
+                    .checked_add(*score as u128)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+        }
+        if total_weight == 0 {
+            return Ok(preview);
+        }
+
+        let adjusted_reward = ctx
+            .accounts
+            .config
+            .adjusted_reward(ctx.accounts.emission_schedule.as_deref(), current_time)?;
+        preview.share_bps = (own_weight * 10_000 / total_weight) as u16;
+        preview.estimated_amount = (own_weight
+            .checked_mul(adjusted_reward as u128)
+            .ok_or(ErrorCode::Overflow)?
+            / total_weight) as u64;
+        Ok(preview)
+    }
+
+    /// Records an immutable, sequenced snapshot of the current ranking that a
+    /// distribution can later be bound to. Operators and keepers only.
+    pub fn create_ranking_snapshot(ctx: Context<CreateRankingSnapshot>) -> Result<()> {
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        let snapshot = &mut ctx.accounts.ranking_snapshot;
+        snapshot.leaderboard = ctx.accounts.leaderboard.key();
+        snapshot.sequence = leaderboard.snapshot_sequence;
+        snapshot.ranking_update = leaderboard.last_update;
+        snapshot.board_hash = leaderboard.board_hash();
+        snapshot.traders_ranked = leaderboard.len;
+        snapshot.created_at = Clock::get()?.unix_timestamp;
+        snapshot.paid_at = 0;
+        snapshot.amount_paid = 0;
+        snapshot.bump = ctx.bumps.ranking_snapshot;
+
+        leaderboard.snapshot_sequence = leaderboard
+            .snapshot_sequence
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Sets the window during which rewards of emission `epoch` can be
+    /// claimed. Claims outside `[open_ts, close_ts)` are rejected, and
+    /// unclaimed rewards become sweepable after `close_ts`. Admin only.
+    pub fn set_claim_window(
+        ctx: Context<SetClaimWindow>,
+        epoch: i64,
+        open_ts: i64,
+        close_ts: i64,
+    ) -> Result<()> {
+        if close_ts <= open_ts {
+            return Err(ErrorCode::InvalidClaimWindow.into());
+        }
+        let claim_window = &mut ctx.accounts.claim_window;
+        claim_window.epoch = epoch;
+        claim_window.open_ts = open_ts;
+        claim_window.close_ts = close_ts;
+        claim_window.bump = ctx.bumps.claim_window;
+        Ok(())
+    }
 
-        // Sort traders in descending order based on their boosted score.
-        ranked_traders.sort_by(|a, b| b.score.cmp(&a.score));
+    /// Creates the reward vault of a reward mint, a `["reward_vault", mint]`
+    /// token account owned by the vault authority PDA, so distributions can
+    /// be cranked without a hot-wallet signer. Admin only.
+    pub fn init_reward_vault(_ctx: Context<InitRewardVault>) -> Result<()> {
+        Ok(())
+    }
 
-        // Update leaderboard with ordered traders and their corresponding scores.
-        leaderboard.traders = ranked_traders.iter().map(|rt| rt.trader).collect();
-        leaderboard.ranking_scores = ranked_traders.iter().map(|rt| rt.score).collect();
-        leaderboard.last_update = current_time;
+    /// Deposits `amount` from the funder's token account into the reward
+    /// vault. Permissionless.
+    pub fn fund_reward_vault<'info>(
+        ctx: Context<'_, '_, '_, 'info, FundRewardVault<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.funder_token_account.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.funder.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.reward_mint.decimals,
+            &[],
+        )?;
+        emit!(RewardVaultFunded {
+            reward_vault: ctx.accounts.reward_vault.key(),
+            funder: ctx.accounts.funder.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
     /// Distributes $T2E rewards to the top N traders.
     ///
-    /// Rewards are scaled proportionally to each trader's ranking score.
-    /// A reward halving mechanism reduces the total reward pool over time.
+    /// Rewards are scaled proportionally to each trader's ranking score. The
+    /// pool is `config.reward_pool`, reduced over time by the reward halving.
+    ///
+    /// Only allowed once the fraud-proof window following the last update has
+    /// elapsed and no flag against the ranking is pending review, and, if
+    /// `max_ranking_age` is set, while the ranking is still recent enough.
+    ///
+    /// Each distribution is bound to an unpaid `RankingSnapshot` whose hash
+    /// matches the current board, which records what was paid.
     ///
-    /// Expects each top trader’s token account to be provided via `remaining_accounts`.
+    /// Permissionless, as the reward vault is owned by the vault authority PDA.
+    ///
+    /// Expects each top trader's TraderStats and token account to be provided via
+    /// `remaining_accounts` as consecutive pairs, in ranking order. Token
+    /// accounts paid out must be owned by the trader and hold the reward mint.
+    ///
+    /// The reward mint may be a Token-2022 mint with transfer fees, in which
+    /// case auto-staked and swap payouts are credited net of the fee. Mints
+    /// with a transfer hook are rejected, as payouts pass no extra accounts.
   pub fn distribute_rewards<'info>(
-    ctx: Context<'_, '_, '_, 'info, DistributeRewards<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, DistributeRewards<'info>>,
     top_n: u64,
-    reward_amount: u64, // Total reward pool amount.
 ) -> Result<()> {
-    let leaderboard = &ctx.accounts.leaderboard;
+    reject_transfer_hook(&ctx.accounts.reward_mint.to_account_info())?;
+    let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+    let current_time = Clock::get()?.unix_timestamp;
+    check_payable(
+        leaderboard,
+        &ctx.accounts.config,
+        &ctx.accounts.ranking_snapshot,
+        current_time,
+    )?;
+
+    let current_epoch = t2e_core::emission_epoch(current_time);
+    let adjusted_reward = ctx
+        .accounts
+        .config
+        .adjusted_reward(ctx.accounts.emission_schedule.as_deref(), current_time)?;
+
+    // Load each top trader's stats (passed as TraderStats/token account pairs).
+    let top_n_usize = top_n as usize;
+    let num_traders = leaderboard.traders().len().min(top_n_usize);
+    let mut winners: Vec<Account<'info, TraderStats>> = Vec::with_capacity(num_traders);
+    for (i, trader) in leaderboard.traders().iter().take(num_traders).enumerate() {
+        let trader_stats_info = ctx
+            .remaining_accounts
+            .get(2 * i)
+            .ok_or(ErrorCode::TraderStatsNotFound)?;
+        let trader_stats = Account::<TraderStats>::try_from(trader_stats_info)?;
+        if trader_stats.trader != *trader {
+            return Err(ErrorCode::TraderStatsNotFound.into());
+        }
+        winners.push(trader_stats);
+    }
+
+    // Scale each trader's reward by their boosted weight, so the vault is
+    // debited exactly `adjusted_reward` including the treasury's remainder.
+    let config = &ctx.accounts.config;
+    let weights = reward_weights(
+        leaderboard.ranking_scores(),
+        winners.iter().map(|w| &**w),
+        config,
+        current_time,
+    )?;
+    let treasury_configured = config.treasury != Pubkey::default();
+    let (rewards, treasury_cut) = split_pool(&weights, adjusted_reward, treasury_configured)?;
+    let vault_balance_before = ctx.accounts.reward_vault.amount;
+
+    let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+
+    // Loop over the top traders and distribute their rewards, routing each
+    // payout according to the trader's payout preference.
+    for (i, mut trader_stats) in winners.into_iter().enumerate() {
+        let trader_reward = rewards[i];
+        let trader_token_account = ctx
+            .remaining_accounts
+            .get(2 * i + 1)
+            .ok_or(ErrorCode::TraderTokenAccountNotFound)?;
+
+        let received = net_of_transfer_fee(&ctx.accounts.reward_mint.to_account_info(), trader_reward)?;
+        let destination = match trader_stats.credit_payout(received, config, current_time)? {
+            PayoutPreference::T2e => {
+                // The leaderboard stores wallets, so check the token account
+                // belongs to the ranked trader and holds the reward mint.
+                let token_account =
+                    InterfaceAccount::<token_interface::TokenAccount>::try_from(trader_token_account)?;
+                if token_account.owner != trader_stats.trader
+                    || token_account.mint != ctx.accounts.reward_vault.mint
+                {
+                    return Err(ErrorCode::InvalidTraderTokenAccount.into());
+                }
+                trader_token_account.to_account_info()
+            }
+            PayoutPreference::AutoStake => ctx.accounts.staking_vault.to_account_info(),
+            PayoutPreference::UsdcViaSwap => ctx.accounts.swap_escrow.to_account_info(),
+        };
+
+        // Redeemed loyalty boosts apply to a single payout.
+        trader_stats.loyalty_boost_bps = 0;
+        if i < ACHIEVEMENT_TOP_RANKS {
+            trader_stats.achievements |= Achievement::TopTenFinish.bit();
+        }
+
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            destination,
+            ctx.accounts.vault_authority.to_account_info(),
+            &[],
+            trader_reward,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )?;
+        emit!(RewardsDistributed {
+            leaderboard: ctx.accounts.leaderboard.key(),
+            trader: trader_stats.trader,
+            rank: i as u32,
+            amount: trader_reward,
+            payout: trader_stats.payout_preference,
+            deferred: false,
+            epoch: current_epoch,
+            timestamp: current_time,
+        });
+
+        trader_stats.exit(&crate::ID)?;
+    }
+
+    if treasury_cut > 0 {
+        let treasury = ctx
+            .accounts
+            .treasury
+            .as_ref()
+            .ok_or(ErrorCode::TreasuryNotProvided)?;
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            treasury.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            &[],
+            treasury_cut,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )?;
+    }
+
+    // The vault must have been debited exactly the adjusted reward.
+    ctx.accounts.reward_vault.reload()?;
+    let vault_debit = vault_balance_before
+        .checked_sub(ctx.accounts.reward_vault.amount)
+        .ok_or(ErrorCode::Overflow)?;
+    if vault_debit != adjusted_reward {
+        return Err(ErrorCode::DistributionAccountingMismatch.into());
+    }
+
+    let snapshot = &mut ctx.accounts.ranking_snapshot;
+    snapshot.paid_at = current_time;
+    snapshot.amount_paid = adjusted_reward;
+    leaderboard.last_paid_update = leaderboard.last_update;
+
+    // Emit one summary of the finalized epoch for indexers and alerting.
+    emit_epoch_summary(
+        ctx.accounts.leaderboard.key(),
+        leaderboard,
+        current_epoch,
+        num_traders,
+        adjusted_reward - treasury_cut,
+        ctx.accounts.config.burned_in_epoch(current_epoch),
+        treasury_cut,
+        current_time,
+    )
+}
+
+    /// Creates the reward claim account of a trader on a leaderboard, into
+    /// which `finalize_epoch` credits rewards. Anyone may pay for it.
+    pub fn init_reward_claim(ctx: Context<InitRewardClaim>) -> Result<()> {
+        let reward_claim = &mut ctx.accounts.reward_claim;
+        reward_claim.leaderboard = ctx.accounts.leaderboard.key();
+        reward_claim.trader = ctx.accounts.trader_stats.trader;
+        reward_claim.claimable = 0;
+        reward_claim.epoch = 0;
+        reward_claim.claimed_total = 0;
+        reward_claim.bump = ctx.bumps.reward_claim;
+        Ok(())
+    }
+
+    /// Creates the vesting schedule of a trader on a leaderboard, into which
+    /// `finalize_epoch` credits rewards while vesting is enabled. Anyone may
+    /// pay for it.
+    pub fn init_vesting_schedule(ctx: Context<InitVestingSchedule>) -> Result<()> {
+        let schedule = &mut ctx.accounts.vesting_schedule;
+        schedule.leaderboard = ctx.accounts.leaderboard.key();
+        schedule.trader = ctx.accounts.trader_stats.trader;
+        schedule.total = 0;
+        schedule.released = 0;
+        schedule.start_ts = 0;
+        schedule.cliff = 0;
+        schedule.duration = 0;
+        schedule.bump = ctx.bumps.vesting_schedule;
+        Ok(())
+    }
+
+    /// Pull-based alternative to `distribute_rewards`: computes the same
+    /// payouts, but moves the pool into the leaderboard's claim vault and
+    /// credits each winner's `RewardClaim` instead of paying every winner in
+    /// one transaction. Winners then call `claim_rewards` themselves.
+    ///
+    /// Subject to the same checks as `distribute_rewards`. Expects each top
+    /// trader's TraderStats and RewardClaim via `remaining_accounts` as
+    /// consecutive pairs, in ranking order. The rounding remainder goes to
+    /// the treasury if one is configured.
+    ///
+    /// While vesting is enabled, each pair is followed by the trader's
+    /// VestingSchedule, which receives the reward instead; see
+    /// `VestingSchedule::restart`.
+    ///
+    /// While `config.referral_reward_bps` is set, each trader's accounts are
+    /// further followed by their Referral PDA and the referrer's RewardClaim.
+    /// If the Referral exists, that share of the reward is credited to the
+    /// referrer's claim; if the PDA has not been created, both slots are
+    /// ignored.
+    ///
+    /// With a fee-bearing Token-2022 reward mint, the reward vault also covers
+    /// the fee on funding the claim vault, so the full claim total arrives.
+    /// Mints with a transfer hook are rejected.
+    pub fn finalize_epoch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeEpoch<'info>>,
+        top_n: u64,
+    ) -> Result<()> {
+        reject_transfer_hook(&ctx.accounts.reward_mint.to_account_info())?;
+        let leaderboard_key = ctx.accounts.leaderboard.key();
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        let config = &ctx.accounts.config;
+        let current_time = Clock::get()?.unix_timestamp;
+        check_payable(leaderboard, config, &ctx.accounts.ranking_snapshot, current_time)?;
+
+        let current_epoch = t2e_core::emission_epoch(current_time);
+        let adjusted_reward =
+            config.adjusted_reward(ctx.accounts.emission_schedule.as_deref(), current_time)?;
+
+        let num_traders = leaderboard.traders().len().min(top_n as usize);
+        let vesting = config.vesting_duration > 0;
+        let referral_offset = if vesting { 3 } else { 2 };
+        let routing = config.referral_reward_bps > 0;
+        let stride = if routing { referral_offset + 2 } else { referral_offset };
+        let mut winners = Vec::with_capacity(num_traders);
+        for (i, trader) in leaderboard.traders().iter().take(num_traders).enumerate() {
+            let trader_stats_info = ctx
+                .remaining_accounts
+                .get(stride * i)
+                .ok_or(ErrorCode::TraderStatsNotFound)?;
+            let trader_stats = Account::<TraderStats>::try_from(trader_stats_info)?;
+            let reward_claim_info = ctx
+                .remaining_accounts
+                .get(stride * i + 1)
+                .ok_or(ErrorCode::RewardClaimNotFound)?;
+            let reward_claim = Account::<RewardClaim>::try_from(reward_claim_info)?;
+            if trader_stats.trader != *trader {
+                return Err(ErrorCode::TraderStatsNotFound.into());
+            }
+            if reward_claim.trader != *trader || reward_claim.leaderboard != leaderboard_key {
+                return Err(ErrorCode::RewardClaimNotFound.into());
+            }
+            let vesting_schedule = if vesting {
+                let schedule_info = ctx
+                    .remaining_accounts
+                    .get(stride * i + 2)
+                    .ok_or(ErrorCode::VestingScheduleNotFound)?;
+                let schedule = Account::<VestingSchedule>::try_from(schedule_info)?;
+                if schedule.trader != *trader || schedule.leaderboard != leaderboard_key {
+                    return Err(ErrorCode::VestingScheduleNotFound.into());
+                }
+                Some(schedule)
+            } else {
+                None
+            };
+            let referral = if routing {
+                let referral_info = ctx
+                    .remaining_accounts
+                    .get(stride * i + referral_offset)
+                    .ok_or(ErrorCode::ReferralNotFound)?;
+                let referrer_claim_info = ctx
+                    .remaining_accounts
+                    .get(stride * i + referral_offset + 1)
+                    .ok_or(ErrorCode::RewardClaimNotFound)?;
+                // The slot must be the trader's Referral PDA, so the cut
+                // cannot be skipped by passing some other account.
+                let (referral_key, _) =
+                    Pubkey::find_program_address(&[b"referral", trader.as_ref()], &crate::ID);
+                if referral_info.key() != referral_key {
+                    return Err(ErrorCode::ReferralNotFound.into());
+                }
+                if referral_info.owner == &crate::ID {
+                    let referral = Account::<Referral>::try_from(referral_info)?;
+                    if referral.trader != *trader {
+                        return Err(ErrorCode::ReferralNotFound.into());
+                    }
+                    let referrer_claim = Account::<RewardClaim>::try_from(referrer_claim_info)?;
+                    if referrer_claim.trader != referral.referrer
+                        || referrer_claim.leaderboard != leaderboard_key
+                    {
+                        return Err(ErrorCode::RewardClaimNotFound.into());
+                    }
+                    Some((referral, referrer_claim_info))
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+            winners.push((trader_stats, reward_claim, vesting_schedule, referral));
+        }
+
+        let weights = reward_weights(
+            leaderboard.ranking_scores(),
+            winners.iter().map(|(trader_stats, _, _, _)| &**trader_stats),
+            config,
+            current_time,
+        )?;
+        let treasury_configured = config.treasury != Pubkey::default();
+        let (rewards, treasury_cut) = split_pool(&weights, adjusted_reward, treasury_configured)?;
+
+        // Referrer credits are applied once all winner claims are written, so
+        // a referrer who is also a winner, or refers several, is not clobbered.
+        let mut referrer_credits = Vec::new();
+        for (i, (mut trader_stats, mut reward_claim, vesting_schedule, referral)) in
+            winners.into_iter().enumerate()
+        {
+            // Redeemed loyalty boosts apply to a single payout.
+            trader_stats.loyalty_boost_bps = 0;
+            if i < ACHIEVEMENT_TOP_RANKS {
+                trader_stats.achievements |= Achievement::TopTenFinish.bit();
+            }
+            trader_stats.exit(&crate::ID)?;
+            if rewards[i] == 0 {
+                continue;
+            }
+            let mut reward = rewards[i];
+            if let Some((mut referral, referrer_claim_info)) = referral {
+                let cut = (reward as u128 * config.referral_reward_bps as u128 / 10_000) as u64;
+                reward -= cut;
+                referral.rewards_routed = referral
+                    .rewards_routed
+                    .checked_add(cut)
+                    .ok_or(ErrorCode::Overflow)?;
+                referral.exit(&crate::ID)?;
+                referrer_credits.push((referrer_claim_info, cut));
+            }
+            let claimable = match vesting_schedule {
+                Some(mut schedule) => {
+                    let unlocked = schedule.restart(reward, config, current_time)?;
+                    schedule.exit(&crate::ID)?;
+                    unlocked
+                }
+                None => reward,
+            };
+            reward_claim.claimable = reward_claim
+                .claimable
+                .checked_add(claimable)
+                .ok_or(ErrorCode::Overflow)?;
+            reward_claim.epoch = current_epoch;
+            reward_claim.exit(&crate::ID)?;
+            emit!(RewardsDistributed {
+                leaderboard: leaderboard_key,
+                trader: trader_stats.trader,
+                rank: i as u32,
+                amount: reward,
+                payout: trader_stats.payout_preference,
+                deferred: true,
+                epoch: current_epoch,
+                timestamp: current_time,
+            });
+        }
+        for (referrer_claim_info, cut) in referrer_credits {
+            let mut referrer_claim = Account::<RewardClaim>::try_from(referrer_claim_info)?;
+            referrer_claim.claimable = referrer_claim
+                .claimable
+                .checked_add(cut)
+                .ok_or(ErrorCode::Overflow)?;
+            referrer_claim.epoch = current_epoch;
+            referrer_claim.exit(&crate::ID)?;
+        }
+
+        let claim_total = adjusted_reward - treasury_cut;
+        let claim_funding =
+            gross_of_transfer_fee(&ctx.accounts.reward_mint.to_account_info(), claim_total)?;
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.claim_vault.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            &[],
+            claim_funding,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )?;
+        if treasury_cut > 0 {
+            let treasury = ctx
+                .accounts
+                .treasury
+                .as_ref()
+                .ok_or(ErrorCode::TreasuryNotProvided)?;
+            transfer_checked_with_hooks(
+                &ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.reward_vault.to_account_info(),
+                ctx.accounts.reward_mint.to_account_info(),
+                treasury.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                &[],
+                treasury_cut,
+                ctx.accounts.reward_mint.decimals,
+                signer_seeds,
+            )?;
+        }
+
+        let snapshot = &mut ctx.accounts.ranking_snapshot;
+        snapshot.paid_at = current_time;
+        snapshot.amount_paid = adjusted_reward;
+        leaderboard.last_paid_update = leaderboard.last_update;
+
+        emit_epoch_summary(
+            leaderboard_key,
+            leaderboard,
+            current_epoch,
+            num_traders,
+            claim_total,
+            config.burned_in_epoch(current_epoch),
+            treasury_cut,
+            current_time,
+        )
+    }
+
+    /// Pulls the trader's claimable rewards from the claim vault, routed by
+    /// their payout preference, or into the staking vault with `compound`.
+    ///
+    /// If a `ClaimWindow` exists for the emission epoch of the latest credit,
+    /// the claim must fall within it.
+    pub fn claim_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>,
+        compound: bool,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        if config.is_paused(PAUSE_REWARDS) || (compound && config.is_paused(PAUSE_STAKING)) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let reward_claim = &mut ctx.accounts.reward_claim;
+        let amount = reward_claim.claimable;
+        if amount == 0 {
+            return Err(ErrorCode::NothingToClaim.into());
+        }
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let claim_window_info = ctx.accounts.claim_window.to_account_info();
+        if claim_window_info.owner == &crate::ID {
+            let data = claim_window_info.try_borrow_data()?;
+            let claim_window = ClaimWindow::try_deserialize(&mut &data[..])?;
+            claim_window.require_open(current_time)?;
+        }
+
+        reward_claim.claimable = 0;
+        reward_claim.claimed_total = reward_claim
+            .claimed_total
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // Credit what arrives, net of any transfer fee.
+        let received = net_of_transfer_fee(&ctx.accounts.reward_mint.to_account_info(), amount)?;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        let preference = if compound {
+            trader_stats.compound(received, &ctx.accounts.config, current_time)?;
+            PayoutPreference::AutoStake
+        } else {
+            trader_stats.credit_payout(received, &ctx.accounts.config, current_time)?
+        };
+        let destination = match preference {
+            PayoutPreference::T2e => ctx.accounts.trader_token_account.to_account_info(),
+            PayoutPreference::AutoStake => ctx.accounts.staking_vault.to_account_info(),
+            PayoutPreference::UsdcViaSwap => ctx.accounts.swap_escrow.to_account_info(),
+        };
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.claim_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            destination,
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Releases the unlocked portion of the trader's vesting schedule from the
+    /// claim vault to the trader's token account.
+    pub fn claim_vested<'info>(ctx: Context<'_, '_, '_, 'info, ClaimVested<'info>>) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_REWARDS) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let schedule = &mut ctx.accounts.vesting_schedule;
+        let amount = schedule.releasable(Clock::get()?.unix_timestamp);
+        if amount == 0 {
+            return Err(ErrorCode::NothingToClaim.into());
+        }
+        schedule.released = schedule
+            .released
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.claim_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Permissionlessly sweeps a reward claim left unclaimed past the close of
+    /// its epoch's claim window from the claim vault to the treasury.
+    pub fn sweep_expired_claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, SweepExpiredClaim<'info>>,
+    ) -> Result<()> {
+        if !ctx
+            .accounts
+            .claim_window
+            .is_expired(Clock::get()?.unix_timestamp)
+        {
+            return Err(ErrorCode::ClaimWindowNotExpired.into());
+        }
+        let reward_claim = &mut ctx.accounts.reward_claim;
+        let amount = reward_claim.claimable;
+        if amount == 0 {
+            return Err(ErrorCode::NothingToClaim.into());
+        }
+        reward_claim.claimable = 0;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.claim_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Posts the Merkle root of an off-chain computed payout of `epoch` for
+    /// large leaderboards, funding the claim vault with `total_amount` from
+    /// the reward vault. Admin only.
+    ///
+    /// Leaves are `sha256(0x00 || trader || amount_le)` and inner nodes
+    /// `sha256(0x01 || min(a, b) || max(a, b))`.
+    ///
+    /// As in `finalize_epoch`, any transfer fee on funding the claim vault is
+    /// paid by the reward vault on top of `total_amount`.
+    pub fn post_reward_root<'info>(
+        ctx: Context<'_, '_, '_, 'info, PostRewardRoot<'info>>,
+        epoch: u64,
+        root: [u8; 32],
+        total_amount: u64,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_REWARDS) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let reward_root = &mut ctx.accounts.reward_root;
+        reward_root.leaderboard = ctx.accounts.leaderboard.key();
+        reward_root.epoch = epoch;
+        reward_root.root = root;
+        reward_root.total_amount = total_amount;
+        reward_root.claimed_amount = 0;
+        reward_root.bump = ctx.bumps.reward_root;
+
+        let funding = gross_of_transfer_fee(&ctx.accounts.reward_mint.to_account_info(), total_amount)?;
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.claim_vault.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            funding,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Claims `amount` from a posted reward root by proving the leaf
+    /// `(trader, amount)`. A receipt PDA per (root, trader) prevents double
+    /// claims. The payout is routed by the trader's payout preference.
+    pub fn claim_with_proof<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimWithProof<'info>>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_REWARDS) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let trader = ctx.accounts.trader.key();
+        let reward_root = &mut ctx.accounts.reward_root;
+        if !verify_reward_proof(&proof, &reward_root.root, &trader, amount) {
+            return Err(ErrorCode::InvalidMerkleProof.into());
+        }
+        reward_root.claimed_amount = reward_root
+            .claimed_amount
+            .checked_add(amount)
+            .filter(|claimed| *claimed <= reward_root.total_amount)
+            .ok_or(ErrorCode::InvalidMerkleProof)?;
+
+        let receipt = &mut ctx.accounts.claim_receipt;
+        receipt.reward_root = reward_root.key();
+        receipt.trader = trader;
+        receipt.amount = amount;
+        receipt.claimed_at = Clock::get()?.unix_timestamp;
+
+        let received = net_of_transfer_fee(&ctx.accounts.reward_mint.to_account_info(), amount)?;
+        let destination = match ctx.accounts.trader_stats.credit_payout(
+            received,
+            &ctx.accounts.config,
+            receipt.claimed_at,
+        )? {
+            PayoutPreference::T2e => ctx.accounts.trader_token_account.to_account_info(),
+            PayoutPreference::AutoStake => ctx.accounts.staking_vault.to_account_info(),
+            PayoutPreference::UsdcViaSwap => ctx.accounts.swap_escrow.to_account_info(),
+        };
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.claim_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            destination,
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Configures the season pass. Super-authority only.
+    ///
+    /// - `current_season`: season for which passes are sold and honored (1-based).
+    /// - `price`: pass price in $T2E; 0 disables sales and boosts.
+    /// - `boost_bps`: reward-share boost for pass holders.
+    /// - `treasury_bps`: share of proceeds routed to the treasury; the rest
+    ///   goes to the prize pool (reward vault).
+    pub fn set_season_pass_params(
+        ctx: Context<UpdateConfig>,
+        current_season: u32,
+        price: u64,
+        boost_bps: u16,
+        treasury_bps: u16,
+    ) -> Result<()> {
+        if treasury_bps > 10_000 {
+            return Err(ErrorCode::InvalidBps.into());
+        }
+        if price > 0 && current_season == 0 {
+            return Err(ErrorCode::InvalidSeason.into());
+        }
+        let config = &mut ctx.accounts.config;
+        config.current_season = current_season;
+        config.season_pass_price = price;
+        config.season_pass_boost_bps = boost_bps;
+        config.season_pass_treasury_bps = treasury_bps;
+        Ok(())
+    }
+
+    /// Purchases the premium season pass for the current season.
+    ///
+    /// Proceeds are split between the prize pool and the treasury per config.
+    /// The pass boosts the holder's reward share and carries cosmetic flags.
+    pub fn purchase_season_pass<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseSeasonPass<'info>>,
+        cosmetic_flags: u8,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        if config.season_pass_price == 0 {
+            return Err(ErrorCode::SeasonPassUnavailable.into());
+        }
+
+        let price = config.season_pass_price;
+        let treasury_cut = if config.treasury == Pubkey::default() {
+            0
+        } else {
+            ((price as u128) * (config.season_pass_treasury_bps as u128) / 10_000) as u64
+        };
+        let prize_pool_cut = price - treasury_cut;
+
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.trader.to_account_info(),
+            ctx.remaining_accounts,
+            prize_pool_cut,
+            ctx.accounts.reward_mint.decimals,
+            &[],
+        )?;
+        if treasury_cut > 0 {
+            let treasury = ctx
+                .accounts
+                .treasury
+                .as_ref()
+                .ok_or(ErrorCode::TreasuryNotProvided)?;
+            transfer_checked_with_hooks(
+                &ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.trader_token_account.to_account_info(),
+                ctx.accounts.reward_mint.to_account_info(),
+                treasury.to_account_info(),
+                ctx.accounts.trader.to_account_info(),
+                ctx.remaining_accounts,
+                treasury_cut,
+                ctx.accounts.reward_mint.decimals,
+                &[],
+            )?;
+        }
+
+        let season_pass = &mut ctx.accounts.season_pass;
+        season_pass.trader = ctx.accounts.trader.key();
+        season_pass.season = config.current_season;
+        season_pass.price_paid = price;
+        season_pass.purchased_at = Clock::get()?.unix_timestamp;
+        season_pass.cosmetic_flags = cosmetic_flags;
+        season_pass.bump = ctx.bumps.season_pass;
+
+        ctx.accounts.trader_stats.season_pass = config.current_season;
+        Ok(())
+    }
+
+    /// Configures loyalty point accrual and redemption rates. Super-authority only.
+    ///
+    /// - `loyalty_points_per_epoch`: points accrued per epoch with at least one trade.
+    /// - `fee_credit_per_point`: fee credits (token units) granted per redeemed point.
+    /// - `points_per_boost_bps`: points needed per basis point of reward boost; 0 disables.
+    pub fn set_loyalty_params(
+        ctx: Context<UpdateConfig>,
+        loyalty_points_per_epoch: u64,
+        fee_credit_per_point: u64,
+        points_per_boost_bps: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.loyalty_points_per_epoch = loyalty_points_per_epoch;
+        config.fee_credit_per_point = fee_credit_per_point;
+        config.points_per_boost_bps = points_per_boost_bps;
+        Ok(())
+    }
+
+    /// Redeems non-transferable loyalty points for fee credits or a one-off
+    /// reward-share boost applied to the trader's next payout. A boost is
+    /// bought in whole basis points; points left over are not deducted.
+    pub fn redeem_points(
+        ctx: Context<RedeemPoints>,
+        points: u64,
+        redemption: PointsRedemption,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        if points > trader_stats.loyalty_points {
+            return Err(ErrorCode::InsufficientLoyaltyPoints.into());
+        }
+
+        let spent = match redemption {
+            PointsRedemption::FeeCredit => {
+                let credit = points
+                    .checked_mul(config.fee_credit_per_point)
+                    .ok_or(ErrorCode::Overflow)?;
+                trader_stats.fee_credits = trader_stats
+                    .fee_credits
+                    .checked_add(credit)
+                    .ok_or(ErrorCode::Overflow)?;
+                points
+            }
+            PointsRedemption::RewardBoost => {
+                if config.points_per_boost_bps == 0 {
+                    return Err(ErrorCode::RedemptionUnavailable.into());
+                }
+                let boost_units = points / config.points_per_boost_bps;
+                let boost = boost_units.saturating_add(trader_stats.loyalty_boost_bps as u64);
+                if boost > MAX_LOYALTY_BOOST_BPS as u64 {
+                    return Err(ErrorCode::LoyaltyBoostCapExceeded.into());
+                }
+                trader_stats.loyalty_boost_bps = boost as u16;
+                boost_units * config.points_per_boost_bps
+            }
+        };
+        trader_stats.loyalty_points -= spent;
+        Ok(())
+    }
+
+    /// Sets the share of followers' protocol fees routed to the leader they
+    /// copy-trade. Super-authority only.
+    pub fn set_leader_share(ctx: Context<UpdateConfig>, leader_share_bps: u16) -> Result<()> {
+        if leader_share_bps > 10_000 {
+            return Err(ErrorCode::InvalidBps.into());
+        }
+        ctx.accounts.config.leader_share_bps = leader_share_bps;
+        Ok(())
+    }
+
+    /// Registers the signer as a copy-trading follower of `leader`.
+    pub fn follow_leader(ctx: Context<FollowLeader>, leader: Pubkey) -> Result<()> {
+        if leader == ctx.accounts.follower.key() {
+            return Err(ErrorCode::CannotFollowSelf.into());
+        }
+        let follow_link = &mut ctx.accounts.follow_link;
+        follow_link.follower = ctx.accounts.follower.key();
+        follow_link.leader = leader;
+        follow_link.followed_at = Clock::get()?.unix_timestamp;
+        follow_link.bump = ctx.bumps.follow_link;
+        Ok(())
+    }
+
+    /// Removes the signer's copy-trading link and refunds its rent.
+    pub fn unfollow_leader(_ctx: Context<UnfollowLeader>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Creates the accumulator of a leader's revenue share for one trade epoch.
+    /// Anyone may pay for it.
+    pub fn init_leader_revenue(
+        ctx: Context<InitLeaderRevenue>,
+        leader: Pubkey,
+        epoch: i64,
+    ) -> Result<()> {
+        let leader_revenue = &mut ctx.accounts.leader_revenue;
+        leader_revenue.leader = leader;
+        leader_revenue.epoch = epoch;
+        leader_revenue.accrued = 0;
+        leader_revenue.claimed = 0;
+        leader_revenue.bump = ctx.bumps.leader_revenue;
+        Ok(())
+    }
+
+    /// Pays out a leader's accrued revenue share for one epoch from the fee vault.
+    pub fn claim_leader_revenue(ctx: Context<ClaimLeaderRevenue>) -> Result<()> {
+        let leader_revenue = &mut ctx.accounts.leader_revenue;
+        let claimable = leader_revenue
+            .accrued
+            .checked_sub(leader_revenue.claimed)
+            .ok_or(ErrorCode::Overflow)?;
+        if claimable == 0 {
+            return Err(ErrorCode::NothingToClaim.into());
+        }
+        leader_revenue.claimed = leader_revenue.accrued;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.fee_vault.to_account_info(),
+                    to: ctx.accounts.leader_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            claimable,
+        )?;
+        Ok(())
+    }
+
+    /// Creates or updates the signer's public trader profile.
+    ///
+    /// The display name is a fixed 32-byte (zero-padded UTF-8) field; the avatar
+    /// or metadata URI is capped at `MAX_PROFILE_URI_LEN` bytes.
+    ///
+    /// Each display name is held by at most one trader through a DisplayName
+    /// PDA seeded by the name. Renaming releases the previous name, whose
+    /// DisplayName account must then be passed as `previous_display_name`.
+    pub fn set_trader_profile(
+        ctx: Context<SetTraderProfile>,
+        display_name: [u8; 32],
+        avatar_uri: String,
+        social_links_hash: [u8; 32],
+    ) -> Result<()> {
+        if avatar_uri.len() > MAX_PROFILE_URI_LEN {
+            return Err(ErrorCode::ProfileUriTooLong.into());
+        }
+        if std::str::from_utf8(&display_name).is_err() || display_name == [0; 32] {
+            return Err(ErrorCode::InvalidDisplayName.into());
+        }
+
+        let trader = ctx.accounts.trader.key();
+        let claim = &mut ctx.accounts.display_name_claim;
+        if claim.owner != Pubkey::default() && claim.owner != trader {
+            return Err(ErrorCode::DisplayNameTaken.into());
+        }
+        claim.owner = trader;
+        claim.bump = ctx.bumps.display_name_claim;
+
+        let renamed = ctx.accounts.trader_profile.trader != Pubkey::default()
+            && ctx.accounts.trader_profile.display_name != display_name;
+        if renamed {
+            let previous = ctx
+                .accounts
+                .previous_display_name
+                .as_ref()
+                .ok_or(ErrorCode::DisplayNameNotProvided)?;
+            previous.close(ctx.accounts.trader.to_account_info())?;
+        }
+
+        let profile = &mut ctx.accounts.trader_profile;
+        profile.trader = ctx.accounts.trader.key();
+        profile.display_name = display_name;
+        profile.avatar_uri = avatar_uri;
+        profile.social_links_hash = social_links_hash;
+        profile.updated_at = Clock::get()?.unix_timestamp;
+        profile.bump = ctx.bumps.trader_profile;
+        Ok(())
+    }
+
+    /// Endorses another trader, adding reputation weighted by the endorser's stake.
+    ///
+    /// Each endorser may endorse a given trader once, and at most once per
+    /// `ENDORSEMENT_COOLDOWN`. Reputation is a minor ranking tiebreaker and an
+    /// input to sybil heuristics.
+    pub fn endorse_trader(ctx: Context<EndorseTrader>) -> Result<()> {
+        if ctx.accounts.endorser_stats.identity() == ctx.accounts.endorsee_stats.identity() {
+            return Err(ErrorCode::CannotEndorseSelf.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        let endorser_stats = &mut ctx.accounts.endorser_stats;
+
+        if endorser_stats.last_endorsement > 0
+            && current_time - endorser_stats.last_endorsement < ENDORSEMENT_COOLDOWN
+        {
+            return Err(ErrorCode::EndorsementTooSoon.into());
+        }
+        let weight = endorser_stats.staked_amount / ENDORSEMENT_STAKE_UNIT;
+        if weight == 0 {
+            return Err(ErrorCode::InsufficientStakeToEndorse.into());
+        }
+        endorser_stats.last_endorsement = current_time;
+
+        let endorsee_stats = &mut ctx.accounts.endorsee_stats;
+        endorsee_stats.reputation = endorsee_stats
+            .reputation
+            .checked_add(weight)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let endorsement = &mut ctx.accounts.endorsement;
+        endorsement.endorser = endorser_stats.trader;
+        endorsement.endorsee = endorsee_stats.trader;
+        endorsement.weight = weight;
+        endorsement.endorsed_at = current_time;
+        endorsement.bump = ctx.bumps.endorsement;
+        Ok(())
+    }
+
+    /// Sets the treasury token account receiving rounding remainders.
+    /// `Pubkey::default()` sends remainders to the #1 trader. Super-authority only.
+    pub fn set_treasury(ctx: Context<UpdateConfig>, treasury: Pubkey) -> Result<()> {
+        ctx.accounts.config.treasury = treasury;
+        Ok(())
+    }
+
+    /// Creates the protocol treasury, a `["treasury", mint]` token account
+    /// owned by the vault authority PDA, and makes it `config.treasury`.
+    /// Super-authority only.
+    pub fn init_treasury(ctx: Context<InitTreasury>) -> Result<()> {
+        ctx.accounts.config.treasury = ctx.accounts.treasury.key();
+        Ok(())
+    }
+
+    /// Sets the share of the treasury balance burned by each `burn_treasury`.
+    /// Super-authority only.
+    pub fn set_treasury_burn_bps(ctx: Context<UpdateConfig>, treasury_burn_bps: u16) -> Result<()> {
+        if treasury_burn_bps > 10_000 {
+            return Err(ErrorCode::InvalidBps.into());
+        }
+        ctx.accounts.config.treasury_burn_bps = treasury_burn_bps;
+        Ok(())
+    }
+
+    /// Burns `config.treasury_burn_bps` of the $T2E accumulated in the
+    /// program-owned treasury. Admin or co-admin only.
+    pub fn burn_treasury(ctx: Context<BurnTreasury>) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        let amount = (treasury.amount as u128 * ctx.accounts.config.treasury_burn_bps as u128
+            / 10_000) as u64;
+        if amount == 0 {
+            return Err(ErrorCode::NothingToBurn.into());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::Burn {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    from: treasury.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.config;
+        config.total_burned = config
+            .total_burned
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        config.epoch_burned = config
+            .burned_in_epoch(t2e_core::emission_epoch(current_time))
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        config.burn_epoch = t2e_core::emission_epoch(current_time);
+        emit!(TreasuryBurned {
+            treasury: ctx.accounts.treasury.key(),
+            amount,
+            remaining: ctx.accounts.treasury.amount - amount,
+            total_burned: config.total_burned,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Sets the yearly staking yield in basis points of the staked amount.
+    /// Yield accrued at the previous rate is checkpointed first.
+    /// Super-authority only.
+    pub fn set_staking_apr(ctx: Context<UpdateConfig>, staking_apr_bps: u16) -> Result<()> {
+        if staking_apr_bps > 10_000 {
+            return Err(ErrorCode::InvalidBps.into());
+        }
+        let config = &mut ctx.accounts.config;
+        let current_time = Clock::get()?.unix_timestamp;
+        config.staking_reward_index = config.staking_index(current_time)?;
+        config.staking_index_updated = current_time;
+        config.staking_apr_bps = staking_apr_bps;
+        Ok(())
+    }
+
+    /// Creates the staking reward vault, a `["staking_rewards", mint]` token
+    /// account owned by the vault authority PDA that funds staking yield.
+    /// Kept apart from the reward vault so leaderboard rewards and staking
+    /// yield are budgeted separately. Super-authority only.
+    pub fn init_staking_reward_vault(_ctx: Context<InitStakingRewardVault>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Creates the staking vault, a `["staking_vault", mint]` token account
+    /// owned by the vault authority PDA that holds staked tokens, so stakes
+    /// and withdrawals can only go through it. Super-authority only.
+    pub fn init_staking_vault(_ctx: Context<InitStakingVault>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Settles a trader's staking yield up to now into `staking_rewards`.
+    /// Permissionless, so keepers can checkpoint balances for off-chain
+    /// display; yield is otherwise settled on every stake change.
+    pub fn accrue_staking_rewards(ctx: Context<AccrueStakingRewards>) -> Result<()> {
+        ctx.accounts
+            .trader_stats
+            .settle_staking_rewards(&ctx.accounts.config, Clock::get()?.unix_timestamp)
+    }
+
+    /// Pays the trader's settled and newly accrued staking yield from the
+    /// staking reward vault. With `compound`, the yield is moved into the
+    /// staking vault and added to `staked_amount` instead.
+    pub fn claim_staking_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimStakingRewards<'info>>,
+        compound: bool,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_STAKING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        trader_stats.settle_staking_rewards(&ctx.accounts.config, current_time)?;
+        let amount = trader_stats.staking_rewards;
+        if amount == 0 {
+            return Err(ErrorCode::NothingToClaim.into());
+        }
+        trader_stats.staking_rewards = 0;
+
+        let destination = if compound {
+            ctx.accounts.staking_vault.to_account_info()
+        } else {
+            ctx.accounts.trader_token_account.to_account_info()
+        };
+        let vault_balance_before = ctx.accounts.staking_vault.amount;
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.staking_reward_vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            destination,
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )?;
+
+        if compound {
+            // Credit what arrived, net of any transfer fee.
+            ctx.accounts.staking_vault.reload()?;
+            let received = ctx
+                .accounts
+                .staking_vault
+                .amount
+                .checked_sub(vault_balance_before)
+                .ok_or(ErrorCode::Overflow)?;
+            ctx.accounts
+                .trader_stats
+                .compound(received, &ctx.accounts.config, current_time)?;
+        }
+        emit!(StakingRewardsClaimed {
+            trader: ctx.accounts.trader_stats.trader,
+            amount,
+            compounded: compound,
+            staked_amount: ctx.accounts.trader_stats.staked_amount,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Creates the emission schedule, which from then on sets the reward pool
+    /// of distributions instead of `halving_period`. Super-authority only.
+    pub fn init_emission_schedule(
+        ctx: Context<InitEmissionSchedule>,
+        genesis_ts: i64,
+        epoch_length: i64,
+        halving_interval: u32,
+        base_emission: u64,
+    ) -> Result<()> {
+        let schedule = &mut ctx.accounts.emission_schedule;
+        schedule.set(
+            genesis_ts,
+            epoch_length,
+            halving_interval,
+            base_emission,
+            Clock::get()?.unix_timestamp,
+        )?;
+        schedule.bump = ctx.bumps.emission_schedule;
+        ctx.accounts.config.emission_schedule = schedule.key();
+        Ok(())
+    }
+
+    /// Updates the emission schedule. Only allowed before its genesis.
+    /// Super-authority only.
+    pub fn update_emission_schedule(
+        ctx: Context<UpdateEmissionSchedule>,
+        genesis_ts: i64,
+        epoch_length: i64,
+        halving_interval: u32,
+        base_emission: u64,
+    ) -> Result<()> {
+        let schedule = &mut ctx.accounts.emission_schedule;
+        let current_time = Clock::get()?.unix_timestamp;
+        if current_time >= schedule.genesis_ts {
+            return Err(ErrorCode::EmissionScheduleLocked.into());
+        }
+        schedule.set(genesis_ts, epoch_length, halving_interval, base_emission, current_time)
+    }
+
+    /// Sets the governance parameters. A `voting_period` of 0 disables
+    /// proposals. The voting period may not exceed `unstake_cooldown`, so
+    /// stake that voted cannot be withdrawn and vote again before the vote
+    /// closes. Super-authority only.
+    pub fn set_governance_params(
+        ctx: Context<UpdateConfig>,
+        voting_period: i64,
+        quorum_votes: u64,
+        proposal_min_stake: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if voting_period < 0 || voting_period > config.unstake_cooldown {
+            return Err(ErrorCode::InvalidGovernanceParams.into());
+        }
+        config.voting_period = voting_period;
+        config.quorum_votes = quorum_votes;
+        config.proposal_min_stake = proposal_min_stake;
+        Ok(())
+    }
+
+    /// Opens a proposal to change the scoring weights, the halving period or
+    /// the pause flags. The proposer needs an effective stake of at least
+    /// `config.proposal_min_stake`.
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: ProposalAction) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if config.voting_period == 0 {
+            return Err(ErrorCode::GovernanceDisabled.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        if ctx.accounts.proposer_stats.effective_stake(current_time) < config.proposal_min_stake {
+            return Err(ErrorCode::InsufficientProposalStake.into());
+        }
+        action.validate()?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.id = config.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action;
+        proposal.votes_for = 0;
+        proposal.votes_against = 0;
+        proposal.created_at = current_time;
+        proposal.voting_ends_at = current_time
+            .checked_add(config.voting_period)
+            .ok_or(ErrorCode::Overflow)?;
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+        config.proposal_count = config
+            .proposal_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ProposalCreated {
+            proposal: proposal.key(),
+            id: proposal.id,
+            proposer: proposal.proposer,
+            action,
+            voting_ends_at: proposal.voting_ends_at,
+        });
+        Ok(())
+    }
+
+    /// Votes on an open proposal with the voter's effective stake, which
+    /// includes the bonus of a locked stake position. One vote per trader.
+    pub fn cast_vote(ctx: Context<CastVote>, support: bool) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        if current_time >= proposal.voting_ends_at {
+            return Err(ErrorCode::VotingClosed.into());
+        }
+        let weight = ctx.accounts.voter_stats.effective_stake(current_time);
+        if weight == 0 {
+            return Err(ErrorCode::NoVotingPower.into());
+        }
+        let tally = if support {
+            &mut proposal.votes_for
+        } else {
+            &mut proposal.votes_against
+        };
+        *tally = tally.checked_add(weight).ok_or(ErrorCode::Overflow)?;
+
+        let vote = &mut ctx.accounts.vote_record;
+        vote.proposal = proposal.key();
+        vote.voter = ctx.accounts.voter.key();
+        vote.weight = weight;
+        vote.support = support;
+        vote.bump = ctx.bumps.vote_record;
+
+        emit!(VoteCast {
+            proposal: vote.proposal,
+            voter: vote.voter,
+            support,
+            weight,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Applies a proposal once voting has ended, if it reached the quorum
+    /// and more stake voted for than against. Permissionless.
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        let config = &mut ctx.accounts.config;
+        if proposal.executed {
+            return Err(ErrorCode::ProposalAlreadyExecuted.into());
+        }
+        if current_time < proposal.voting_ends_at {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+        if !proposal.passed(config.quorum_votes) {
+            return Err(ErrorCode::ProposalRejected.into());
+        }
+        proposal.executed = true;
+        config.apply_action(proposal.action, proposal.key(), current_time)?;
+        emit!(ProposalExecuted {
+            proposal: proposal.key(),
+            id: proposal.id,
+            action: proposal.action,
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Sets the operator allowed to update and snapshot leaderboards.
+    /// `Pubkey::default()` leaves these to the admins. Super-authority only.
+    pub fn set_operator(ctx: Context<UpdateConfig>, operator: Pubkey) -> Result<()> {
+        ctx.accounts.config.operator = operator;
+        Ok(())
+    }
+
+    /// Grants `grantee` the `ROLE_*` flags in `roles`, replacing any earlier
+    /// grant. Operators may update and snapshot leaderboards and keepers may
+    /// call the crank instructions; config changes and pauses stay with the
+    /// admins. Super-authority only.
+    pub fn set_roles(ctx: Context<SetRoles>, grantee: Pubkey, roles: u8) -> Result<()> {
+        if roles == 0 || roles & !ROLE_ALL != 0 {
+            return Err(ErrorCode::InvalidRoles.into());
+        }
+        let role = &mut ctx.accounts.role;
+        role.grantee = grantee;
+        role.roles = roles;
+        role.granted_at = Clock::get()?.unix_timestamp;
+        role.bump = ctx.bumps.role;
+        Ok(())
+    }
+
+    /// Revokes all roles of a grantee by closing their Role account.
+    /// Super-authority only.
+    pub fn revoke_roles(_ctx: Context<RevokeRoles>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Sets the key whose Ed25519 signatures `record_attested_trade` accepts.
+    /// `Pubkey::default()` disables attested trades. Super-authority only.
+    pub fn set_trade_oracle(ctx: Context<UpdateConfig>, trade_oracle: Pubkey) -> Result<()> {
+        ctx.accounts.config.trade_oracle = trade_oracle;
+        Ok(())
+    }
+
+    /// Sets how the trader wants to receive leaderboard rewards.
+    ///
+    /// `UsdcViaSwap` is only available once the admin has configured a swap escrow.
+    pub fn set_payout_preference(
+        ctx: Context<SetPayoutPreference>,
+        preference: PayoutPreference,
+    ) -> Result<()> {
+        if preference == PayoutPreference::UsdcViaSwap
+            && ctx.accounts.config.swap_escrow == Pubkey::default()
+        {
+            return Err(ErrorCode::SwapPayoutUnavailable.into());
+        }
+        ctx.accounts.trader_stats.payout_preference = preference;
+        Ok(())
+    }
+
+    /// Suspends a trader from ranking and rewards until `until_ts`, at most
+    /// `MAX_SUSPENSION` from now. Passing 0 lifts the suspension. Admin only.
+    pub fn suspend_trader(ctx: Context<SuspendTrader>, until_ts: i64) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        if until_ts != 0 && (until_ts <= current_time || until_ts - current_time > MAX_SUSPENSION) {
+            return Err(ErrorCode::InvalidSuspension.into());
+        }
+        ctx.accounts.trader_stats.suspended_until = until_ts;
+        Ok(())
+    }
+
+    /// Bans a trader indefinitely: they can no longer record trades, stake,
+    /// claim rewards or close their stats, and are left out of rankings and
+    /// reward splits. Admin only.
+    pub fn ban_trader(ctx: Context<BanTrader>) -> Result<()> {
+        set_trader_ban(&mut ctx.accounts.trader_stats, true)
+    }
+
+    /// Lifts a trader's ban. Admin only.
+    pub fn unban_trader(ctx: Context<BanTrader>) -> Result<()> {
+        set_trader_ban(&mut ctx.accounts.trader_stats, false)
+    }
+
+    /// Cancels the vesting schedule of a banned trader on a leaderboard: what
+    /// has vested so far stays releasable should the ban be lifted, and the
+    /// still-locked remainder is returned from the claim vault to the
+    /// insurance fund, or with `to_insurance_fund` unset to the reward vault.
+    /// Admin only, or an approved `AdminAction::ClawbackVesting` while an
+    /// admin set exists.
+    ///
+    /// Extra accounts required by a Token-2022 transfer hook are passed via
+    /// `remaining_accounts`.
+    pub fn clawback_vesting<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClawbackVesting<'info>>,
+        to_insurance_fund: bool,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        authorize_admin_action(
+            config,
+            ctx.accounts.admin_set.as_deref(),
+            ctx.accounts.admin_action.as_deref_mut(),
+            AdminAction::ClawbackVesting {
+                trader: ctx.accounts.trader_stats.trader,
+                leaderboard: ctx.accounts.vesting_schedule.leaderboard,
+                to_insurance_fund,
+            },
+        )?;
+        let expected = if to_insurance_fund {
+            config.insurance_fund
+        } else {
+            Pubkey::find_program_address(
+                &[b"reward_vault", ctx.accounts.reward_mint.key().as_ref()],
+                &crate::ID,
+            )
+            .0
+        };
+        if expected == Pubkey::default() || ctx.accounts.destination.key() != expected {
+            return Err(ErrorCode::InvalidClawbackDestination.into());
+        }
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let schedule = &mut ctx.accounts.vesting_schedule;
+        let amount = schedule.cancel(current_time);
+        emit!(VestingClawedBack {
+            trader: schedule.trader,
+            leaderboard: schedule.leaderboard,
+            amount,
+            destination: expected,
+            timestamp: current_time,
+        });
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.claim_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Confiscates `slash_bps` of a trader's staked $T2E into the treasury and
+    /// zeroes their stats for the active season, recording the penalty in a
+    /// SlashRecord. The trader's season stats must be passed while a season
+    /// is running. Admin only, or an approved `AdminAction::SlashTrader` while
+    /// an admin set exists.
+    ///
+    /// The slash is taken from stake held outside positions first and then
+    /// from the trader's stake positions pro rata, sponsored ones included,
+    /// along with their lock bonus. When it reaches into positions, every
+    /// open StakePosition of the trader must lead `remaining_accounts`.
+    /// Extra accounts required by a Token-2022 transfer hook follow them.
+    pub fn slash_trader<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SlashTrader<'info>>,
+        slash_bps: u16,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        if slash_bps == 0 || slash_bps > 10_000 {
+            return Err(ErrorCode::InvalidSlashParams.into());
+        }
+        authorize_admin_action(
+            &ctx.accounts.config,
+            ctx.accounts.admin_set.as_deref(),
+            ctx.accounts.admin_action.as_deref_mut(),
+            AdminAction::SlashTrader {
+                trader: ctx.accounts.trader_stats.trader,
+                slash_bps,
+                reason_hash,
+            },
+        )?;
+        let current_time = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+
+        match ctx.accounts.season_stats.as_mut() {
+            Some(season_stats) => {
+                if season_stats.season != config.active_season
+                    || season_stats.trader != trader_stats.trader
+                {
+                    return Err(ErrorCode::SeasonStatsMismatch.into());
+                }
+                season_stats.total_volume = 0;
+                season_stats.average_execution_time = 0;
+                season_stats.trade_count = 0;
+                season_stats.pnl = 0;
+            }
+            None if config.active_season == 0 => {}
+            None => return Err(ErrorCode::SeasonStatsMismatch.into()),
+        }
+
+        // Leading program-owned accounts are the trader's stake positions; the
+        // rest are transfer hook accounts.
+        let mut positions: Vec<Account<StakePosition>> = Vec::new();
+        for info in ctx.remaining_accounts.iter() {
+            if info.owner != &crate::ID {
+                break;
+            }
+            let position = Account::<StakePosition>::try_from(info)?;
+            if position.trader != trader_stats.trader
+                || positions.iter().any(|seen| seen.key() == position.key())
+            {
+                return Err(ErrorCode::StakePositionsIncomplete.into());
+            }
+            positions.push(position);
+        }
+        let hook_accounts = &ctx.remaining_accounts[positions.len()..];
+
+        trader_stats.settle_staking_rewards(config, current_time)?;
+        let amount = (trader_stats.staked_amount as u128 * slash_bps as u128 / 10_000) as u64;
+        trader_stats.slash_stake(amount, &mut positions)?;
+        for position in positions.iter() {
+            position.exit(&crate::ID)?;
+        }
+        trader_stats.slash_count = trader_stats
+            .slash_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let record = &mut ctx.accounts.slash_record;
+        record.trader = trader_stats.trader;
+        record.admin = ctx.accounts.admin.key();
+        record.amount = amount;
+        record.slash_bps = slash_bps;
+        record.season = config.active_season;
+        record.reason_hash = reason_hash;
+        record.slashed_at = current_time;
+        record.bump = ctx.bumps.slash_record;
+
+        emit!(TraderSlashed {
+            trader: record.trader,
+            amount,
+            slash_bps,
+            season: record.season,
+            reason_hash,
+            timestamp: current_time,
+        });
+
+        if amount == 0 {
+            return Ok(());
+        }
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.staking_vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            hook_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Configures penalty appeals: the arbiter resolving them, the insurance
+    /// fund receiving bonds of rejected appeals, and the minimum bond.
+    /// Super-authority only.
+    pub fn set_appeal_params(
+        ctx: Context<UpdateConfig>,
+        arbiter: Pubkey,
+        insurance_fund: Pubkey,
+        min_appeal_bond: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.arbiter = arbiter;
+        config.insurance_fund = insurance_fund;
+        config.min_appeal_bond = min_appeal_bond;
+        Ok(())
+    }
+
+    /// Opens an appeal against the trader's current suspension by bonding
+    /// $T2E into the appeal's own `["appeal_bond", appeal]` vault. The bond
+    /// must be in a reward mint that the insurance fund also holds.
+    pub fn open_appeal<'info>(
+        ctx: Context<'_, '_, '_, 'info, OpenAppeal<'info>>,
+        bond_amount: u64,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        if config.arbiter == Pubkey::default() {
+            return Err(ErrorCode::AppealsDisabled.into());
+        }
+        if bond_amount < config.min_appeal_bond {
+            return Err(ErrorCode::AppealBondTooLow.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        let trader_stats = &ctx.accounts.trader_stats;
+        if !trader_stats.is_suspended(current_time) {
+            return Err(ErrorCode::NotSuspended.into());
+        }
+
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.bond_vault.to_account_info(),
+            ctx.accounts.trader.to_account_info(),
+            ctx.remaining_accounts,
+            bond_amount,
+            ctx.accounts.mint.decimals,
+            &[],
+        )?;
+        let bonded = net_of_transfer_fee(&ctx.accounts.mint.to_account_info(), bond_amount)?;
+
+        let appeal = &mut ctx.accounts.appeal;
+        appeal.trader = trader_stats.trader;
+        appeal.trader_stats = trader_stats.key();
+        appeal.trader_token_account = ctx.accounts.trader_token_account.key();
+        appeal.bond_amount = bonded;
+        appeal.suspended_until = trader_stats.suspended_until;
+        appeal.reason_hash = reason_hash;
+        appeal.opened_at = current_time;
+        appeal.bump = ctx.bumps.appeal;
+
+        emit!(AppealOpened {
+            trader: appeal.trader,
+            bond_amount: bonded,
+            suspended_until: appeal.suspended_until,
+            reason_hash,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Resolves an appeal. Lifting the penalty clears the suspension and
+    /// returns the bond; upholding it sends the bond to the insurance fund.
+    /// Arbiter only.
+    pub fn resolve_appeal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveAppeal<'info>>,
+        lift: bool,
+    ) -> Result<()> {
+        let appeal = &ctx.accounts.appeal;
+        if lift {
+            ctx.accounts.trader_stats.suspended_until = 0;
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        let bond_destination = if lift {
+            ctx.accounts.trader_token_account.to_account_info()
+        } else {
+            ctx.accounts.insurance_fund.to_account_info()
+        };
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.bond_vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            bond_destination,
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            appeal.bond_amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )?;
+
+        emit!(AppealResolved {
+            trader: appeal.trader,
+            arbiter: ctx.accounts.arbiter.key(),
+            lifted: lift,
+            bond_amount: appeal.bond_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Sets the $T2E fee for claiming a referral code; 0 = free. Requires a
+    /// treasury when non-zero. Super-authority only.
+    pub fn set_referral_code_fee(ctx: Context<UpdateConfig>, referral_code_fee: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if referral_code_fee > 0 && config.treasury == Pubkey::default() {
+            return Err(ErrorCode::TreasuryNotProvided.into());
+        }
+        config.referral_code_fee = referral_code_fee;
+        Ok(())
+    }
+
+    /// Claims a human-readable referral code for the trader.
+    ///
+    /// Codes are 3-16 characters of `a-z`, `0-9` and `-`, and map to a PDA
+    /// seeded by their hash, so each code can only be claimed once.
+    pub fn claim_referral_code(ctx: Context<ClaimReferralCode>, code: String) -> Result<()> {
+        if !is_valid_referral_code(&code) {
+            return Err(ErrorCode::InvalidReferralCode.into());
+        }
+
+        let fee = ctx.accounts.config.referral_code_fee;
+        if fee > 0 {
+            let trader_token_account = ctx
+                .accounts
+                .trader_token_account
+                .as_ref()
+                .ok_or(ErrorCode::TraderTokenAccountNotFound)?;
+            let treasury = ctx
+                .accounts
+                .treasury
+                .as_ref()
+                .ok_or(ErrorCode::TreasuryNotProvided)?;
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: trader_token_account.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: ctx.accounts.trader.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+
+        let referral_code = &mut ctx.accounts.referral_code;
+        referral_code.owner = ctx.accounts.trader.key();
+        referral_code.code = code;
+        referral_code.claimed_at = Clock::get()?.unix_timestamp;
+        referral_code.bump = ctx.bumps.referral_code;
+        Ok(())
+    }
+
+    /// Records the owner of a referral code as the trader's referrer. Can only
+    /// be set once.
+    pub fn apply_referral_code(ctx: Context<ApplyReferralCode>, _code: String) -> Result<()> {
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        if trader_stats.referrer != Pubkey::default() {
+            return Err(ErrorCode::ReferrerAlreadySet.into());
+        }
+        let referrer = ctx.accounts.referral_code.owner;
+        if referrer == trader_stats.trader || referrer == trader_stats.identity() {
+            return Err(ErrorCode::CannotReferSelf.into());
+        }
+        trader_stats.referrer = referrer;
+        Ok(())
+    }
+
+    /// Links the trader to `referrer` through a Referral PDA, which routes
+    /// `config.referral_reward_bps` of the trader's `finalize_epoch` rewards
+    /// to the referrer. Also records the referrer on the trader's stats, so
+    /// it can only be set once and is exclusive with `apply_referral_code`.
+    pub fn register_referral(ctx: Context<RegisterReferral>, referrer: Pubkey) -> Result<()> {
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        if trader_stats.referrer != Pubkey::default() {
+            return Err(ErrorCode::ReferrerAlreadySet.into());
+        }
+        if referrer == trader_stats.trader || referrer == trader_stats.identity() {
+            return Err(ErrorCode::CannotReferSelf.into());
+        }
+        trader_stats.referrer = referrer;
+
+        let referral = &mut ctx.accounts.referral;
+        referral.trader = trader_stats.trader;
+        referral.referrer = referrer;
+        referral.registered_at = Clock::get()?.unix_timestamp;
+        referral.rewards_routed = 0;
+        referral.bump = ctx.bumps.referral;
+        Ok(())
+    }
+
+    /// Sets the share of referees' epoch rewards routed to their referrer by
+    /// `finalize_epoch`; 0 disables routing. Admin only.
+    pub fn set_referral_reward_bps(ctx: Context<UpdateConfig>, referral_reward_bps: u16) -> Result<()> {
+        if referral_reward_bps > MAX_REFERRAL_REWARD_BPS {
+            return Err(ErrorCode::InvalidBps.into());
+        }
+        ctx.accounts.config.referral_reward_bps = referral_reward_bps;
+        Ok(())
+    }
+
+    /// Creates the trader's affiliate account, starting at the bronze tier.
+    pub fn init_affiliate(ctx: Context<InitAffiliate>) -> Result<()> {
+        let affiliate = &mut ctx.accounts.affiliate;
+        affiliate.referrer = ctx.accounts.trader.key();
+        affiliate.tier = AffiliateTier::Bronze;
+        affiliate.revenue_share_bps = AffiliateTier::Bronze.revenue_share_bps();
+        affiliate.active_referees = 0;
+        affiliate.referee_volume = 0;
+        affiliate.last_recalculated = 0;
+        affiliate.bump = ctx.bumps.affiliate;
+        Ok(())
+    }
+
+    /// Recalculates the referrer's affiliate tier from their referees'
+    /// TraderStats, passed via `remaining_accounts`. At most once per
+    /// `AFFILIATE_RECALC_INTERVAL`.
+    ///
+    /// Referees who traded within `AFFILIATE_ACTIVE_WINDOW` count as active,
+    /// and their total volume counts towards the tier.
+    pub fn recalculate_affiliate_tier(ctx: Context<RecalculateAffiliateTier>) -> Result<()> {
+        let affiliate = &mut ctx.accounts.affiliate;
+        let current_time = Clock::get()?.unix_timestamp;
+        if affiliate.last_recalculated > 0
+            && current_time - affiliate.last_recalculated < AFFILIATE_RECALC_INTERVAL
+        {
+            return Err(ErrorCode::AffiliateRecalcTooSoon.into());
+        }
+
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut active_referees: u32 = 0;
+        let mut referee_volume: u64 = 0;
+        for acc in ctx.remaining_accounts.iter() {
+            if acc.owner != &crate::ID || seen.contains(acc.key) {
+                return Err(ErrorCode::TraderStatsNotFound.into());
+            }
+            seen.push(*acc.key);
+            let data = acc.try_borrow_data()?;
+            let stats = TraderStats::try_deserialize(&mut &data[..])
+                .map_err(|_| ErrorCode::TraderStatsNotFound)?;
+            if stats.referrer != affiliate.referrer {
+                return Err(ErrorCode::NotAReferee.into());
+            }
+            if current_time - stats.last_trade <= AFFILIATE_ACTIVE_WINDOW {
+                active_referees += 1;
+                referee_volume = referee_volume
+                    .checked_add(stats.total_volume)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+        }
+
+        let tier = AffiliateTier::for_activity(active_referees, referee_volume);
+        affiliate.tier = tier;
+        affiliate.revenue_share_bps = tier.revenue_share_bps();
+        affiliate.active_referees = active_referees;
+        affiliate.referee_volume = referee_volume;
+        affiliate.last_recalculated = current_time;
+        Ok(())
+    }
+
+    /// Applies for registered market-maker status, pending admin review.
+    pub fn apply_market_maker(ctx: Context<ApplyMarketMaker>) -> Result<()> {
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        if trader_stats.market_maker != MarketMakerStatus::None {
+            return Err(ErrorCode::InvalidMarketMakerStatus.into());
+        }
+        trader_stats.market_maker = MarketMakerStatus::Pending;
+        Ok(())
+    }
+
+    /// Approves or rejects a pending market-maker application, or revokes an
+    /// approved market maker (`approved = false`). Admin only.
+    pub fn review_market_maker(ctx: Context<ReviewMarketMaker>, approved: bool) -> Result<()> {
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        trader_stats.market_maker = match (trader_stats.market_maker, approved) {
+            (MarketMakerStatus::Pending, true) => MarketMakerStatus::Approved,
+            (MarketMakerStatus::Pending | MarketMakerStatus::Approved, false) => {
+                MarketMakerStatus::None
+            }
+            _ => return Err(ErrorCode::InvalidMarketMakerStatus.into()),
+        };
+        Ok(())
+    }
+
+    /// Registers the badge mint of an achievement. Admin only.
+    ///
+    /// The mint must be a Token-2022 mint with the `NonTransferable`
+    /// extension, zero decimals and the `BADGE_AUTHORITY_SEED` PDA as its mint
+    /// authority, so badges stay with the trader who earned them.
+    pub fn set_badge_mint(ctx: Context<SetBadgeMint>, achievement: Achievement) -> Result<()> {
+        let badge_mint = &ctx.accounts.badge_mint;
+        if badge_mint.decimals != 0
+            || badge_mint.mint_authority != Some(ctx.accounts.badge_authority.key()).into()
+        {
+            return Err(ErrorCode::InvalidBadgeMint.into());
+        }
+        let info = badge_mint.to_account_info();
+        let data = info.try_borrow_data()?;
+        let non_transferable = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)
+            .map(|state| state.get_extension::<NonTransferable>().is_ok())
+            .unwrap_or(false);
+        if !non_transferable {
+            return Err(ErrorCode::InvalidBadgeMint.into());
+        }
+        ctx.accounts.config.badge_mints[achievement as usize] = badge_mint.key();
+        Ok(())
+    }
+
+    /// Mints the trader's non-transferable badge of a reached achievement.
+    /// Each badge can be minted once per trader.
+    pub fn mint_achievement(ctx: Context<MintAchievement>, achievement: Achievement) -> Result<()> {
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        let bit = achievement.bit();
+        if trader_stats.achievements & bit == 0 {
+            return Err(ErrorCode::AchievementNotReached.into());
+        }
+        if trader_stats.badges_minted & bit != 0 {
+            return Err(ErrorCode::BadgeAlreadyMinted.into());
+        }
+        trader_stats.badges_minted |= bit;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[BADGE_AUTHORITY_SEED, &[ctx.bumps.badge_authority]]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::MintTo {
+                    mint: ctx.accounts.badge_mint.to_account_info(),
+                    to: ctx.accounts.trader_token_account.to_account_info(),
+                    authority: ctx.accounts.badge_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )
+    }
+
+    /// Sets the escrow token account that collects $T2E rewards awaiting a
+    /// USDC swap. `Pubkey::default()` disables swap payouts. Super-authority only.
+    pub fn set_swap_escrow(ctx: Context<UpdateConfig>, swap_escrow: Pubkey) -> Result<()> {
+        ctx.accounts.config.swap_escrow = swap_escrow;
+        Ok(())
+    }
+
+    /// Settles a trader's pending swap payout in USDC. Admin or co-admin only.
+    ///
+    /// The keeper swaps escrowed $T2E off-chain and pays `usdc_amount` from its
+    /// own USDC account; `t2e_amount` is deducted from the trader's pending balance.
+    pub fn settle_swap_payout(
+        ctx: Context<SettleSwapPayout>,
+        t2e_amount: u64,
+        usdc_amount: u64,
+    ) -> Result<()> {
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        trader_stats.pending_swap_payout = trader_stats
+            .pending_swap_payout
+            .checked_sub(t2e_amount)
+            .ok_or(ErrorCode::SwapSettlementExceedsPending)?;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.keeper_usdc_account.to_account_info(),
+            to: ctx.accounts.trader_usdc_account.to_account_info(),
+            authority: ctx.accounts.admin.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            usdc_amount,
+        )?;
+        Ok(())
+    }
+
+    /// Allows traders to stake $T2E tokens.
+    ///
+    /// Tokens are transferred to a staking vault with `transfer_checked`, so both
+    /// SPL Token and Token-2022 mints are supported. Extra accounts required by a
+    /// mint's transfer hook are passed via `remaining_accounts`. Only the amount
+    /// actually received by the vault (net of any transfer fee) is credited.
+    ///
+    /// Each stake opens a new stake position holding the received amount
+    /// under `lock`, with the lock's multiplier credited as `lock_bonus`
+    /// while any lock is running; see `TraderStats::effective_stake`.
+    pub fn stake_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeTokens<'info>>,
+        amount: u64,
+        lock: LockTier,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_STAKING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+
+        let vault_balance_before = ctx.accounts.staking_vault.amount;
+
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.staking_vault.to_account_info(),
+            ctx.accounts.trader.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            &[],
+        )?;
+
+        ctx.accounts.staking_vault.reload()?;
+        let received = ctx
+            .accounts
+            .staking_vault
+            .amount
+            .checked_sub(vault_balance_before)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        let position = trader_stats.open_stake_position(
+            received,
+            lock,
+            ctx.accounts.trader.key(),
+            &ctx.accounts.config,
+            current_time,
+            ctx.bumps.stake_position,
+        )?;
+        emit!(TokensStaked {
+            trader: trader_stats.trader,
+            funder: position.funder,
+            position_id: position.position_id,
+            amount: received,
+            lock,
+            staked_amount: trader_stats.staked_amount,
+            locked_until: trader_stats.locked_until,
+            timestamp: current_time,
+        });
+        ctx.accounts.stake_position.set_inner(position);
+        Ok(())
+    }
+
+    /// Stakes the signer's $T2E on behalf of `trader`, e.g. from a team
+    /// treasury or sponsor, in a new stake position credited to the
+    /// trader's stats as in `stake_tokens`.
+    ///
+    /// The position records the signer as its funder: only they can
+    /// withdraw it, through `request_sponsored_unstake` and
+    /// `withdraw_sponsored_stake`, and the trader cannot unstake or close it.
+    pub fn stake_for<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeFor<'info>>,
+        trader: Pubkey,
+        amount: u64,
+        lock: LockTier,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_STAKING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+
+        let vault_balance_before = ctx.accounts.staking_vault.amount;
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.funder_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.staking_vault.to_account_info(),
+            ctx.accounts.funder.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            &[],
+        )?;
+        ctx.accounts.staking_vault.reload()?;
+        let received = ctx
+            .accounts
+            .staking_vault
+            .amount
+            .checked_sub(vault_balance_before)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        let position = trader_stats.open_stake_position(
+            received,
+            lock,
+            ctx.accounts.funder.key(),
+            &ctx.accounts.config,
+            current_time,
+            ctx.bumps.stake_position,
+        )?;
+        emit!(TokensStaked {
+            trader,
+            funder: position.funder,
+            position_id: position.position_id,
+            amount: received,
+            lock,
+            staked_amount: trader_stats.staked_amount,
+            locked_until: trader_stats.locked_until,
+            timestamp: current_time,
+        });
+        ctx.accounts.stake_position.set_inner(position);
+        Ok(())
+    }
+
+    /// Starts the unstake cooldown for `amount` of a position the signer
+    /// funded for another trader, once its lock has expired. The amount
+    /// stops counting as the trader's stake immediately.
+    pub fn request_sponsored_unstake(ctx: Context<RequestSponsoredUnstake>, amount: u64) -> Result<()> {
+        let position = &mut ctx.accounts.stake_position;
+        if amount == 0 || amount > position.amount {
+            return Err(ErrorCode::InvalidUnstakeAmount.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        if current_time < position.locked_until {
+            return Err(ErrorCode::StakeLocked.into());
+        }
+        let config = &ctx.accounts.config;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        trader_stats.settle_staking_rewards(config, current_time)?;
+        trader_stats.release_stake_position(position, amount)?;
+        trader_stats.staked_amount = trader_stats
+            .staked_amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::InvalidUnstakeAmount)?;
+        position.unstake_pending = position
+            .unstake_pending
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        position.unstake_available_at = current_time
+            .checked_add(config.unstake_cooldown)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Withdraws the pending unstake amount of a sponsored position to its
+    /// funder once the cooldown has elapsed, and closes the position to the
+    /// funder once it is empty.
+    ///
+    /// Extra accounts required by a Token-2022 transfer hook are passed via
+    /// `remaining_accounts`.
+    pub fn withdraw_sponsored_stake<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawSponsoredStake<'info>>,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_STAKING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let position = &mut ctx.accounts.stake_position;
+        let amount = position.unstake_pending;
+        if amount == 0 {
+            return Err(ErrorCode::NoPendingUnstake.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        if current_time < position.unstake_available_at {
+            return Err(ErrorCode::UnstakeCooldownActive.into());
+        }
+        position.unstake_pending = 0;
+        emit!(TokensUnstaked {
+            trader: position.trader,
+            amount,
+            penalty: 0,
+            staked_amount: ctx.accounts.trader_stats.staked_amount,
+            timestamp: current_time,
+        });
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.staking_vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.funder_token_account.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )?;
+        if position.amount == 0 {
+            position.close(ctx.accounts.funder.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    /// Starts the unstake cooldown for `amount` staked $T2E.
+    ///
+    /// The amount is taken from the stake position passed, partially or in
+    /// full, once its lock has expired. Without a position, only stake held
+    /// outside positions (e.g. compounded rewards) can be unstaked.
+    ///
+    /// A locked position can only be unstaked while an early-unstake penalty
+    /// is configured; see `set_early_unstake_penalty`. The penalty is
+    /// withheld from the pending amount and routed by `unstake_tokens`.
+    ///
+    /// The amount stops counting as staked immediately and becomes
+    /// withdrawable with `unstake_tokens` once `config.unstake_cooldown` has
+    /// elapsed. A new request adds to any pending amount and restarts the
+    /// cooldown.
+    pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Err(ErrorCode::InvalidUnstakeAmount.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        let config = &ctx.accounts.config;
+        trader_stats.settle_staking_rewards(config, current_time)?;
+        let mut penalty = 0;
+        match ctx.accounts.stake_position.as_mut() {
+            Some(position) => {
+                if amount > position.amount {
+                    return Err(ErrorCode::InvalidUnstakeAmount.into());
+                }
+                if current_time < position.locked_until {
+                    if config.early_unstake_max_penalty_bps == 0 {
+                        return Err(ErrorCode::StakeLocked.into());
+                    }
+                    let penalty_bps = t2e_core::early_unstake_penalty_bps(
+                        config.early_unstake_min_penalty_bps,
+                        config.early_unstake_max_penalty_bps,
+                        position.locked_until - current_time,
+                        position.locked_until - position.staked_at,
+                    );
+                    penalty = (amount as u128 * penalty_bps as u128 / 10_000) as u64;
+                }
+                trader_stats.release_stake_position(position, amount)?;
+            }
+            None => {
+                if amount > trader_stats.staked_amount.saturating_sub(trader_stats.position_stake) {
+                    return Err(ErrorCode::InvalidUnstakeAmount.into());
+                }
+            }
+        }
+        trader_stats.staked_amount = trader_stats
+            .staked_amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::InvalidUnstakeAmount)?;
+        trader_stats.unstake_pending = trader_stats
+            .unstake_pending
+            .checked_add(amount - penalty)
+            .ok_or(ErrorCode::Overflow)?;
+        trader_stats.unstake_penalty = trader_stats
+            .unstake_penalty
+            .checked_add(penalty)
+            .ok_or(ErrorCode::Overflow)?;
+        trader_stats.unstake_available_at = current_time
+            .checked_add(ctx.accounts.config.unstake_cooldown)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Withdraws the pending unstake amount from the staking vault to the
+    /// trader once its cooldown has elapsed.
+    ///
+    /// Any early-unstake penalty withheld meanwhile is moved to the treasury
+    /// or the reward vault of the mint, per
+    /// `config.early_unstake_penalty_to_treasury`, which must then be passed
+    /// as `penalty_destination`.
+    ///
+    /// Extra accounts required by a Token-2022 transfer hook are passed via
+    /// `remaining_accounts`.
+    pub fn unstake_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnstakeTokens<'info>>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        if config.is_paused(PAUSE_STAKING) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        let amount = trader_stats.unstake_pending;
+        let penalty = trader_stats.unstake_penalty;
+        if amount == 0 && penalty == 0 {
+            return Err(ErrorCode::NoPendingUnstake.into());
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        if current_time < trader_stats.unstake_available_at {
+            return Err(ErrorCode::UnstakeCooldownActive.into());
+        }
+        trader_stats.unstake_pending = 0;
+        trader_stats.unstake_penalty = 0;
+        emit!(TokensUnstaked {
+            trader: trader_stats.trader,
+            amount,
+            penalty,
+            staked_amount: trader_stats.staked_amount,
+            timestamp: current_time,
+        });
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        if penalty > 0 {
+            let destination = ctx
+                .accounts
+                .penalty_destination
+                .as_ref()
+                .ok_or(ErrorCode::InvalidPenaltyDestination)?;
+            let expected = if config.early_unstake_penalty_to_treasury {
+                config.treasury
+            } else {
+                Pubkey::find_program_address(
+                    &[b"reward_vault", ctx.accounts.mint.key().as_ref()],
+                    &crate::ID,
+                )
+                .0
+            };
+            if destination.key() != expected {
+                return Err(ErrorCode::InvalidPenaltyDestination.into());
+            }
+            transfer_checked_with_hooks(
+                &ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.staking_vault.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                destination.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.remaining_accounts,
+                penalty,
+                ctx.accounts.mint.decimals,
+                signer_seeds,
+            )?;
+        }
+        if amount == 0 {
+            return Ok(());
+        }
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.staking_vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Calculates a fee discount based on the staked amount, including the
+    /// bonus of a locked stake position.
+    ///
+    /// The discount is that of the highest tier of `config.fee_discount_tiers`
+    /// whose threshold the stake reaches, which is also recorded as
+    /// `fee_tier`; by default 5% to 50% for staking between 1,000 and
+    /// 10,000 $T2E.
+    pub fn calculate_fee_discount(ctx: Context<CalculateFeeDiscount>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let trader_stats = &mut ctx.accounts.trader_stats;
+        let staked = trader_stats.effective_stake(Clock::get()?.unix_timestamp);
+        let (fee_tier, fee_discount) = config.fee_tier(staked);
+        trader_stats.fee_tier = fee_tier;
+        trader_stats.fee_discount = fee_discount;
+        Ok(())
+    }
+
+    /// Closes the signer's TraderStats and refunds its rent. Only allowed once
+    /// nothing is staked, pending withdrawal or awaiting a swap, no stake is
+    /// locked, and no suspension or ban is in effect.
+    pub fn close_trader_stats(ctx: Context<CloseTraderStats>) -> Result<()> {
+        let trader_stats = &ctx.accounts.trader_stats;
+        let current_time = Clock::get()?.unix_timestamp;
+        // A banned trader could otherwise shed the ban by reopening their stats.
+        if trader_stats.banned {
+            return Err(ErrorCode::TraderBanned.into());
+        }
+        if trader_stats.is_suspended(current_time) {
+            return Err(ErrorCode::TraderSuspended.into());
+        }
+        if trader_stats.staked_amount != 0
+            || trader_stats.unstake_pending != 0
+            || trader_stats.unstake_penalty != 0
+            || trader_stats.pending_swap_payout != 0
+            || current_time < trader_stats.locked_until
+        {
+            return Err(ErrorCode::AccountNotEmpty.into());
+        }
+        Ok(())
+    }
+
+    /// Closes one of the signer's stake positions once its lock has expired
+    /// and refunds its rent. Any tokens left in it stay staked outside
+    /// positions, without a lock bonus.
+    pub fn close_stake_position(ctx: Context<CloseStakePosition>) -> Result<()> {
+        let position = &mut ctx.accounts.stake_position;
+        if Clock::get()?.unix_timestamp < position.locked_until {
+            return Err(ErrorCode::StakeLocked.into());
+        }
+        let amount = position.amount;
+        ctx.accounts.trader_stats.release_stake_position(position, amount)
+    }
+
+    /// Takes a snapshot of the current leaderboard ranking into the next
+    /// indexed LeaderboardSnapshot PDA. With a snapshot retention configured,
+    /// the PDAs form a ring buffer and the oldest snapshot is overwritten.
+    ///
+    /// Useful for creating daily, weekly, or monthly leaderboard history.
+    /// Admin or operator only.
+    pub fn snapshot_leaderboard(ctx: Context<SnapshotLeaderboard>) -> Result<()> {
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        ctx.accounts.snapshot.set_inner(LeaderboardSnapshot::capture(
+            ctx.accounts.leaderboard.key(),
+            leaderboard,
+            Clock::get()?.unix_timestamp,
+            ctx.bumps.snapshot,
+        ));
+        ctx.accounts.snapshot.emit_taken(ctx.accounts.snapshot.key());
+        leaderboard.history_count = leaderboard
+            .history_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Closes expired history snapshots of a leaderboard, passed via
+    /// `remaining_accounts`, and refunds their rent to the admin. A snapshot
+    /// is expired once `config.snapshot_retention` newer ones have been
+    /// taken. Admin only.
+    pub fn prune_snapshots<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PruneSnapshots<'info>>,
+    ) -> Result<()> {
+        let retention = ctx.accounts.config.snapshot_retention;
+        if retention == 0 {
+            return Err(ErrorCode::SnapshotRetentionDisabled.into());
+        }
+        let leaderboard_key = ctx.accounts.leaderboard.key();
+        let history_count = ctx.accounts.leaderboard.load()?.history_count;
+        for info in ctx.remaining_accounts.iter() {
+            let snapshot = Account::<LeaderboardSnapshot>::try_from(info)?;
+            if snapshot.leaderboard != leaderboard_key {
+                return Err(ErrorCode::SnapshotNotExpired.into());
+            }
+            if snapshot.index.saturating_add(retention) > history_count {
+                return Err(ErrorCode::SnapshotNotExpired.into());
+            }
+            snapshot.close(ctx.accounts.admin.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    /// Closes a leaderboard history snapshot and refunds its rent to the
+    /// admin. Admin only.
+    pub fn close_leaderboard_snapshot(_ctx: Context<CloseLeaderboardSnapshot>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Flags the current ranking as suspicious during the fraud-proof window.
+    ///
+    /// The TraderStats accounts contradicting the posted ranking must be passed
+    /// via `remaining_accounts` as evidence, and the flagger bonds at least
+    /// `min_dispute_bond` of a reward mint into the flag's bond vault, so a
+    /// dismissed flag's bond can be forfeited into its reward vault.
+    /// Distribution stays blocked until the admin resolves every flag against
+    /// the ranking.
+    pub fn flag_ranking(
+        ctx: Context<FlagRanking>,
+        reason_hash: [u8; 32],
+        bond_amount: u64,
+    ) -> Result<()> {
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        if current_time - leaderboard.last_update >= ctx.accounts.config.distribution_delay {
+            return Err(ErrorCode::DistributionWindowClosed.into());
+        }
+        if bond_amount < ctx.accounts.config.min_dispute_bond {
+            return Err(ErrorCode::DisputeBondTooLow.into());
+        }
+        if ctx.remaining_accounts.is_empty() || ctx.remaining_accounts.len() > MAX_FLAG_EVIDENCE {
+            return Err(ErrorCode::InvalidFlagEvidence.into());
+        }
+
+        // Every evidence account must be a TraderStats account owned by this program.
+        let mut evidence = Vec::with_capacity(ctx.remaining_accounts.len());
+        for acc in ctx.remaining_accounts.iter() {
+            if acc.owner != &crate::ID {
+                return Err(ErrorCode::InvalidFlagEvidence.into());
+            }
+            let data = acc.try_borrow_data()?;
+            TraderStats::try_deserialize(&mut &data[..]).map_err(|_| ErrorCode::InvalidFlagEvidence)?;
+            evidence.push(acc.key());
+        }
+
+        // Remaining accounts carry the evidence, so no hook accounts can be passed.
+        reject_transfer_hook(&ctx.accounts.mint.to_account_info())?;
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.flagger_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.bond_vault.to_account_info(),
+            ctx.accounts.flagger.to_account_info(),
+            &[],
+            bond_amount,
+            ctx.accounts.mint.decimals,
+            &[],
+        )?;
+
+        let flag = &mut ctx.accounts.ranking_flag;
+        flag.leaderboard = ctx.accounts.leaderboard.key();
+        flag.flagger = ctx.accounts.flagger.key();
+        flag.flagger_token_account = ctx.accounts.flagger_token_account.key();
+        flag.ranking_update = leaderboard.last_update;
+        flag.reason_hash = reason_hash;
+        flag.evidence = evidence;
+        flag.flagged_at = current_time;
+        flag.bond_amount = net_of_transfer_fee(&ctx.accounts.mint.to_account_info(), bond_amount)?;
+        flag.resolved = false;
+        flag.upheld = false;
+
+        leaderboard.pending_flags = leaderboard
+            .pending_flags
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Resolves a pending ranking flag. Admin only.
+    ///
+    /// - If upheld, the flagged ranking is discarded and must be recomputed,
+    ///   and the bond is returned.
+    /// - If dismissed, the bond is forfeited into the reward vault.
+    ///
+    /// Distribution of the current ranking may proceed once no flag against it
+    /// is pending; flags against an earlier ranking no longer block it.
+    pub fn resolve_ranking_flag<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveRankingFlag<'info>>,
+        upheld: bool,
+    ) -> Result<()> {
+        let flag = &mut ctx.accounts.ranking_flag;
+        if flag.resolved {
+            return Err(ErrorCode::FlagAlreadyResolved.into());
+        }
+        flag.resolved = true;
+        flag.upheld = upheld;
+
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        if flag.ranking_update == leaderboard.last_update {
+            leaderboard.pending_flags = leaderboard.pending_flags.saturating_sub(1);
+            if upheld {
+                leaderboard.len = 0;
+            }
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        let bond_destination = if upheld {
+            ctx.accounts.flagger_token_account.to_account_info()
+        } else {
+            ctx.accounts.reward_vault.to_account_info()
+        };
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.bond_vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            bond_destination,
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            flag.bond_amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+
+    /// Opens a dispute against a single leaderboard entry.
+    ///
+    /// The challenger bonds at least `min_dispute_bond` of a reward mint into
+    /// the dispute's own `["dispute_bond", dispute]` vault and references the
+    /// entry's on-chain TraderStats, whose recomputed score must differ from
+    /// the posted one. Distribution of the disputed ranking is blocked while
+    /// the dispute is pending; posting a new ranking supersedes it.
+    pub fn open_score_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, OpenScoreDispute<'info>>,
+        rank_index: u32,
+        bond_amount: u64,
+    ) -> Result<()> {
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        let trader_stats = &ctx.accounts.trader_stats;
+        let index = rank_index as usize;
+
+        if bond_amount < ctx.accounts.config.min_dispute_bond {
+            return Err(ErrorCode::DisputeBondTooLow.into());
+        }
+        let posted_trader = *leaderboard
+            .traders()
+            .get(index)
+            .ok_or(ErrorCode::InvalidRankIndex)?;
+        if posted_trader != trader_stats.trader {
+            return Err(ErrorCode::DisputeTraderMismatch.into());
+        }
+        let posted_score = leaderboard.ranking_scores()[index];
+        // Posted scores are decayed as of the ranking update.
+        let config = &ctx.accounts.config;
+        let onchain_score = TraderStatsInput::scored(trader_stats, &config.scoring, leaderboard.last_update)
+        .decayed_score(
+            &config.scoring.weights(),
+            config.score_half_life,
+            leaderboard.last_update,
+        );
+        if onchain_score == posted_score {
+            return Err(ErrorCode::ScoreNotContradicted.into());
+        }
+
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.challenger_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.bond_vault.to_account_info(),
+            ctx.accounts.challenger.to_account_info(),
+            ctx.remaining_accounts,
+            bond_amount,
+            ctx.accounts.mint.decimals,
+            &[],
+        )?;
+
+        let dispute = &mut ctx.accounts.score_dispute;
+        dispute.leaderboard = ctx.accounts.leaderboard.key();
+        dispute.challenger = ctx.accounts.challenger.key();
+        dispute.challenger_token_account = ctx.accounts.challenger_token_account.key();
+        dispute.trader_stats = trader_stats.key();
+        dispute.trader = posted_trader;
+        dispute.ranking_update = leaderboard.last_update;
+        dispute.rank_index = rank_index;
+        dispute.posted_score = posted_score;
+        dispute.claimed_score = onchain_score;
+        dispute.bond_amount = net_of_transfer_fee(&ctx.accounts.mint.to_account_info(), bond_amount)?;
+        dispute.resolved = false;
+        dispute.upheld = false;
+
+        leaderboard.pending_disputes = leaderboard
+            .pending_disputes
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Resolves a score dispute. Admin only.
+    ///
+    /// - If upheld, the entry is corrected and the board re-sorted, the bond is
+    ///   returned and the challenger receives `dispute_reward` from the reward vault.
+    /// - Otherwise the bond is forfeited into the reward vault.
+    ///
+    /// Disputes against an earlier ranking no longer block distribution and
+    /// only settle their bond.
+    pub fn resolve_score_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveScoreDispute<'info>>,
+        upheld: bool,
+    ) -> Result<()> {
+        let dispute = &mut ctx.accounts.score_dispute;
+        if dispute.resolved {
+            return Err(ErrorCode::DisputeAlreadyResolved.into());
+        }
+        dispute.resolved = true;
+        dispute.upheld = upheld;
+
+        let leaderboard = &mut load_board_mut(&ctx.accounts.leaderboard)?;
+        if dispute.ranking_update == leaderboard.last_update {
+            leaderboard.pending_disputes = leaderboard.pending_disputes.saturating_sub(1);
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        let bond_destination = if upheld {
+            ctx.accounts.challenger_token_account.to_account_info()
+        } else {
+            ctx.accounts.reward_vault.to_account_info()
+        };
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.bond_vault.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            bond_destination,
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            dispute.bond_amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )?;
+
+        if !upheld {
+            return Ok(());
+        }
+
+        // Correct the disputed entry if the board has not been recomputed since.
+        let index = dispute.rank_index as usize;
+        if leaderboard.last_update == dispute.ranking_update && index < leaderboard.traders().len() {
+            leaderboard.set_score(index, dispute.claimed_score);
+            let mut ranked_traders: Vec<RankedTrader> = leaderboard
+                .traders()
+                .iter()
+                .zip(leaderboard.ranking_scores().iter())
+                .zip(leaderboard.tiebreaks().iter())
+                .map(|((trader, score), tiebreak)| RankedTrader {
+                    trader: *trader,
+                    score: *score,
+                    tiebreak: *tiebreak,
+                })
+                .collect();
+            ranked_traders.sort_by_key(|rt| rank_key(&rt.trader, rt.score, &rt.tiebreak));
+            leaderboard.set_entries(&ranked_traders);
+            emit_leaderboard_updated(
+                ctx.accounts.leaderboard.key(),
+                leaderboard,
+                Clock::get()?.unix_timestamp,
+            );
+        }
+
+        let dispute_reward = ctx.accounts.config.dispute_reward;
+        if dispute_reward > 0 {
+            let signer_seeds: &[&[&[u8]]] =
+                &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+            transfer_checked_with_hooks(
+                &ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.reward_vault.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.challenger_token_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.remaining_accounts,
+                dispute_reward,
+                ctx.accounts.mint.decimals,
+                signer_seeds,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Adds a co-admin who may perform operational duties (pausing, resolving
+    /// flags and disputes). Super-authority only.
+    pub fn add_co_admin(ctx: Context<UpdateConfig>, co_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if config.co_admins.contains(&co_admin) {
+            return Err(ErrorCode::CoAdminAlreadyExists.into());
+        }
+        if config.co_admins.len() >= MAX_CO_ADMINS {
+            return Err(ErrorCode::TooManyCoAdmins.into());
+        }
+        config.co_admins.push(co_admin);
+        Ok(())
+    }
+
+    /// Removes a co-admin. Super-authority only.
+    pub fn remove_co_admin(ctx: Context<UpdateConfig>, co_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let index = config
+            .co_admins
+            .iter()
+            .position(|key| *key == co_admin)
+            .ok_or(ErrorCode::CoAdminNotFound)?;
+        config.co_admins.swap_remove(index);
+        Ok(())
+    }
+
+    /// Returns a page of leaderboard entries via return data.
+    ///
+    /// `limit` is capped at `MAX_PAGE_SIZE` so the page fits in the return-data limit.
+    pub fn get_leaderboard_page(
+        ctx: Context<GetLeaderboardPage>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<LeaderboardPage> {
+        let leaderboard = &load_board(&ctx.accounts.leaderboard)?;
+        let limit = limit.min(MAX_PAGE_SIZE) as usize;
+        let entries = leaderboard
+            .traders()
+            .iter()
+            .zip(leaderboard.ranking_scores().iter())
+            .enumerate()
+            .skip(offset as usize)
+            .take(limit)
+            .map(|(rank, (trader, score))| LeaderboardEntry {
+                rank: rank as u32,
+                trader: *trader,
+                score: *score,
+            })
+            .collect();
+        Ok(LeaderboardPage {
+            total: leaderboard.len,
+            offset,
+            entries,
+        })
+    }
+
+    /// Returns the rank and score of `trader` (an identity) via return data,
+    /// so other programs can read it after a CPI with `get_return_data`.
+    pub fn get_trader_rank(ctx: Context<GetTraderRank>, trader: Pubkey) -> Result<TraderRank> {
+        let leaderboard = &load_board(&ctx.accounts.leaderboard)?;
+        let position = leaderboard.traders().iter().position(|t| *t == trader);
+        Ok(TraderRank {
+            trader,
+            ranked: position.is_some(),
+            rank: position.unwrap_or_default() as u32,
+            score: position.map_or(0, |rank| leaderboard.ranking_scores()[rank]),
+            total: leaderboard.len,
+        })
+    }
+
+    /// Sets the scoped pause flags (`PAUSE_TRADING`, `PAUSE_STAKING`,
+    /// `PAUSE_REWARDS`). Passing `PAUSE_ALL` declares a full emergency.
+    /// Admin or co-admin only.
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, pause_flags: u8) -> Result<()> {
+        if pause_flags & !PAUSE_ALL != 0 {
+            return Err(ErrorCode::InvalidPauseFlags.into());
+        }
+        ctx.accounts.config.pause_flags = pause_flags;
+        emit!(PauseToggled {
+            authority: ctx.accounts.admin.key(),
+            leaderboard: None,
+            pause_flags,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Queues an emergency withdrawal of the reward vault. Super-authority only.
+    ///
+    /// The queued withdrawal is stored on-chain and can only be executed after
+    /// `EMERGENCY_WITHDRAW_DELAY`, giving users time to react.
+    pub fn queue_emergency_withdraw(
+        ctx: Context<QueueEmergencyWithdraw>,
+        amount: u64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let withdrawal = &mut ctx.accounts.emergency_withdrawal;
+        withdrawal.reward_vault = ctx.accounts.reward_vault.key();
+        withdrawal.destination = ctx.accounts.destination.key();
+        withdrawal.amount = amount;
+        withdrawal.queued_at = current_time;
+        withdrawal.executable_at = current_time
+            .checked_add(EMERGENCY_WITHDRAW_DELAY)
+            .ok_or(ErrorCode::Overflow)?;
+        withdrawal.bump = ctx.bumps.emergency_withdrawal;
+        Ok(())
+    }
+
+    /// Executes a queued emergency withdrawal once its timelock has expired.
+    /// Super-authority only.
+    pub fn execute_emergency_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteEmergencyWithdraw<'info>>,
+    ) -> Result<()> {
+        let withdrawal = &ctx.accounts.emergency_withdrawal;
+        if Clock::get()?.unix_timestamp < withdrawal.executable_at {
+            return Err(ErrorCode::TimelockNotExpired.into());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            withdrawal.amount,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Cancels a queued emergency withdrawal. Super-authority only.
+    pub fn cancel_emergency_withdraw(_ctx: Context<CancelEmergencyWithdraw>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Queues a scoring, emission or pause change that can be executed after
+    /// `CONFIG_CHANGE_DELAY`, giving traders advance notice through the
+    /// `ConfigChangeQueued` event. Super-authority only.
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
+        action: ProposalAction,
+    ) -> Result<()> {
+        action.validate()?;
+        let current_time = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.config;
+        let change = &mut ctx.accounts.config_change;
+        change.id = config.config_change_count;
+        change.action = action;
+        change.queued_at = current_time;
+        change.executable_at = current_time
+            .checked_add(CONFIG_CHANGE_DELAY)
+            .ok_or(ErrorCode::Overflow)?;
+        change.bump = ctx.bumps.config_change;
+        config.config_change_count = config
+            .config_change_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ConfigChangeQueued {
+            config_change: change.key(),
+            id: change.id,
+            action,
+            executable_at: change.executable_at,
+        });
+        Ok(())
+    }
+
+    /// Applies a queued config change once its timelock has expired.
+    /// Super-authority only.
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let change = &ctx.accounts.config_change;
+        if current_time < change.executable_at {
+            return Err(ErrorCode::TimelockNotExpired.into());
+        }
+        ctx.accounts
+            .config
+            .apply_action(change.action, ctx.accounts.admin.key(), current_time)
+    }
+
+    /// Cancels a queued config change. Super-authority only.
+    pub fn cancel_config_change(_ctx: Context<CancelConfigChange>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Creates the admin set of up to `MAX_ADMIN_SET_SIZE` authorities. From
+    /// then on, `admin_pause_rewards`, `slash_trader` and `withdraw_treasury`
+    /// run only with an `AdminAction` approved by `threshold` of them.
+    /// Super-authority only.
+    pub fn init_admin_set(
+        ctx: Context<InitAdminSet>,
+        authorities: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let unique = authorities
+            .iter()
+            .enumerate()
+            .all(|(i, authority)| !authorities[..i].contains(authority));
+        if authorities.len() > MAX_ADMIN_SET_SIZE
+            || !unique
+            || threshold == 0
+            || threshold as usize > authorities.len()
+        {
+            return Err(ErrorCode::InvalidAdminSet.into());
+        }
+        let admin_set = &mut ctx.accounts.admin_set;
+        admin_set.authorities = authorities;
+        admin_set.threshold = threshold;
+        admin_set.action_count = 0;
+        admin_set.bump = ctx.bumps.admin_set;
+        ctx.accounts.config.admin_set = admin_set.key();
+        Ok(())
+    }
+
+    /// Proposes a multisig-gated action, counting as the proposer's approval.
+    /// Admin set members only.
+    pub fn propose_admin_action(
+        ctx: Context<ProposeAdminAction>,
+        action: AdminAction,
+    ) -> Result<()> {
+        let admin_set = &mut ctx.accounts.admin_set;
+        let index = admin_set.authority_index(&ctx.accounts.authority.key())?;
+        let pending = &mut ctx.accounts.admin_action;
+        pending.id = admin_set.action_count;
+        pending.action = action;
+        pending.proposer = ctx.accounts.authority.key();
+        pending.approvals = 1 << index;
+        pending.executed = false;
+        pending.created_at = Clock::get()?.unix_timestamp;
+        pending.bump = ctx.bumps.admin_action;
+        admin_set.action_count = admin_set
+            .action_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(AdminActionApproved {
+            admin_action: pending.key(),
+            action,
+            authority: pending.proposer,
+            approvals: pending.approvals.count_ones() as u8,
+            threshold: admin_set.threshold,
+        });
+        Ok(())
+    }
+
+    /// Approves a pending multisig-gated action. Admin set members only.
+    pub fn approve_admin_action(ctx: Context<ApproveAdminAction>) -> Result<()> {
+        let admin_set = &ctx.accounts.admin_set;
+        let authority = ctx.accounts.authority.key();
+        let bit = 1 << admin_set.authority_index(&authority)?;
+        let pending = &mut ctx.accounts.admin_action;
+        if pending.executed {
+            return Err(ErrorCode::AdminActionExecuted.into());
+        }
+        if pending.approvals & bit != 0 {
+            return Err(ErrorCode::AdminActionAlreadyApproved.into());
+        }
+        pending.approvals |= bit;
+
+        emit!(AdminActionApproved {
+            admin_action: pending.key(),
+            action: pending.action,
+            authority,
+            approvals: pending.approvals.count_ones() as u8,
+            threshold: admin_set.threshold,
+        });
+        Ok(())
+    }
+
+    /// Transfers `amount` out of the program-owned treasury. Requires an
+    /// approved `AdminAction::WithdrawTreasury` while an admin set exists,
+    /// otherwise the super-authority.
+    pub fn withdraw_treasury<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawTreasury<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        authorize_admin_action(
+            &ctx.accounts.config,
+            ctx.accounts.admin_set.as_deref(),
+            ctx.accounts.admin_action.as_deref_mut(),
+            AdminAction::WithdrawTreasury {
+                destination: ctx.accounts.destination.key(),
+                amount,
+            },
+        )?;
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            signer_seeds,
+        )
+    }
+
+    /// Emits ranks `[start, start + count)` of the current board as chunked
+    /// self-CPI events, giving indexers a replayable record of the full ranking
+    /// even after the board is overwritten. Permissionless.
+    pub fn export_rankings(ctx: Context<ExportRankings>, start: u32, count: u32) -> Result<()> {
+        let leaderboard = &load_board(&ctx.accounts.leaderboard)?;
+        let start = start as usize;
+        let end = start
+            .saturating_add(count as usize)
+            .min(leaderboard.traders().len());
+        if start >= end {
+            return Err(ErrorCode::InvalidRankIndex.into());
+        }
+
+        let mut chunk_start = start;
+        while chunk_start < end {
+            let chunk_end = (chunk_start + RANKING_EXPORT_CHUNK).min(end);
+            let entries = (chunk_start..chunk_end)
+                .map(|rank| LeaderboardEntry {
+                    rank: rank as u32,
+                    trader: leaderboard.traders()[rank],
+                    score: leaderboard.ranking_scores()[rank],
+                })
+                .collect();
+            emit_cpi!(RankingChunk {
+                leaderboard: ctx.accounts.leaderboard.key(),
+                ranking_update: leaderboard.last_update,
+                total_ranked: leaderboard.len,
+                entries,
+            });
+            chunk_start = chunk_end;
+        }
+        Ok(())
+    }
+
+    /// Creates a sponsored competition and escrows its prize pool in a
+    /// competition-scoped vault, outside the global reward vault.
+    pub fn create_competition(
+        ctx: Context<CreateCompetition>,
+        competition_id: u64,
+        start_ts: i64,
+        end_ts: i64,
+        prize_amount: u64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        if start_ts < current_time || end_ts <= start_ts {
+            return Err(ErrorCode::InvalidCompetitionWindow.into());
+        }
+        if prize_amount == 0 {
+            return Err(ErrorCode::InvalidPrizeAmount.into());
+        }
+
+        let competition = &mut ctx.accounts.competition;
+        competition.sponsor = ctx.accounts.sponsor.key();
+        competition.competition_id = competition_id;
+        competition.prize_vault = ctx.accounts.prize_vault.key();
+        competition.prize_amount = prize_amount;
+        competition.start_ts = start_ts;
+        competition.end_ts = end_ts;
+        competition.status = CompetitionStatus::Open;
+        competition.bump = ctx.bumps.competition;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.sponsor_token_account.to_account_info(),
+                    to: ctx.accounts.prize_vault.to_account_info(),
+                    authority: ctx.accounts.sponsor.to_account_info(),
+                },
+            ),
+            prize_amount,
+        )?;
+        Ok(())
+    }
+
+    /// Cancels a competition before it starts and refunds the prize pool to
+    /// the sponsor. Sponsor only.
+    pub fn cancel_competition(ctx: Context<CancelCompetition>) -> Result<()> {
+        let competition = &ctx.accounts.competition;
+        if competition.status != CompetitionStatus::Open {
+            return Err(ErrorCode::CompetitionClosed.into());
+        }
+        if Clock::get()?.unix_timestamp >= competition.start_ts {
+            return Err(ErrorCode::CompetitionAlreadyStarted.into());
+        }
+
+        let sponsor = competition.sponsor;
+        let competition_id = competition.competition_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"competition",
+            sponsor.as_ref(),
+            &competition_id,
+            &[competition.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.prize_vault.to_account_info(),
+                    to: ctx.accounts.sponsor_token_account.to_account_info(),
+                    authority: ctx.accounts.competition.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            ctx.accounts.prize_vault.amount,
+        )?;
+
+        ctx.accounts.competition.status = CompetitionStatus::Cancelled;
+        Ok(())
+    }
+
+    /// Releases the prize pool to the winners once the competition has ended.
+    /// Admin only.
+    ///
+    /// Expects each winner's token account via `remaining_accounts`, in the
+    /// same order as `payouts`, which must add up to the full prize pool.
+    pub fn finalize_competition<'info>(
+        ctx: Context<'_, '_, '_, 'info, FinalizeCompetition<'info>>,
+        payouts: Vec<u64>,
+    ) -> Result<()> {
+        let competition = &ctx.accounts.competition;
+        if competition.status != CompetitionStatus::Open {
+            return Err(ErrorCode::CompetitionClosed.into());
+        }
+        if Clock::get()?.unix_timestamp < competition.end_ts {
+            return Err(ErrorCode::CompetitionNotEnded.into());
+        }
+        if payouts.is_empty()
+            || payouts.len() > MAX_COMPETITION_WINNERS
+            || payouts.len() != ctx.remaining_accounts.len()
+        {
+            return Err(ErrorCode::InvalidCompetitionPayouts.into());
+        }
+        let total = payouts
+            .iter()
+            .try_fold(0u64, |acc, p| acc.checked_add(*p))
+            .ok_or(ErrorCode::Overflow)?;
+        if total != ctx.accounts.prize_vault.amount {
+            return Err(ErrorCode::InvalidCompetitionPayouts.into());
+        }
+
+        let sponsor = competition.sponsor;
+        let competition_id = competition.competition_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"competition",
+            sponsor.as_ref(),
+            &competition_id,
+            &[competition.bump],
+        ]];
+        for (payout, winner_token_account) in payouts.iter().zip(ctx.remaining_accounts.iter()) {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.prize_vault.to_account_info(),
+                        to: winner_token_account.clone(),
+                        authority: ctx.accounts.competition.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                *payout,
+            )?;
+        }
+
+        ctx.accounts.competition.status = CompetitionStatus::Finalized;
+        Ok(())
+    }
+
+    /// Allows an admin to pause or unpause reward distribution in emergencies.
+    /// Needs an approved `AdminAction::PauseRewards` while an admin set exists.
+    pub fn admin_pause_rewards(ctx: Context<AdminPauseRewards>, paused: bool) -> Result<()> {
+        authorize_admin_action(
+            &ctx.accounts.config,
+            ctx.accounts.admin_set.as_deref(),
+            ctx.accounts.admin_action.as_deref_mut(),
+            AdminAction::PauseRewards {
+                leaderboard: ctx.accounts.leaderboard.key(),
+                paused,
+            },
+        )?;
+        ctx.accounts.leaderboard.load_mut()?.emergency_pause = paused as u8;
+        emit!(PauseToggled {
+            authority: ctx.accounts.admin.key(),
+            leaderboard: Some(ctx.accounts.leaderboard.key()),
+            pause_flags: if paused { PAUSE_REWARDS } else { 0 },
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Publishes a quest: reach `target` volume or trades within one
+    /// `period` (e.g. a day) to earn `reward_amount` from the reward vault
+    /// passed, once per period. `end_ts` of 0 leaves the quest open-ended.
+    /// Admin only.
+    pub fn create_quest(
+        ctx: Context<CreateQuest>,
+        quest_id: u64,
+        kind: QuestKind,
+        target: u64,
+        reward_amount: u64,
+        period: i64,
+        end_ts: i64,
+    ) -> Result<()> {
+        if target == 0 || reward_amount == 0 || period <= 0 {
+            return Err(ErrorCode::InvalidQuest.into());
+        }
+        ctx.accounts.quest.set_inner(Quest {
+            quest_id,
+            kind,
+            target,
+            reward_amount,
+            reward_mint: ctx.accounts.reward_vault.mint,
+            period,
+            end_ts,
+            active: true,
+            completions: 0,
+            bump: ctx.bumps.quest,
+        });
+        Ok(())
+    }
+
+    /// Changes a quest's reward and end, or pauses it. Progress already
+    /// completed in the current period stays claimable. Admin only.
+    pub fn update_quest(
+        ctx: Context<UpdateQuest>,
+        reward_amount: u64,
+        end_ts: i64,
+        active: bool,
+    ) -> Result<()> {
+        if reward_amount == 0 {
+            return Err(ErrorCode::InvalidQuest.into());
+        }
+        let quest = &mut ctx.accounts.quest;
+        quest.reward_amount = reward_amount;
+        quest.end_ts = end_ts;
+        quest.active = active;
+        Ok(())
+    }
+
+    /// Creates the signer's progress account for a quest, which
+    /// `record_trade` advances when passed alongside the quest.
+    pub fn init_quest_progress(ctx: Context<InitQuestProgress>) -> Result<()> {
+        ctx.accounts.quest_progress.set_inner(QuestProgress {
+            quest: ctx.accounts.quest.key(),
+            trader: ctx.accounts.trader.key(),
+            period: 0,
+            progress: 0,
+            claimed: false,
+            bump: ctx.bumps.quest_progress,
+        });
+        Ok(())
+    }
+
+    /// Pays the reward of a quest completed in the current period from the
+    /// reward vault. Once per period.
+    pub fn claim_quest_reward<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimQuestReward<'info>>,
+    ) -> Result<()> {
+        if ctx.accounts.config.is_paused(PAUSE_REWARDS) {
+            return Err(ErrorCode::EmergencyPaused.into());
+        }
+        let quest = &mut ctx.accounts.quest;
+        let quest_progress = &mut ctx.accounts.quest_progress;
+        if quest_progress.period != quest.period_index(Clock::get()?.unix_timestamp)
+            || quest_progress.progress < quest.target
+        {
+            return Err(ErrorCode::QuestNotCompleted.into());
+        }
+        if quest_progress.claimed {
+            return Err(ErrorCode::QuestRewardClaimed.into());
+        }
+        quest_progress.claimed = true;
+        quest.completions = quest.completions.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[ctx.bumps.vault_authority]]];
+        transfer_checked_with_hooks(
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+            ctx.remaining_accounts,
+            quest.reward_amount,
+            ctx.accounts.reward_mint.decimals,
+            signer_seeds,
+        )
+    }
+}
+
+/// Maximum number of evidence accounts attached to a ranking flag.
+pub const MAX_FLAG_EVIDENCE: usize = 8;
+
+/// Maximum number of co-admins stored on the config.
+pub const MAX_CO_ADMINS: usize = 8;
+
+/// Maximum entries returned by one `get_leaderboard_page` call (fits in 1024 bytes of return data).
+pub const MAX_PAGE_SIZE: u32 = 20;
+
+/// Scoped pause flags stored in `Config::pause_flags`.
+pub const PAUSE_TRADING: u8 = 1 << 0;
+pub const PAUSE_STAKING: u8 = 1 << 1;
+pub const PAUSE_REWARDS: u8 = 1 << 2;
+pub const PAUSE_ALL: u8 = PAUSE_TRADING | PAUSE_STAKING | PAUSE_REWARDS;
+
+/// Delay between queueing and executing an emergency vault withdrawal (3 days).
+pub const EMERGENCY_WITHDRAW_DELAY: i64 = 3 * 24 * 60 * 60;
+
+/// Delay between queueing and executing a timelocked config change (48 hours).
+pub const CONFIG_CHANGE_DELAY: i64 = 48 * 60 * 60;
+
+/// Trade timestamps kept per trader for the sliding rate-limit window, which
+/// caps `Config::rate_window_trades`.
+pub const MAX_RATE_WINDOW_TRADES: usize = 16;
+
+/// Highest volume multiplier of a registered market: 3x.
+pub const MAX_MARKET_MULTIPLIER_BPS: u16 = 30_000;
+
+/// Role flags stored in `Role::roles`.
+pub const ROLE_OPERATOR: u8 = 1 << 0;
+pub const ROLE_KEEPER: u8 = 1 << 1;
+pub const ROLE_ALL: u8 = ROLE_OPERATOR | ROLE_KEEPER;
+
+/// Maximum number of authorities in the admin set (one approval bit each).
+pub const MAX_ADMIN_SET_SIZE: usize = 16;
+
+/// Maximum reward-share boost a trader can hold from redeemed loyalty points.
+pub const MAX_LOYALTY_BOOST_BPS: u16 = 2_000;
+
+/// Maximum length in bytes of a trader profile's avatar/metadata URI.
+pub const MAX_PROFILE_URI_LEN: usize = 128;
+
+/// Minimum time between two endorsements by the same trader (1 day).
+pub const ENDORSEMENT_COOLDOWN: i64 = 24 * 60 * 60;
+
+/// Length of a streak day; days start at midnight UTC.
+pub const STREAK_DAY: i64 = 24 * 60 * 60;
+
+/// Default minimum time between two trades of a trader.
+pub const DEFAULT_TRADE_COOLDOWN: i64 = 10;
+
+/// Default minimum time between two leaderboard updates (10 minutes).
+pub const DEFAULT_UPDATE_INTERVAL: i64 = 600;
+
+/// Default time between reward halvings (6 emission epochs).
+pub const DEFAULT_HALVING_PERIOD: i64 =
+    t2e_core::EMISSION_EPOCH_LENGTH * t2e_core::HALVING_INTERVAL_EPOCHS;
+
+/// Capacity of the fee discount tier table.
+pub const MAX_FEE_DISCOUNT_TIERS: usize = 8;
+
+/// Default fee discount tiers: 5%, 10%, 25% and 50% from 1,000, 2,500,
+/// 5,000 and 10,000 staked $T2E.
+pub const DEFAULT_FEE_DISCOUNT_TIERS: [FeeDiscountTier; 4] = [
+    FeeDiscountTier { min_stake: 1_000, discount: 5 },
+    FeeDiscountTier { min_stake: 2_500, discount: 10 },
+    FeeDiscountTier { min_stake: 5_000, discount: 25 },
+    FeeDiscountTier { min_stake: 10_000, discount: 50 },
+];
+
+/// Most entry slots a leaderboard account may hold.
+pub const MAX_LEADERBOARD_ENTRIES: u32 = 4096;
+
+/// Most entry slots one `grow_leaderboard` call adds, keeping the realloc
+/// within the runtime's per-instruction limit of 10 KiB.
+pub const MAX_LEADERBOARD_GROWTH: u32 = 160;
+
+/// Default number of entries kept per ranking.
+pub const DEFAULT_MAX_LEADERBOARD_SIZE: u32 = 1000;
+
+/// Default cooldown between an unstake request and the withdrawal (7 days).
+pub const DEFAULT_UNSTAKE_COOLDOWN: i64 = 7 * 24 * 60 * 60;
+
+/// Staked $T2E per unit of endorsement weight.
+pub const ENDORSEMENT_STAKE_UNIT: u64 = 1000;
+
+/// Leaderboard entries per `RankingChunk` event emitted by `export_rankings`.
+pub const RANKING_EXPORT_CHUNK: usize = 50;
+
+/// Raydium AMM v4 program, accepted by the AMM swap adapter.
+pub mod raydium_amm_v4 {
+    use super::*;
+    declare_id!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+}
+
+/// Orca Whirlpool program, accepted by the AMM swap adapter.
+pub mod orca_whirlpool {
+    use super::*;
+    declare_id!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+}
+
+/// Drift v2 program, accepted as a perp venue for funding payments.
+pub mod drift {
+    use super::*;
+    declare_id!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
+}
+
+/// Byte offset of `authority` in a Drift `User` account.
+pub const DRIFT_USER_AUTHORITY_OFFSET: usize = 8;
+
+/// Byte offset of `cumulative_perp_funding` (i64) in a Drift `User` account.
+pub const DRIFT_USER_CUMULATIVE_FUNDING_OFFSET: usize = 5016;
+
+/// Pyth Solana receiver program, owner of `PriceUpdateV2` accounts.
+pub mod pyth_receiver {
+    use super::*;
+    declare_id!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+}
+
+/// Byte offset of the verification level in a `PriceUpdateV2` account.
+pub const PYTH_VERIFICATION_LEVEL_OFFSET: usize = 40;
+
+/// Byte offset of the price message in a fully verified `PriceUpdateV2` account.
+pub const PYTH_PRICE_MESSAGE_OFFSET: usize = 41;
+
+/// Decimals of oracle-normalized (USD) volume.
+pub const NORMALIZED_VOLUME_DECIMALS: i32 = 6;
+
+/// Minimum seconds between trades for approved market makers (10 for everyone else).
+pub const MARKET_MAKER_TRADE_INTERVAL: i64 = 1;
+
+pub use t2e_core::{MARKET_MAKER_PNL_WEIGHT_BPS, MARKET_MAKER_VOLUME_WEIGHT_BPS};
+
+/// Longest temporary suspension a moderator can impose (90 days).
+pub const MAX_SUSPENSION: i64 = 90 * 24 * 60 * 60;
+
+/// Maximum share of a referee's rewards routed to the referrer (20%).
+pub const MAX_REFERRAL_REWARD_BPS: u16 = 2_000;
+
+/// Length bounds of a vanity referral code.
+pub const MIN_REFERRAL_CODE_LEN: usize = 3;
+pub const MAX_REFERRAL_CODE_LEN: usize = 16;
+
+/// Minimum seconds between affiliate tier recalculations (7 days).
+pub const AFFILIATE_RECALC_INTERVAL: i64 = 7 * 24 * 60 * 60;
+
+/// Referees who traded within this window count as active (30 days).
+pub const AFFILIATE_ACTIVE_WINDOW: i64 = 30 * 24 * 60 * 60;
+
+/// Silver and gold affiliate tier thresholds.
+pub const SILVER_MIN_REFEREES: u32 = 10;
+pub const SILVER_MIN_VOLUME: u64 = 100_000;
+pub const GOLD_MIN_REFEREES: u32 = 50;
+pub const GOLD_MIN_VOLUME: u64 = 1_000_000;
+
+/// Seed of the PDA a partner program signs `record_partner_trade` CPIs with,
+/// derived under the partner's own program id.
+pub const PARTNER_SIGNER_SEED: &[u8] = b"t2e_partner";
+
+/// Maximum number of leaderboards in the global registry.
+pub const MAX_REGISTERED_LEADERBOARDS: usize = 64;
+
+/// Entries kept per history snapshot; accounts created by a program are
+/// limited to 10 KiB.
+pub const MAX_SNAPSHOT_ENTRIES: usize = 200;
+
+/// Maximum number of prize recipients per competition.
+pub const MAX_COMPETITION_WINNERS: usize = 10;
+
+/// Seed of the PDA that owns program-controlled token vaults.
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault_authority";
+
+/// Seed of the PDA that is the mint authority of achievement badges.
+pub const BADGE_AUTHORITY_SEED: &[u8] = b"badge_authority";
+
+/// Number of `Achievement` variants.
+pub const ACHIEVEMENT_COUNT: usize = 3;
+
+/// Lifetime volume of the `FirstMillionVolume` achievement.
+pub const ACHIEVEMENT_VOLUME: u64 = 1_000_000;
+
+/// Lifetime trade count of the `HundredTrades` achievement.
+pub const ACHIEVEMENT_TRADE_COUNT: u64 = 100;
+
+/// Ranks paid in a distribution that earn the `TopTenFinish` achievement.
+pub const ACHIEVEMENT_TOP_RANKS: usize = 10;
+
+/// Fixed-point scale of `Config::staking_reward_index`.
+pub const STAKING_INDEX_SCALE: u128 = 1_000_000_000_000;
+
+/// Seconds per year over which `Config::staking_apr_bps` accrues.
+pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+
+pub use t2e_core::halved_reward;
+
+/// Deserializes TraderStats accounts into ranking inputs keyed by identity,
+/// leaving out banned traders and traders suspended at `current_time`.
+/// Inputs are scored per `scoring`; see `TraderStatsInput::scored`. Fails if
+/// the same account is passed more than once.
+pub fn load_trader_stats_inputs(
+    accounts: &[AccountInfo],
+    scoring: &ScoringConfig,
+    current_time: i64,
+) -> Result<Vec<TraderStatsInput>> {
+    let mut inputs = Vec::with_capacity(accounts.len());
+    let mut seen: Vec<&Pubkey> = Vec::with_capacity(accounts.len());
+    for acc in accounts.iter() {
+        if acc.owner != &crate::ID {
+            return Err(ErrorCode::TraderStatsNotFound.into());
+        }
+        if seen.contains(&acc.key) {
+            return Err(ErrorCode::DuplicateTraderStats.into());
+        }
+        seen.push(acc.key);
+        let data = acc.try_borrow_data()?;
+        let stats = TraderStats::try_deserialize(&mut &data[..])
+            .map_err(|_| ErrorCode::TraderStatsNotFound)?;
+        if stats.is_excluded(current_time) {
+            continue;
+        }
+        inputs.push(TraderStatsInput {
+            trader: stats.identity(),
+            ..TraderStatsInput::scored(&stats, scoring, current_time)
+        });
+    }
+    Ok(inputs)
+}
+
+/// Builds ranking inputs for a market-scoped leaderboard from consecutive
+/// (TraderStats, MarketStats) pairs: volume, execution time and P&L come from
+/// the market, staking, reputation and market-maker status from the trader.
+/// Markets only track the average execution time; risk-adjusted P&L, win
+/// rate and day streak reflect the trader's activity across all markets.
+/// Fails if the same TraderStats account is passed more than once.
+pub fn load_market_stats_inputs(
+    accounts: &[AccountInfo],
+    market: &Pubkey,
+    scoring: &ScoringConfig,
+    current_time: i64,
+) -> Result<Vec<TraderStatsInput>> {
+    if !accounts.len().is_multiple_of(2) {
+        return Err(ErrorCode::MarketStatsMismatch.into());
+    }
+    let mut inputs = Vec::with_capacity(accounts.len() / 2);
+    let mut seen: Vec<&Pubkey> = Vec::with_capacity(accounts.len() / 2);
+    for pair in accounts.chunks(2) {
+        if pair[0].owner != &crate::ID || pair[1].owner != &crate::ID {
+            return Err(ErrorCode::TraderStatsNotFound.into());
+        }
+        if seen.contains(&pair[0].key) {
+            return Err(ErrorCode::DuplicateTraderStats.into());
+        }
+        seen.push(pair[0].key);
+        let stats = TraderStats::try_deserialize(&mut &pair[0].try_borrow_data()?[..])
+            .map_err(|_| ErrorCode::TraderStatsNotFound)?;
+        let market_stats = MarketStats::try_deserialize(&mut &pair[1].try_borrow_data()?[..])
+            .map_err(|_| ErrorCode::MarketStatsMismatch)?;
+        if market_stats.market != *market || market_stats.trader != stats.trader {
+            return Err(ErrorCode::MarketStatsMismatch.into());
+        }
+        if stats.is_excluded(current_time) {
+            continue;
+        }
+        inputs.push(TraderStatsInput {
+            trader: stats.identity(),
+            total_volume: market_stats.total_volume,
+            average_execution_time: market_stats.average_execution_time,
+            pnl: market_stats.pnl,
+            last_trade: market_stats.last_trade,
+            ..TraderStatsInput::scored(&stats, scoring, current_time)
+        });
+    }
+    Ok(inputs)
+}
+
+/// Fails unless the current ranking of `leaderboard` may be paid out against
+/// `snapshot`: rewards are not paused, no flag or dispute is pending, the
+/// fraud-proof window has elapsed, the ranking is not stale, and the unpaid
+/// snapshot captures exactly the current board.
+pub fn check_payable<D: std::ops::Deref<Target = [u8]>>(
+    leaderboard: &Board<D>,
+    config: &Config,
+    snapshot: &RankingSnapshot,
+    current_time: i64,
+) -> Result<()> {
+    if leaderboard.emergency_pause != 0 || config.is_paused(PAUSE_REWARDS) {
+        return Err(ErrorCode::EmergencyPaused.into());
+    }
+    if leaderboard.pending_flags > 0 || leaderboard.pending_disputes > 0 {
+        return Err(ErrorCode::RankingFlagged.into());
+    }
+    if current_time - leaderboard.last_update < config.distribution_delay {
+        return Err(ErrorCode::DistributionWindowOpen.into());
+    }
+    if config.max_ranking_age > 0 && current_time - leaderboard.last_update > config.max_ranking_age {
+        return Err(ErrorCode::RankingStale.into());
+    }
+    if snapshot.paid_at != 0 {
+        return Err(ErrorCode::SnapshotAlreadyPaid.into());
+    }
+    // Fresh snapshots of an already paid ranking must not pay it again.
+    if leaderboard.last_paid_update == leaderboard.last_update {
+        return Err(ErrorCode::RankingAlreadyPaid.into());
+    }
+    if snapshot.ranking_update != leaderboard.last_update
+        || snapshot.board_hash != leaderboard.board_hash()
+    {
+        return Err(ErrorCode::SnapshotMismatch.into());
+    }
+    Ok(())
+}
+
+/// Reward weight of each winner in ranking order: their score, boosted for
+/// holders of the current season pass and by any redeemed loyalty boost.
+/// Suspended and banned traders keep their rank but get zero weight.
+pub fn reward_weights<'a>(
+    scores: &[u64],
+    winners: impl Iterator<Item = &'a TraderStats>,
+    config: &Config,
+    current_time: i64,
+) -> Result<Vec<u128>> {
+    let mut weights = Vec::new();
+    for (score, trader_stats) in scores.iter().zip(winners) {
+        if trader_stats.is_excluded(current_time) {
+            weights.push(0);
+            continue;
+        }
+        let weight = t2e_core::boosted_weight(*score, trader_stats.reward_boost_bps(config))
+            .ok_or(ErrorCode::Overflow)?;
+        weights.push(weight);
+    }
+    if weights.iter().all(|w| *w == 0) {
+        return Err(ErrorCode::NoValidScores.into());
+    }
+    Ok(weights)
+}
+
+/// Splits `pool` pro rata to `weights`. The rounding remainder is returned as
+/// the treasury's cut if a treasury is configured, and otherwise added to the
+/// top trader with a nonzero weight, so the shares always add up to `pool`.
+pub fn split_pool(weights: &[u128], pool: u64, treasury_configured: bool) -> Result<(Vec<u64>, u64)> {
+    let mut rewards = vec![0u64; weights.len()];
+    let remainder =
+        t2e_core::split_rewards(weights, pool, &mut rewards).ok_or(ErrorCode::Overflow)?;
+    if treasury_configured {
+        return Ok((rewards, remainder));
+    }
+    let top = weights.iter().position(|w| *w > 0).unwrap_or(0);
+    rewards[top] = rewards[top].checked_add(remainder).ok_or(ErrorCode::Overflow)?;
+    Ok((rewards, 0))
+}
+
+/// Emits the `EpochSummary` of a payout to the top `traders_paid` traders.
+#[allow(clippy::too_many_arguments)]
+pub fn emit_epoch_summary<D: std::ops::Deref<Target = [u8]>>(
+    leaderboard_key: Pubkey,
+    leaderboard: &Board<D>,
+    epoch: i64,
+    traders_paid: usize,
+    total_distributed: u64,
+    burned: u64,
+    treasury_cut: u64,
+    timestamp: i64,
+) -> Result<()> {
+    let total_score = leaderboard
+        .ranking_scores()
+        .iter()
+        .take(traders_paid)
+        .try_fold(0u64, |acc, score| acc.checked_add(*score))
+        .ok_or(ErrorCode::Overflow)?;
+    let mut top_traders = [Pubkey::default(); 3];
+    let mut top_scores = [0u64; 3];
+    for (i, (trader, score)) in leaderboard
+        .traders()
+        .iter()
+        .zip(leaderboard.ranking_scores().iter())
+        .take(3)
+        .enumerate()
+    {
+        top_traders[i] = *trader;
+        top_scores[i] = *score;
+    }
+    emit!(EpochSummary {
+        leaderboard: leaderboard_key,
+        epoch,
+        ranking_update: leaderboard.last_update,
+        traders_ranked: leaderboard.len,
+        traders_paid: traders_paid as u32,
+        total_score,
+        total_distributed,
+        burned,
+        treasury_cut,
+        top_traders,
+        top_scores,
+        timestamp,
+    });
+    Ok(())
+}
+
+/// Emits `LeaderboardUpdated` with the top page of the board just written.
+pub fn emit_leaderboard_updated<D: std::ops::Deref<Target = [u8]>>(
+    leaderboard_key: Pubkey,
+    leaderboard: &Board<D>,
+    timestamp: i64,
+) {
+    let top_entries = leaderboard
+        .traders()
+        .iter()
+        .zip(leaderboard.ranking_scores().iter())
+        .take(MAX_PAGE_SIZE as usize)
+        .enumerate()
+        .map(|(rank, (trader, score))| LeaderboardEntry {
+            rank: rank as u32,
+            trader: *trader,
+            score: *score,
+        })
+        .collect();
+    emit!(LeaderboardUpdated {
+        leaderboard: leaderboard_key,
+        ranking_update: leaderboard.last_update,
+        traders_ranked: leaderboard.len,
+        board_hash: leaderboard.board_hash(),
+        top_entries,
+        timestamp,
+    });
+}
+
+/// Verifies a Merkle proof of the reward leaf `(trader, amount)` against `root`.
+pub fn verify_reward_proof(proof: &[[u8; 32]], root: &[u8; 32], trader: &Pubkey, amount: u64) -> bool {
+    use anchor_lang::solana_program::hash::hashv;
+    let mut node = hashv(&[&[0u8], trader.as_ref(), &amount.to_le_bytes()]).to_bytes();
+    for sibling in proof {
+        let (a, b) = if node <= *sibling { (&node, sibling) } else { (sibling, &node) };
+        node = hashv(&[&[1u8], a, b]).to_bytes();
+    }
+    node == *root
+}
+
+/// Aggregates ranking inputs sharing a trader key into one entry per identity.
+pub fn merge_identities(trader_stats_list: Vec<TraderStatsInput>) -> Result<Vec<TraderStatsInput>> {
+    let mut identities: Vec<TraderStatsInput> = Vec::with_capacity(trader_stats_list.len());
+    for ts in trader_stats_list {
+        match identities.iter_mut().find(|existing| existing.trader == ts.trader) {
+            Some(existing) => existing.merge(&ts)?,
+            None => identities.push(ts),
+        }
+    }
+    Ok(identities)
+}
+
+/// Emits the per-component score of each identity in `identities`.
+pub fn emit_score_breakdowns(
+    leaderboard: Pubkey,
+    identities: &[TraderStatsInput],
+    weights: &t2e_core::ScoringWeights,
+    timestamp: i64,
+) {
+    for ts in identities {
+        let breakdown = ts.score_breakdown(weights);
+        emit!(ScoreBreakdownRecorded {
+            leaderboard,
+            trader: ts.trader,
+            volume_score: breakdown.volume_score,
+            pnl_score: breakdown.pnl_score,
+            staking_bonus: breakdown.staking_bonus,
+            win_rate_bonus: breakdown.win_rate_bonus,
+            streak_bonus: breakdown.streak_bonus,
+            volume_weight_bps: breakdown.volume_weight_bps as u16,
+            pnl_weight_bps: breakdown.pnl_weight_bps as u16,
+            score: breakdown.total(),
+            timestamp,
+        });
+    }
+}
+
+/// Sets a trader's ban flag and emits a `TraderBanUpdated` event.
+fn set_trader_ban(trader_stats: &mut TraderStats, banned: bool) -> Result<()> {
+    trader_stats.banned = banned;
+    emit!(TraderBanUpdated {
+        trader: trader_stats.trader,
+        banned,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Drops the identities of the banned TraderStats among `accounts` from
+/// `identities`. Stats of linked wallets are aggregated under the primary
+/// wallet, so banning any wallet of an identity unranks the whole identity.
+pub fn exclude_banned(
+    accounts: &[AccountInfo],
+    identities: Vec<TraderStatsInput>,
+) -> Result<Vec<TraderStatsInput>> {
+    let mut banned = Vec::with_capacity(accounts.len());
+    for acc in accounts.iter() {
+        if acc.owner != &crate::ID {
+            return Err(ErrorCode::TraderStatsNotFound.into());
+        }
+        let stats = TraderStats::try_deserialize(&mut &acc.try_borrow_data()?[..])
+            .map_err(|_| ErrorCode::TraderStatsNotFound)?;
+        if stats.banned {
+            banned.push(stats.identity());
+        }
+    }
+    Ok(identities
+        .into_iter()
+        .filter(|ts| !banned.contains(&ts.trader))
+        .collect())
+}
+
+/// Drops identities whose stake is below `min_stake`, emitting a
+/// `TraderExcludedFromRanking` event for each so they know why they're unranked.
+pub fn exclude_understaked(
+    leaderboard: Pubkey,
+    identities: Vec<TraderStatsInput>,
+    min_stake: u64,
+    timestamp: i64,
+) -> Vec<TraderStatsInput> {
+    identities
+        .into_iter()
+        .filter(|ts| {
+            if ts.staked_amount >= min_stake {
+                return true;
+            }
+            emit!(TraderExcludedFromRanking {
+                leaderboard,
+                trader: ts.trader,
+                staked_amount: ts.staked_amount,
+                min_stake,
+                timestamp,
+            });
+            false
+        })
+        .collect()
+}
+
+/// Applies a trade recorded by `record_trade` or `record_partner_trade`:
+/// scales its volume by the registered market's multiplier, applies it to
+/// `trader_stats`, and carries it into the optional pair, market, season,
+/// quest and live-ranking accounts. `quests` holds (Quest, QuestProgress)
+/// pairs.
+#[allow(clippy::too_many_arguments)]
+pub fn book_trade<'info>(
+    config: &Config,
+    trader_stats: &mut TraderStats,
+    mut pair_volume: Option<&mut PairVolume>,
+    mut market_stats: Option<&mut MarketStats>,
+    registered_market: Option<&Market>,
+    season: Option<&Season>,
+    season_stats: Option<&mut TraderSeasonStats>,
+    leaderboard: Option<&AccountLoader<'info, Leaderboard>>,
+    quests: &'info [AccountInfo<'info>],
+    volume: u64,
+    execution_time: u64,
+    pnl: i64,
+    venue: VenueType,
+    pair: Option<Pubkey>,
+    market: Option<Pubkey>,
+) -> Result<()> {
+    let volume = match (market, registered_market) {
+        (Some(market), Some(registered)) if registered.market == market => {
+            registered.scale_volume(volume)
+        }
+        (_, None) if config.registered_markets == 0 => volume,
+        _ => return Err(ErrorCode::MarketNotRegistered.into()),
+    };
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let (volume, pnl, weighted) = trader_stats.apply_trade(
+        config,
+        venue,
+        volume,
+        execution_time,
+        pnl,
+        current_time,
+    )?;
+    let execution_time = weighted.then_some(execution_time);
+
+    // Update the optional per-pair breakdown.
+    match (pair, pair_volume.as_mut()) {
+        (Some(pair), Some(pair_volume)) => {
+            if pair_volume.pair != pair || pair_volume.trader != trader_stats.trader {
+                return Err(ErrorCode::PairVolumeMismatch.into());
+            }
+            pair_volume.volume = pair_volume
+                .volume
+                .checked_add(volume)
+                .ok_or(ErrorCode::Overflow)?;
+            if weighted {
+                pair_volume.trade_count = pair_volume
+                    .trade_count
+                    .checked_add(1)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+            pair_volume.pnl = pair_volume
+                .pnl
+                .checked_add(pnl)
+                .ok_or(ErrorCode::Overflow)?;
+            pair_volume.last_trade = current_time;
+        }
+        (None, None) => {}
+        _ => return Err(ErrorCode::PairVolumeMismatch.into()),
+    }
+
+    match (market, market_stats.as_mut()) {
+        (Some(market), Some(market_stats)) => {
+            if market_stats.market != market || market_stats.trader != trader_stats.trader {
+                return Err(ErrorCode::MarketStatsMismatch.into());
+            }
+            market_stats.accumulate(volume, execution_time, pnl)?;
+            market_stats.last_trade = current_time;
+        }
+        (None, None) => {}
+        _ => return Err(ErrorCode::MarketStatsMismatch.into()),
+    }
+
+    match (season, season_stats) {
+        (Some(season), Some(season_stats)) => {
+            if season.index != config.active_season
+                || season_stats.season != season.index
+                || season_stats.trader != trader_stats.trader
+            {
+                return Err(ErrorCode::SeasonStatsMismatch.into());
+            }
+            if season.is_live(current_time) {
+                season_stats.accumulate(volume, execution_time, pnl)?;
+            }
+        }
+        (None, None) if config.active_season == 0 => {}
+        _ => return Err(ErrorCode::SeasonStatsMismatch.into()),
+    }
+
+    if !quests.len().is_multiple_of(2) {
+        return Err(ErrorCode::QuestProgressMismatch.into());
+    }
+    for accounts in quests.chunks(2) {
+        let quest = Account::<Quest>::try_from(&accounts[0])?;
+        let mut quest_progress = Account::<QuestProgress>::try_from(&accounts[1])?;
+        if quest_progress.quest != quest.key() || quest_progress.trader != trader_stats.trader {
+            return Err(ErrorCode::QuestProgressMismatch.into());
+        }
+        if volume > 0 && quest.is_live(current_time) {
+            quest_progress.advance(&quest, volume, current_time);
+            quest_progress.exit(&crate::ID)?;
+        }
+    }
+
+    if let Some(leaderboard) = leaderboard {
+        update_live_ranking(
+            leaderboard.key(),
+            &mut load_board_mut(leaderboard)?,
+            trader_stats,
+            market_stats.as_deref(),
+            config,
+            current_time,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Re-scores the trader of `trader_stats` after a trade on a live-ranked
+/// board (see `set_live_ranking`) and moves them to their place, or off the
+/// board if banned, suspended or below `config.min_stake_for_ranking`.
+///
+/// Market-scoped boards are scored from `market_stats` and skip trades on
+/// other markets. Wallets linked to another identity are only scored as a
+/// whole by a full update, and a board with an open flag or dispute must
+/// keep its ranks, so both are left unchanged. Other entries keep the score
+/// of their own last trade.
+pub fn update_live_ranking<D: std::ops::DerefMut<Target = [u8]>>(
+    leaderboard_key: Pubkey,
+    leaderboard: &mut Board<D>,
+    trader_stats: &TraderStats,
+    market_stats: Option<&MarketStats>,
+    config: &Config,
+    current_time: i64,
+) -> Result<()> {
+    if leaderboard.live_ranking == 0 {
+        return Err(ErrorCode::LiveRankingDisabled.into());
+    }
+    if trader_stats.identity() != trader_stats.trader
+        || leaderboard.pending_flags > 0
+        || leaderboard.pending_disputes > 0
+    {
+        return Ok(());
+    }
+    let mut input = TraderStatsInput::scored(trader_stats, &config.scoring, current_time);
+    if leaderboard.market != Pubkey::default() {
+        match market_stats {
+            Some(market_stats) if market_stats.market == leaderboard.market => {
+                input.total_volume = market_stats.total_volume;
+                input.average_execution_time = market_stats.average_execution_time;
+                input.pnl = market_stats.pnl;
+                input.last_trade = market_stats.last_trade;
+            }
+            _ => return Ok(()),
+        }
+    }
+
+    if trader_stats.is_excluded(current_time) || input.staked_amount < config.min_stake_for_ranking {
+        match leaderboard.traders().iter().position(|t| *t == input.trader) {
+            Some(index) => leaderboard.remove(index),
+            None => return Ok(()),
+        }
+    } else {
+        leaderboard.upsert(
+            RankedTrader {
+                trader: input.trader,
+                score: input.decayed_score(&config.scoring.weights(), config.score_half_life, current_time),
+                tiebreak: input.tiebreak(),
+            },
+            config.max_leaderboard_size as usize,
+        );
+    }
+    leaderboard.last_update = current_time;
+    emit_leaderboard_updated(leaderboard_key, leaderboard, current_time);
+    Ok(())
+}
+
+/// Aggregates entries of the same identity, scores them with decay as of
+/// `current_time` and sorts them in descending order of score. Equal scores
+/// are ordered by `rank_key`.
+pub fn rank_traders(
+    trader_stats_list: Vec<TraderStatsInput>,
+    weights: &t2e_core::ScoringWeights,
+    half_life: i64,
+    current_time: i64,
+) -> Result<Vec<RankedTrader>> {
+    let identities = merge_identities(trader_stats_list)?;
+    let mut ranked_traders: Vec<RankedTrader> = identities
+        .into_iter()
+        .map(|ts| RankedTrader {
+            trader: ts.trader,
+            score: ts.decayed_score(weights, half_life, current_time),
+            tiebreak: ts.tiebreak(),
+        })
+        .collect();
+    ranked_traders.sort_by_key(|rt| rank_key(&rt.trader, rt.score, &rt.tiebreak));
+    Ok(ranked_traders)
+}
+
+/// Returns true for 3-16 characters of lowercase ASCII letters, digits and `-`.
+pub fn is_valid_referral_code(code: &str) -> bool {
+    (MIN_REFERRAL_CODE_LEN..=MAX_REFERRAL_CODE_LEN).contains(&code.len())
+        && code
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+}
+
+/// Size of the header of an Ed25519 program instruction: signature count and padding.
+const ED25519_HEADER_LEN: usize = 2;
+/// Size of the offsets of one signature in an Ed25519 program instruction.
+const ED25519_OFFSETS_LEN: usize = 14;
+/// Instruction index meaning "this instruction" in Ed25519 signature offsets.
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Checks that `ix` is an Ed25519 program instruction verifying exactly one
+/// signature by `signer` over `message`, with all data in the instruction.
+pub fn verify_ed25519_instruction(
+    ix: &anchor_lang::solana_program::instruction::Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let data = &ix.data;
+    if ix.program_id != anchor_lang::solana_program::ed25519_program::ID
+        || data.len() < ED25519_HEADER_LEN + ED25519_OFFSETS_LEN
+        || data[0] != 1
+    {
+        return Err(ErrorCode::InvalidAttestation.into());
+    }
+    let offset = |i: usize| {
+        let at = ED25519_HEADER_LEN + 2 * i;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+    // signature, signature ix, public key, public key ix, message, message size, message ix
+    let (public_key_offset, message_offset, message_size) =
+        (offset(2) as usize, offset(4) as usize, offset(5) as usize);
+    if [offset(1), offset(3), offset(6)]
+        .iter()
+        .any(|index| *index != ED25519_CURRENT_INSTRUCTION)
+    {
+        return Err(ErrorCode::InvalidAttestation.into());
+    }
+    let public_key = data.get(public_key_offset..public_key_offset + 32);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    if public_key != Some(signer.as_ref()) || signed_message != Some(message) {
+        return Err(ErrorCode::InvalidAttestation.into());
+    }
+    Ok(())
+}
+
+/// Reads the cumulative perp funding of a Drift `User` account, checking that
+/// it is owned by Drift and belongs to `trader`.
+pub fn read_drift_cumulative_funding(venue_user: &AccountInfo, trader: &Pubkey) -> Result<i64> {
+    if venue_user.owner != &drift::ID {
+        return Err(ErrorCode::InvalidVenueAccount.into());
+    }
+    let data = venue_user.try_borrow_data()?;
+    if data.len() < DRIFT_USER_CUMULATIVE_FUNDING_OFFSET + 8 {
+        return Err(ErrorCode::InvalidVenueAccount.into());
+    }
+    let authority = &data[DRIFT_USER_AUTHORITY_OFFSET..DRIFT_USER_AUTHORITY_OFFSET + 32];
+    if authority != trader.as_ref() {
+        return Err(ErrorCode::InvalidVenueAccount.into());
+    }
+    let mut funding = [0u8; 8];
+    funding.copy_from_slice(
+        &data[DRIFT_USER_CUMULATIVE_FUNDING_OFFSET..DRIFT_USER_CUMULATIVE_FUNDING_OFFSET + 8],
+    );
+    Ok(i64::from_le_bytes(funding))
+}
+
+/// A Pyth price: `price * 10^exponent` USD per whole token, +/- `conf`.
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    /// Rejects prices older than `config.max_price_age` or with a confidence
+    /// interval wider than `config.max_price_confidence_bps` of the price.
+    pub fn validate(&self, config: &Config, current_time: i64) -> Result<()> {
+        if self.price <= 0 {
+            return Err(ErrorCode::InvalidOracleAccount.into());
+        }
+        if current_time.saturating_sub(self.publish_time) > config.max_price_age {
+            return Err(ErrorCode::OraclePriceStale.into());
+        }
+        let max_conf = (self.price as u128) * (config.max_price_confidence_bps as u128) / 10_000;
+        if (self.conf as u128) > max_conf {
+            return Err(ErrorCode::OracleConfidenceTooWide.into());
+        }
+        Ok(())
+    }
+
+    /// Converts `amount` base units of a token with `decimals` decimals into
+    /// USD with `NORMALIZED_VOLUME_DECIMALS` decimals.
+    pub fn normalize(&self, amount: u64, decimals: u8) -> Result<u64> {
+        let value = (amount as u128)
+            .checked_mul(self.price as u128)
+            .ok_or(ErrorCode::Overflow)?;
+        let scale = self.exponent + NORMALIZED_VOLUME_DECIMALS - decimals as i32;
+        let factor = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(ErrorCode::Overflow)?;
+        let value = if scale >= 0 {
+            value.checked_mul(factor).ok_or(ErrorCode::Overflow)?
+        } else {
+            value / factor
+        };
+        u64::try_from(value).map_err(|_| ErrorCode::Overflow.into())
+    }
+}
+
+/// Rescales `amount` base units of a token with `decimals` decimals to
+/// `NORMALIZED_VOLUME_DECIMALS` decimals, so volume in tokens of different
+/// precision is comparable without an oracle.
+pub fn normalize_decimals(amount: u64, decimals: u8) -> Result<u64> {
+    let scale = NORMALIZED_VOLUME_DECIMALS - decimals as i32;
+    let factor = 10u128
+        .checked_pow(scale.unsigned_abs())
+        .ok_or(ErrorCode::Overflow)?;
+    let value = if scale >= 0 {
+        (amount as u128).checked_mul(factor).ok_or(ErrorCode::Overflow)?
+    } else {
+        amount as u128 / factor
+    };
+    u64::try_from(value).map_err(|_| ErrorCode::Overflow.into())
+}
+
+/// Reads the price for `feed_id` from a fully verified Pyth `PriceUpdateV2`
+/// account.
+pub fn read_pyth_price(price_update: &AccountInfo, feed_id: &[u8; 32]) -> Result<OraclePrice> {
+    if price_update.owner != &pyth_receiver::ID {
+        return Err(ErrorCode::InvalidOracleAccount.into());
+    }
+    let data = price_update.try_borrow_data()?;
+    let discriminator =
+        anchor_lang::solana_program::hash::hash(b"account:PriceUpdateV2").to_bytes();
+    if data.len() < PYTH_PRICE_MESSAGE_OFFSET + 60
+        || data[..8] != discriminator[..8]
+        || data[PYTH_VERIFICATION_LEVEL_OFFSET] != 1
+    {
+        return Err(ErrorCode::InvalidOracleAccount.into());
+    }
+    let message = &data[PYTH_PRICE_MESSAGE_OFFSET..];
+    if &message[..32] != feed_id {
+        return Err(ErrorCode::InvalidOracleAccount.into());
+    }
+    let field = |offset: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&message[offset..offset + 8]);
+        bytes
+    };
+    let mut exponent = [0u8; 4];
+    exponent.copy_from_slice(&message[48..52]);
+    Ok(OraclePrice {
+        price: i64::from_le_bytes(field(32)),
+        conf: u64::from_le_bytes(field(40)),
+        exponent: i32::from_le_bytes(exponent),
+        publish_time: i64::from_le_bytes(field(52)),
+    })
+}
+
+/// Performs a `transfer_checked` through whichever token program owns the mint,
+/// appending the extra accounts a Token-2022 transfer hook needs.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked_with_hooks<'info>(
+    token_program: &AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    extra_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint,
+        to,
+        authority,
+        extra_accounts,
+        amount,
+        decimals,
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Transfer fee config of a Token-2022 mint, if it has one.
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(state.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+/// Fails if `mint` is a Token-2022 mint with a transfer hook program set.
+/// For transfers that cannot pass the hook's extra accounts.
+pub fn reject_transfer_hook(mint: &AccountInfo) -> Result<()> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let hooked = state
+        .get_extension::<TransferHook>()
+        .map(|hook| Option::<Pubkey>::from(hook.program_id).is_some())
+        .unwrap_or(false);
+    if hooked {
+        return Err(ErrorCode::TransferHookUnsupported.into());
+    }
+    Ok(())
+}
+
+/// Amount arriving from a transfer of `amount` of `mint` in the current
+/// epoch, net of any Token-2022 transfer fee.
+pub fn net_of_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let Some(fee_config) = transfer_fee_config(mint)? else {
+        return Ok(amount);
+    };
+    fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .and_then(|fee| amount.checked_sub(fee))
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Amount of `mint` to transfer in the current epoch so that `amount` arrives
+/// after any Token-2022 transfer fee.
+pub fn gross_of_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let Some(fee_config) = transfer_fee_config(mint)? else {
+        return Ok(amount);
+    };
+    fee_config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+        .and_then(|fee| amount.checked_add(fee))
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Checks that a multisig-gated instruction may run. Without an admin set the
+/// instruction's admin signer suffices; with one, `admin_action` must carry
+/// `action` with at least `threshold` approvals, and is marked executed.
+pub fn authorize_admin_action(
+    config: &Config,
+    admin_set: Option<&AdminSet>,
+    admin_action: Option<&mut PendingAdminAction>,
+    action: AdminAction,
+) -> Result<()> {
+    if config.admin_set == Pubkey::default() {
+        return Ok(());
+    }
+    let admin_set = admin_set.ok_or(ErrorCode::AdminSetNotProvided)?;
+    let pending = admin_action.ok_or(ErrorCode::AdminActionNotApproved)?;
+    if pending.executed {
+        return Err(ErrorCode::AdminActionExecuted.into());
+    }
+    if pending.action != action || pending.approvals.count_ones() < admin_set.threshold as u32 {
+        return Err(ErrorCode::AdminActionNotApproved.into());
+    }
+    pending.executed = true;
+    Ok(())
+}
+
+/// Input structure for trader stats used during leaderboard updates.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TraderStatsInput {
+    pub trader: Pubkey,
+    pub total_volume: u64,
+    pub average_execution_time: u64, // Per `ScoringConfig::execution_time_metric`.
+    pub pnl: i64,
+    pub staked_amount: u64, // Added for leaderboard boost
+    pub reputation: u64, // Endorsement reputation, used as a tiebreaker
+    pub first_trade: i64, // Timestamp of the first recorded trade; 0 = none.
+    pub last_trade: i64, // Scores decay with the time since the last trade.
+    pub market_maker: bool, // Approved market makers are scored volume-heavy, PnL-light.
+    pub trade_count: u64,
+    pub trade_pnl_sum: i64, // Per-trade P&L moments for risk-adjusted scoring.
+    pub trade_pnl_sum_squares: u128,
+    pub winning_trades: u64,
+    pub losing_trades: u64,
+    pub streak_days: u32, // Current day streak under `ScoringConfig::streak_mode`.
+}
+
+impl TraderStatsInput {
+    /// Folds the stats of another wallet of the same identity into this entry.
+    pub fn merge(&mut self, other: &TraderStatsInput) -> Result<()> {
+        let weight_self = self.total_volume.max(1);
+        let weight_other = other.total_volume.max(1);
+        // Volume-weighted average execution time.
+        self.average_execution_time = ((self.average_execution_time as u128
+            * weight_self as u128
+            + other.average_execution_time as u128 * weight_other as u128)
+            / (weight_self as u128 + weight_other as u128)) as u64;
+        self.total_volume = self
+            .total_volume
+            .checked_add(other.total_volume)
+            .ok_or(ErrorCode::Overflow)?;
+        self.pnl = self.pnl.checked_add(other.pnl).ok_or(ErrorCode::Overflow)?;
+        self.staked_amount = self
+            .staked_amount
+            .checked_add(other.staked_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.reputation = self
+            .reputation
+            .checked_add(other.reputation)
+            .ok_or(ErrorCode::Overflow)?;
+        self.first_trade = match (self.first_trade, other.first_trade) {
+            (0, t) | (t, 0) => t,
+            (a, b) => a.min(b),
+        };
+        self.last_trade = self.last_trade.max(other.last_trade);
+        self.market_maker = self.market_maker && other.market_maker;
+        self.trade_count = self
+            .trade_count
+            .checked_add(other.trade_count)
+            .ok_or(ErrorCode::Overflow)?;
+        self.trade_pnl_sum = self
+            .trade_pnl_sum
+            .checked_add(other.trade_pnl_sum)
+            .ok_or(ErrorCode::Overflow)?;
+        self.trade_pnl_sum_squares = self
+            .trade_pnl_sum_squares
+            .saturating_add(other.trade_pnl_sum_squares);
+        self.winning_trades = self
+            .winning_trades
+            .checked_add(other.winning_trades)
+            .ok_or(ErrorCode::Overflow)?;
+        self.losing_trades = self
+            .losing_trades
+            .checked_add(other.losing_trades)
+            .ok_or(ErrorCode::Overflow)?;
+        self.streak_days = self.streak_days.max(other.streak_days);
+        Ok(())
+    }
+
+    /// Tie-break criteria of this entry, in ranking order after the score.
+    pub fn tiebreak(&self) -> TieBreak {
+        TieBreak {
+            reputation: self.reputation,
+            volume: self.total_volume,
+            first_trade: self.first_trade,
+        }
+    }
+
+    /// Composite ranking score under `weights`; see `t2e_core::score_with_weights`.
+    pub fn score(&self, weights: &t2e_core::ScoringWeights) -> u64 {
+        self.score_breakdown(weights).total()
+    }
+
+    /// `score` decayed by the time between the last trade and `current_time`;
+    /// see `t2e_core::decayed_score`. A zero `half_life` disables decay.
+    pub fn decayed_score(
+        &self,
+        weights: &t2e_core::ScoringWeights,
+        half_life: i64,
+        current_time: i64,
+    ) -> u64 {
+        t2e_core::decayed_score(self.score(weights), current_time - self.last_trade, half_life)
+    }
+
+    /// Per-component breakdown of `score`, including the day-streak bonus.
+    pub fn score_breakdown(&self, weights: &t2e_core::ScoringWeights) -> t2e_core::ScoreBreakdown {
+        let breakdown = t2e_core::score_breakdown_with_moments(
+            self.total_volume,
+            self.average_execution_time,
+            self.pnl,
+            &t2e_core::PnlMoments {
+                trade_count: self.trade_count,
+                sum: self.trade_pnl_sum,
+                sum_squares: self.trade_pnl_sum_squares,
+                wins: self.winning_trades,
+                losses: self.losing_trades,
+            },
+            self.staked_amount,
+            self.market_maker,
+            weights,
+        );
+        t2e_core::with_streak_bonus(breakdown, self.streak_days as u64, weights)
+    }
+}
+
+impl TraderStatsInput {
+    /// Ranking input of a wallet as scored on-chain under `scoring` at
+    /// `as_of`: the configured execution-time statistic and day streak, and
+    /// the effective stake.
+    pub fn scored(stats: &TraderStats, scoring: &ScoringConfig, as_of: i64) -> Self {
+        TraderStatsInput {
+            average_execution_time: stats.execution_time(scoring.execution_time_metric),
+            staked_amount: stats.effective_stake(as_of),
+            streak_days: stats.streak(scoring.streak_mode, as_of),
+            ..TraderStatsInput::from(stats)
+        }
+    }
+
+    /// Builds the ranking input for a venue-specific leaderboard, using only
+    /// the trader's volume and P&L on that venue type.
+    pub fn for_venue(stats: &TraderStats, venue: VenueType) -> Self {
+        let (total_volume, pnl) = match venue {
+            VenueType::Spot => (stats.spot_volume, stats.spot_pnl),
+            VenueType::Perp => (stats.perp_volume, stats.perp_pnl),
+        };
+        TraderStatsInput {
+            total_volume,
+            pnl,
+            ..TraderStatsInput::from(stats)
+        }
+    }
+}
+
+impl From<&TraderStats> for TraderStatsInput {
+    fn from(stats: &TraderStats) -> Self {
+        TraderStatsInput {
+            trader: stats.trader,
+            total_volume: stats.total_volume,
+            average_execution_time: stats.average_execution_time,
+            pnl: stats.pnl,
+            staked_amount: stats.staked_amount,
+            reputation: stats.reputation,
+            first_trade: stats.first_trade,
+            last_trade: stats.last_trade,
+            market_maker: stats.market_maker == MarketMakerStatus::Approved,
+            trade_count: stats.trade_count,
+            trade_pnl_sum: stats.trade_pnl_sum,
+            trade_pnl_sum_squares: stats.trade_pnl_sum_squares,
+            winning_trades: stats.winning_trades,
+            losing_trades: stats.losing_trades,
+            streak_days: 0, // Depends on the scoring config; see `scored`.
+        }
+    }
+}
+
+/// Weights and divisors of the composite ranking score, stored in the config.
+/// Weights are in basis points; 10_000 leaves a component unchanged.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct ScoringConfig {
+    pub volume_weight_bps: u16,
+    pub execution_time_weight_bps: u16, // Scales the execution-time penalty.
+    pub pnl_weight_bps: u16,
+    pub staking_weight_bps: u16,
+    pub volume_divisor: u64,
+    pub staking_divisor: u64, // Staked $T2E per point of staking bonus.
+    pub execution_time_metric: ExecutionTimeMetric, // Execution time the penalty is based on.
+    pub pnl_mode: PnlMode,
+    pub win_rate_weight_bps: u16, // Score points per bps of win rate; 0 = not scored.
+    pub streak_mode: StreakMode,
+    pub streak_bonus_bps: u16, // Score multiplier added per streak day; 0 = no streak bonus.
+    pub max_streak_days: u16, // Streak days rewarded at most.
+}
+
+/// Which consecutive days count towards a trader's streak.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace)]
+pub enum StreakMode {
+    /// Days with at least one trade; broken by a day without trading.
+    #[default]
+    TradingDays,
+    /// Days with positive P&L; broken by a losing or flat day or a gap.
+    ProfitableDays,
+}
+
+/// How the P&L component is scored.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace)]
+pub enum PnlMode {
+    /// Positive P&L as is, which favors large traders.
+    #[default]
+    Absolute,
+    /// Positive P&L in basis points of total volume, so smaller but more
+    /// profitable traders can rank competitively.
+    ReturnOnVolume,
+    /// Mean per-trade P&L over its standard deviation, in basis points, so
+    /// consistent traders outrank lucky streaks.
+    RiskAdjusted,
+}
+
+/// Statistic of a trader's execution times used for scoring. Quantiles are
+/// robust to a few outlier trades skewing the average.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace)]
+pub enum ExecutionTimeMetric {
+    #[default]
+    Average,
+    Median,
+    P95,
+}
+
+impl ScoringConfig {
+    /// The original fixed formula: volume / (exec_time + 1) + pnl + stake / 1000.
+    pub const DEFAULT: ScoringConfig = ScoringConfig {
+        volume_weight_bps: 10_000,
+        execution_time_weight_bps: 10_000,
+        pnl_weight_bps: 10_000,
+        staking_weight_bps: 10_000,
+        volume_divisor: 1,
+        staking_divisor: t2e_core::STAKING_BONUS_UNIT,
+        execution_time_metric: ExecutionTimeMetric::Average,
+        pnl_mode: PnlMode::Absolute,
+        win_rate_weight_bps: 0,
+        streak_mode: StreakMode::TradingDays,
+        streak_bonus_bps: 0,
+        max_streak_days: 0,
+    };
+
+    /// The same weights in the form used by `t2e_core`.
+    pub fn weights(&self) -> t2e_core::ScoringWeights {
+        t2e_core::ScoringWeights {
+            volume_weight_bps: self.volume_weight_bps as u64,
+            execution_time_weight_bps: self.execution_time_weight_bps as u64,
+            pnl_weight_bps: self.pnl_weight_bps as u64,
+            staking_weight_bps: self.staking_weight_bps as u64,
+            volume_divisor: self.volume_divisor,
+            staking_divisor: self.staking_divisor,
+            pnl_scoring: match self.pnl_mode {
+                PnlMode::Absolute => t2e_core::PnlScoring::Absolute,
+                PnlMode::ReturnOnVolume => t2e_core::PnlScoring::ReturnOnVolume,
+                PnlMode::RiskAdjusted => t2e_core::PnlScoring::RiskAdjusted,
+            },
+            win_rate_weight_bps: self.win_rate_weight_bps as u64,
+            streak_bonus_bps: self.streak_bonus_bps as u64,
+            max_streak_days: self.max_streak_days as u64,
+        }
+    }
+}
+
+/// A single ranked entry returned by view instructions.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeaderboardEntry {
+    pub rank: u32, // Zero-based position on the board.
+    pub trader: Pubkey,
+    pub score: u64,
+}
+
+/// A page of leaderboard entries returned by `get_leaderboard_page`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeaderboardPage {
+    pub total: u32,
+    pub offset: u32,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// A trader's position on a leaderboard, returned by `get_trader_rank`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TraderRank {
+    pub trader: Pubkey,
+    pub ranked: bool, // False if the trader is not on the board.
+    pub rank: u32, // Zero-based position on the board.
+    pub score: u64,
+    pub total: u32, // Entries on the board.
+}
+
+/// Projected payout of a trader, returned by `preview_payout`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PayoutPreview {
+    pub trader: Pubkey,
+    pub ranked: bool, // False if the trader is not on the board.
+    pub rank: u32, // Zero-based position on the board.
+    pub share_bps: u16, // Share of the (halved) reward pool.
+    pub estimated_amount: u64,
+}
+
+/// Trade reported by the trade oracle; signed in its Borsh serialization.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TradeAttestation {
+    pub trader: Pubkey,
+    pub volume: u64,
+    pub execution_time: u64,
+    pub pnl: i64,
+    pub venue: VenueType,
+    pub attested_at: i64, // Must exceed the trader's previous attestation.
+}
+
+/// Helper struct for ranking a trader.
+pub struct RankedTrader {
+    pub trader: Pubkey,
+    pub score: u64,
+    pub tiebreak: TieBreak,
+}
+
+/// Criteria ordering traders with equal scores, stored next to each board
+/// entry so that the ranking can be re-sorted reproducibly.
+#[zero_copy]
+pub struct TieBreak {
+    pub reputation: u64, // Higher first.
+    pub volume: u64, // Higher first.
+    pub first_trade: i64, // Earlier first; 0 = unknown, ranked last.
+}
+
+/// Sort key of a board entry: score, reputation and volume descending, then
+/// earliest first trade, then trader pubkey. Distinct traders never compare
+/// equal, so any sort yields the same ranking.
+pub fn rank_key(
+    trader: &Pubkey,
+    score: u64,
+    tiebreak: &TieBreak,
+) -> (std::cmp::Reverse<(u64, u64, u64)>, i64, Pubkey) {
+    let first_trade = if tiebreak.first_trade == 0 {
+        i64::MAX
+    } else {
+        tiebreak.first_trade
+    };
+    (
+        std::cmp::Reverse((score, tiebreak.reputation, tiebreak.volume)),
+        first_trade,
+        *trader,
+    )
+}
+
+/// Market type a trade was executed on; spot and perp stats are tracked separately.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VenueType {
+    Spot,
+    Perp,
+}
+
+/// Fee discount granted from a staked amount on.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FeeDiscountTier {
+    pub min_stake: u64, // Effective stake, in $T2E.
+    pub discount: u8, // Percent.
+}
+
+/// Trading milestone for which a badge can be minted.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    FirstMillionVolume,
+    HundredTrades,
+    TopTenFinish,
+}
+
+impl Achievement {
+    /// Bit of the achievement in `TraderStats::achievements`.
+    pub fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+/// Lock duration of staked tokens; longer locks earn a larger staking bonus.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace)]
+pub enum LockTier {
+    #[default]
+    None,
+    Days30,
+    Days90,
+    Days180,
+}
+
+impl LockTier {
+    /// Lock duration in seconds.
+    pub fn duration(&self) -> i64 {
+        const DAY: i64 = 24 * 60 * 60;
+        match self {
+            LockTier::None => 0,
+            LockTier::Days30 => 30 * DAY,
+            LockTier::Days90 => 90 * DAY,
+            LockTier::Days180 => 180 * DAY,
+        }
+    }
+
+    /// Weight of locked tokens in the staked amount, in basis points.
+    pub fn multiplier_bps(&self) -> u64 {
+        match self {
+            LockTier::None => 10_000,
+            LockTier::Days30 => 12_500,
+            LockTier::Days90 => 15_000,
+            LockTier::Days180 => 20_000,
+        }
+    }
+}
+
+/// Registered market-maker status of a trader.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace)]
+pub enum MarketMakerStatus {
+    #[default]
+    None,
+    /// Applied; awaiting admin review.
+    Pending,
+    /// Relaxed spam interval and volume-heavy scoring.
+    Approved,
+}
+
+/// Affiliate tier of a referrer, determining their revenue share.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum AffiliateTier {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl AffiliateTier {
+    /// Highest tier whose active-referee and volume thresholds are both met.
+    pub fn for_activity(active_referees: u32, referee_volume: u64) -> Self {
+        if active_referees >= GOLD_MIN_REFEREES && referee_volume >= GOLD_MIN_VOLUME {
+            AffiliateTier::Gold
+        } else if active_referees >= SILVER_MIN_REFEREES && referee_volume >= SILVER_MIN_VOLUME {
+            AffiliateTier::Silver
+        } else {
+            AffiliateTier::Bronze
+        }
+    }
+
+    /// Share of referees' protocol fees paid to the referrer.
+    pub fn revenue_share_bps(&self) -> u16 {
+        match self {
+            AffiliateTier::Bronze => 500,
+            AffiliateTier::Silver => 1_000,
+            AffiliateTier::Gold => 1_500,
+        }
+    }
+}
+
+/// A leaderboard listed in the global registry.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RegistryEntry {
+    pub leaderboard: Pubkey,
+    pub name: [u8; 32], // Zero-padded UTF-8; unique across the registry.
+    pub market: Pubkey, // Market or pool the board ranks; default = all markets.
+    pub authority: Pubkey, // Admin that registered the board.
+    pub period: i64, // Ranking period in seconds; 0 = all-time.
+}
+
+/// Lifecycle of a sponsored competition.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CompetitionStatus {
+    Open,
+    Finalized,
+    Cancelled,
+}
+
+/// What a quest counts towards its target.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum QuestKind {
+    Volume, // Credited trade volume.
+    TradeCount,
+}
+
+/// How a trader receives leaderboard rewards.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace)]
+pub enum PayoutPreference {
+    /// Paid in $T2E to the trader's token account.
+    #[default]
+    T2e,
+    /// Escrowed in $T2E and settled in USDC by a keeper after a swap.
+    UsdcViaSwap,
+    /// Moved into the staking vault and credited to `staked_amount`.
+    AutoStake,
+}
+
+impl Season {
+    /// Returns true while trades count towards the season.
+    pub fn is_live(&self, current_time: i64) -> bool {
+        !self.ended && current_time >= self.start_ts && current_time < self.end_ts
+    }
+}
+
+impl TraderSeasonStats {
+    /// Adds one (already weighted) trade to the season totals. Zero-weight
+    /// trades pass no `execution_time` and only add their losses.
+    pub fn accumulate(&mut self, volume: u64, execution_time: Option<u64>, pnl: i64) -> Result<()> {
+        self.total_volume = self.total_volume.checked_add(volume).ok_or(ErrorCode::Overflow)?;
+        if let Some(execution_time) = execution_time {
+            self.average_execution_time =
+                t2e_core::average_execution_time(self.average_execution_time, self.trade_count, execution_time)
+                    .ok_or(ErrorCode::Overflow)?;
+            self.trade_count = self.trade_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        }
+        self.pnl = self.pnl.checked_add(pnl).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
+
+impl MarketStats {
+    /// Adds one (already weighted) trade to the market totals. Zero-weight
+    /// trades pass no `execution_time` and only add their losses.
+    pub fn accumulate(&mut self, volume: u64, execution_time: Option<u64>, pnl: i64) -> Result<()> {
+        self.total_volume = self.total_volume.checked_add(volume).ok_or(ErrorCode::Overflow)?;
+        if let Some(execution_time) = execution_time {
+            self.average_execution_time =
+                t2e_core::average_execution_time(self.average_execution_time, self.trade_count, execution_time)
+                    .ok_or(ErrorCode::Overflow)?;
+            self.trade_count = self.trade_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        }
+        self.pnl = self.pnl.checked_add(pnl).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
+
+impl TraderStats {
+    /// Returns true while a moderator suspension is in effect.
+    pub fn is_suspended(&self, current_time: i64) -> bool {
+        current_time < self.suspended_until
+    }
+
+    /// Returns true while the trader is banned or suspended, and so left out
+    /// of rankings and rewards.
+    pub fn is_excluded(&self, current_time: i64) -> bool {
+        self.banned || self.is_suspended(current_time)
+    }
+
+    /// Execution time scored under `metric`; quantiles are estimated from
+    /// the histogram of all recorded trades.
+    pub fn execution_time(&self, metric: ExecutionTimeMetric) -> u64 {
+        match metric {
+            ExecutionTimeMetric::Average => self.average_execution_time,
+            ExecutionTimeMetric::Median => {
+                t2e_core::execution_time_quantile(&self.execution_time_histogram, 5_000)
+            }
+            ExecutionTimeMetric::P95 => {
+                t2e_core::execution_time_quantile(&self.execution_time_histogram, 9_500)
+            }
+        }
+    }
+
+    /// Per-trade P&L statistics of `trade_count` trades.
+    pub fn pnl_moments(&self) -> t2e_core::PnlMoments {
+        t2e_core::PnlMoments {
+            trade_count: self.trade_count,
+            sum: self.trade_pnl_sum,
+            sum_squares: self.trade_pnl_sum_squares,
+            wins: self.winning_trades,
+            losses: self.losing_trades,
+        }
+    }
+
+    /// Current day streak under `mode` at `current_time`. The day of the
+    /// latest trade counts once it is profitable (or traded, for trading
+    /// days); a streak whose last day passed without continuation is over.
+    pub fn streak(&self, mode: StreakMode, current_time: i64) -> u32 {
+        let today = current_time.div_euclid(STREAK_DAY);
+        if self.trade_count == 0 || today > self.streak_day + 1 {
+            return 0;
+        }
+        match mode {
+            StreakMode::TradingDays => self.trading_streak.saturating_add(1),
+            StreakMode::ProfitableDays if self.streak_day_pnl > 0 => self.profitable_streak.saturating_add(1),
+            // A losing or flat day breaks the streak once it is over.
+            StreakMode::ProfitableDays if today > self.streak_day => 0,
+            StreakMode::ProfitableDays => self.profitable_streak,
+        }
+    }
+
+    /// Rolls the day streaks forward to the day of a trade at `current_time`
+    /// and adds the trade's `pnl` to that day.
+    fn record_streak_day(&mut self, pnl: i64, current_time: i64) {
+        let day = current_time.div_euclid(STREAK_DAY);
+        if day != self.streak_day {
+            let consecutive = day == self.streak_day + 1;
+            self.trading_streak = if consecutive {
+                self.trading_streak.saturating_add(1)
+            } else {
+                0
+            };
+            self.profitable_streak = if consecutive && self.streak_day_pnl > 0 {
+                self.profitable_streak.saturating_add(1)
+            } else {
+                0
+            };
+            self.streak_day = day;
+            self.streak_day_pnl = 0;
+        }
+        self.streak_day_pnl = self.streak_day_pnl.saturating_add(pnl);
+    }
+
+    /// Staked amount counted for scoring and fee discounts: the lock bonus
+    /// is added while the stake position is locked.
+    pub fn effective_stake(&self, current_time: i64) -> u64 {
+        if current_time < self.locked_until {
+            self.staked_amount.saturating_add(self.lock_bonus)
+        } else {
+            self.staked_amount
+        }
+    }
+
+    /// Number of the trader's latest `MAX_RATE_WINDOW_TRADES` trades recorded
+    /// within the last `window` seconds.
+    pub fn trades_in_window(&self, window: i64, current_time: i64) -> usize {
+        self.recent_trades
+            .iter()
+            .filter(|&&t| t != 0 && current_time - t < window)
+            .count()
+    }
+
+    /// Settles the staking yield accrued since the last settlement into
+    /// `staking_rewards`. Must run before every change of `staked_amount`.
+    pub fn settle_staking_rewards(&mut self, config: &Config, current_time: i64) -> Result<()> {
+        let index = config.staking_index(current_time)?;
+        let accrued = index
+            .saturating_sub(self.staking_index_paid)
+            .checked_mul(self.staked_amount as u128)
+            .ok_or(ErrorCode::Overflow)?
+            / STAKING_INDEX_SCALE;
+        self.staking_rewards = u64::try_from(accrued)
+            .ok()
+            .and_then(|accrued| self.staking_rewards.checked_add(accrued))
+            .ok_or(ErrorCode::Overflow)?;
+        self.staking_index_paid = index;
+        Ok(())
+    }
+
+    /// Adds `amount` staked under `lock` by `funder`, settling the staking
+    /// yield earned on the previous stake first, and returns the new stake
+    /// position. Once every earlier lock has run out, `lock_bonus` restarts
+    /// from this position.
+    pub fn open_stake_position(
+        &mut self,
+        amount: u64,
+        lock: LockTier,
+        funder: Pubkey,
+        config: &Config,
+        current_time: i64,
+        bump: u8,
+    ) -> Result<StakePosition> {
+        self.settle_staking_rewards(config, current_time)?;
+        self.staked_amount = self
+            .staked_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        let position = StakePosition {
+            trader: self.trader,
+            funder,
+            position_id: self.stake_positions,
+            amount,
+            lock,
+            multiplier_bps: lock.multiplier_bps() as u16,
+            staked_at: current_time,
+            locked_until: current_time
+                .checked_add(lock.duration())
+                .ok_or(ErrorCode::Overflow)?,
+            unstake_pending: 0,
+            unstake_available_at: 0,
+            bump,
+        };
+        if position.staked_at >= self.locked_until {
+            self.lock_bonus = 0;
+            self.lock_bonus_since = position.staked_at;
+        }
+        self.lock_bonus = self
+            .lock_bonus
+            .checked_add(position.bonus_stake()?)
+            .ok_or(ErrorCode::Overflow)?;
+        self.locked_until = self.locked_until.max(position.locked_until);
+        self.position_stake = self
+            .position_stake
+            .checked_add(position.amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.stake_positions = self
+            .stake_positions
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(position)
+    }
+
+    /// Takes `amount` out of a stake position, along with its share of
+    /// `lock_bonus` unless that has since restarted. `staked_amount` is left
+    /// to the caller.
+    pub fn release_stake_position(&mut self, position: &mut StakePosition, amount: u64) -> Result<()> {
+        let bonus = position.bonus_stake()?;
+        position.amount = position
+            .amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::InvalidUnstakeAmount)?;
+        if position.staked_at >= self.lock_bonus_since {
+            self.lock_bonus = self
+                .lock_bonus
+                .saturating_sub(bonus - position.bonus_stake()?);
+        }
+        self.position_stake = self.position_stake.saturating_sub(amount);
+        Ok(())
+    }
+
+    /// Takes a slashed `amount` out of the stake: from stake held outside
+    /// positions first, then from `positions` pro rata to their amounts. If
+    /// positions are cut, `positions` must hold every open position.
+    pub fn slash_stake(&mut self, amount: u64, positions: &mut [Account<StakePosition>]) -> Result<()> {
+        let loose = self.staked_amount.saturating_sub(self.position_stake);
+        let from_positions = amount.saturating_sub(loose);
+        if from_positions > 0 {
+            let total = positions
+                .iter()
+                .try_fold(0u64, |total, position| total.checked_add(position.amount))
+                .ok_or(ErrorCode::Overflow)?;
+            if total != self.position_stake {
+                return Err(ErrorCode::StakePositionsIncomplete.into());
+            }
+            let mut cuts: Vec<u64> = positions
+                .iter()
+                .map(|position| (position.amount as u128 * from_positions as u128 / total as u128) as u64)
+                .collect();
+            // Hand the rounding remainder to the first positions with room left.
+            let mut remainder = from_positions - cuts.iter().sum::<u64>();
+            for (cut, position) in cuts.iter_mut().zip(positions.iter()) {
+                let extra = remainder.min(position.amount - *cut);
+                *cut += extra;
+                remainder -= extra;
+            }
+            for (position, cut) in positions.iter_mut().zip(cuts) {
+                self.release_stake_position(position, cut)?;
+            }
+        }
+        self.staked_amount = self
+            .staked_amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Adds a payout moved into the staking vault to `staked_amount`,
+    /// settling the staking yield earned on the previous stake first.
+    pub fn compound(&mut self, amount: u64, config: &Config, current_time: i64) -> Result<()> {
+        self.settle_staking_rewards(config, current_time)?;
+        self.staked_amount = self
+            .staked_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Credits a payout according to the payout preference: auto-staked
+    /// rewards count as staked and USDC rewards as awaiting a swap. Returns
+    /// the preference, which determines the destination of the transfer.
+    pub fn credit_payout(
+        &mut self,
+        amount: u64,
+        config: &Config,
+        current_time: i64,
+    ) -> Result<PayoutPreference> {
+        match self.payout_preference {
+            PayoutPreference::T2e => {}
+            PayoutPreference::AutoStake => self.compound(amount, config, current_time)?,
+            PayoutPreference::UsdcViaSwap => {
+                self.pending_swap_payout = self
+                    .pending_swap_payout
+                    .checked_add(amount)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+        }
+        Ok(self.payout_preference)
+    }
+
+    /// Reward-weight boost for the next payout: the current season pass boost
+    /// plus any redeemed loyalty boost.
+    pub fn reward_boost_bps(&self, config: &Config) -> u16 {
+        let pass_boost_bps = if config.season_pass_price > 0
+            && self.season_pass != 0
+            && self.season_pass == config.current_season
+        {
+            config.season_pass_boost_bps
+        } else {
+            0
+        };
+        pass_boost_bps.saturating_add(self.loyalty_boost_bps)
+    }
+
+    /// The trader identity this wallet ranks as. Rate limits still apply per
+    /// wallet.
+    pub fn identity(&self) -> Pubkey {
+        if self.identity == Pubkey::default() {
+            self.trader
+        } else {
+            self.identity
+        }
+    }
+
+    /// Applies one trade to the stats: spam gate, per-epoch cap, volume,
+    /// weighted average execution time, P&L, loyalty accrual and the volume
+    /// and trade count achievements.
+    ///
+    /// Volume and P&L are added both to the totals and to the `venue`
+    /// breakdown. Returns the `(volume, pnl)` actually credited, which is
+    /// zero-weighted for trades over the per-epoch cap, and whether the trade
+    /// carried weight, and emits the credited trade as a `TradeRecorded`
+    /// event. Zero-weight trades only advance the rate limit and count
+    /// their losses; they leave the volume, execution time, trade count and
+    /// outcome, streak, loyalty and achievement stats untouched.
+    pub fn apply_trade(
+        &mut self,
+        config: &Config,
+        venue: VenueType,
+        volume: u64,
+        execution_time: u64,
+        pnl: i64,
+        current_time: i64,
+    ) -> Result<(u64, i64, bool)> {
+        if self.banned {
+            return Err(ErrorCode::TraderBanned.into());
+        }
+        if volume < config.min_trade_volume {
+            return Err(ErrorCode::TradeBelowMinimumVolume.into());
+        }
+
+        // Prevent trade spam: with a rate window, reject a trade once the
+        // window already holds `rate_window_trades` trades; otherwise reject
+        // trades within the configured cooldown. Approved market makers are
+        // exempt from the window and use a relaxed cooldown.
+        let market_maker = self.market_maker == MarketMakerStatus::Approved;
+        if config.rate_window_trades > 0 {
+            if !market_maker
+                && self.trades_in_window(config.rate_window, current_time)
+                    >= config.rate_window_trades as usize
+            {
+                return Err(ErrorCode::TradeSpamDetected.into());
+            }
+        } else {
+            let min_interval = if market_maker {
+                MARKET_MAKER_TRADE_INTERVAL.min(config.trade_cooldown)
+            } else {
+                config.trade_cooldown
+            };
+            if self.trade_count > 0 && current_time - self.last_trade < min_interval {
+                return Err(ErrorCode::TradeSpamDetected.into());
+            }
+        }
+
+        // Enforce the per-epoch trade cap, if enabled.
+        let (volume, pnl, weighted) = if config.max_trades_per_epoch > 0 {
+            let epoch = current_time / config.trade_epoch_length;
+            if self.trade_epoch != epoch {
+                self.trade_epoch = epoch;
+                self.epoch_trade_count = 0;
+            }
+            let over_cap = self.epoch_trade_count >= config.max_trades_per_epoch;
+            if over_cap && !config.zero_weight_excess_trades {
+                return Err(ErrorCode::TradeLimitExceeded.into());
+            }
+            self.epoch_trade_count = self
+                .epoch_trade_count
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+            if over_cap {
+                (0, pnl.min(0), false)
+            } else {
+                (volume, pnl, true)
+            }
+        } else {
+            (volume, pnl, true)
+        };
+
+        self.last_trade = current_time;
+        self.recent_trades[self.recent_trade_index as usize] = current_time;
+        self.recent_trade_index = ((self.recent_trade_index as usize + 1) % MAX_RATE_WINDOW_TRADES) as u8;
+
+        if weighted {
+            self.apply_weighted_trade(config, venue, volume, execution_time, pnl, current_time)?;
+        } else {
+            // Only losses past the cap still count, against the P&L totals.
+            self.pnl = self.pnl.checked_add(pnl).ok_or(ErrorCode::Overflow)?;
+            let venue_pnl = match venue {
+                VenueType::Spot => &mut self.spot_pnl,
+                VenueType::Perp => &mut self.perp_pnl,
+            };
+            *venue_pnl = venue_pnl.checked_add(pnl).ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(TradeRecorded {
+            trader: self.trader,
+            venue,
+            volume,
+            execution_time,
+            pnl,
+            total_volume: self.total_volume,
+            total_pnl: self.pnl,
+            trade_count: self.trade_count,
+            winning_trades: self.winning_trades,
+            losing_trades: self.losing_trades,
+            win_rate_bps: t2e_core::win_rate_bps(&self.pnl_moments()) as u16,
+            timestamp: current_time,
+        });
+        Ok((volume, pnl, weighted))
+    }
+
+    /// Counts a trade within the per-epoch cap in the volume, execution time,
+    /// P&L, trade count and outcome, streak, loyalty and achievement stats.
+    fn apply_weighted_trade(
+        &mut self,
+        config: &Config,
+        venue: VenueType,
+        volume: u64,
+        execution_time: u64,
+        pnl: i64,
+        current_time: i64,
+    ) -> Result<()> {
+        // Update trading volume.
+        self.total_volume = self
+            .total_volume
+            .checked_add(volume)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // Update average execution time using a simple weighted average.
+        self.average_execution_time = t2e_core::average_execution_time(
+            self.average_execution_time,
+            self.trade_count,
+            execution_time,
+        )
+        .ok_or(ErrorCode::Overflow)?;
+        t2e_core::record_execution_time(&mut self.execution_time_histogram, execution_time);
+
+        // Count the trade along with its P&L moments and outcome.
+        let mut moments = self.pnl_moments();
+        moments.record(pnl).ok_or(ErrorCode::Overflow)?;
+        self.trade_count = moments.trade_count;
+        self.trade_pnl_sum = moments.sum;
+        self.trade_pnl_sum_squares = moments.sum_squares;
+        self.winning_trades = moments.wins;
+        self.losing_trades = moments.losses;
+
+        // Update profit & loss.
+        self.pnl = self
+            .pnl
+            .checked_add(pnl)
+            .ok_or(ErrorCode::Overflow)?;
+        self.record_streak_day(pnl, current_time);
+
+        // Update the per-venue breakdown.
+        let (venue_volume, venue_pnl) = match venue {
+            VenueType::Spot => (&mut self.spot_volume, &mut self.spot_pnl),
+            VenueType::Perp => (&mut self.perp_volume, &mut self.perp_pnl),
+        };
+        *venue_volume = venue_volume.checked_add(volume).ok_or(ErrorCode::Overflow)?;
+        *venue_pnl = venue_pnl.checked_add(pnl).ok_or(ErrorCode::Overflow)?;
+
+        if self.first_trade == 0 {
+            self.first_trade = current_time;
+        }
+
+        // Accrue loyalty points once per epoch of participation.
+        let loyalty_epoch = current_time / config.trade_epoch_length;
+        if self.last_loyalty_epoch != loyalty_epoch {
+            self.last_loyalty_epoch = loyalty_epoch;
+            self.loyalty_points = self
+                .loyalty_points
+                .checked_add(config.loyalty_points_per_epoch)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        if self.total_volume >= ACHIEVEMENT_VOLUME {
+            self.achievements |= Achievement::FirstMillionVolume.bit();
+        }
+        if self.trade_count >= ACHIEVEMENT_TRADE_COUNT {
+            self.achievements |= Achievement::HundredTrades.bit();
+        }
+        Ok(())
+    }
+}
+
+/// What loyalty points are redeemed for.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PointsRedemption {
+    FeeCredit,
+    RewardBoost,
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct TraderStats {
+    pub trader: Pubkey,
+    pub total_volume: u64,
+    pub average_execution_time: u64,
+    pub trade_count: u64,
+    pub pnl: i64,
+    pub staked_amount: u64,
+    pub fee_discount: u8, // Percentage discount (0-50)
+    pub last_trade: i64,
+    pub first_trade: i64, // Ranking tiebreaker: earlier traders rank first.
+    pub payout_preference: PayoutPreference,
+    pub pending_swap_payout: u64, // $T2E escrowed for a USDC swap, not yet settled.
+    pub trade_epoch: i64, // Rate-limit epoch index of `epoch_trade_count`.
+    pub epoch_trade_count: u32,
+    pub season_pass: u32, // Season of the trader's most recent season pass.
+    pub loyalty_points: u64, // Non-transferable; accrued per epoch of participation.
+    pub last_loyalty_epoch: i64,
+    pub fee_credits: u64, // Redeemed from loyalty points.
+    pub loyalty_boost_bps: u16, // Redeemed boost applied to the next payout.
+    pub reputation: u64, // Stake-weighted endorsements received.
+    pub last_endorsement: i64, // Last time this trader endorsed someone.
+    pub spot_volume: u64,
+    pub spot_pnl: i64,
+    pub perp_volume: u64, // Notional; leverage-inflated relative to spot volume.
+    pub perp_pnl: i64, // Includes funding payments.
+    pub identity: Pubkey, // Primary wallet this wallet is linked to; default = unlinked.
+    pub market_maker: MarketMakerStatus,
+    pub suspended_until: i64, // Excluded from ranking and rewards until this time.
+    pub referrer: Pubkey, // Trader who referred this one; default = none.
+    pub unstake_pending: u64, // Requested for withdrawal; no longer counted as staked.
+    pub unstake_available_at: i64, // When `unstake_pending` can be withdrawn.
+    pub lock_bonus: u64, // Extra stake credited for locked stake positions.
+    pub slash_count: u32, // Number of SlashRecords of this trader.
+    pub last_attestation: i64, // `attested_at` of the latest attested trade.
+    pub archived_season: u32, // Last season rolled over into a TraderSeasonArchive.
+    pub locked_until: i64, // Latest lock end of the stake positions; `lock_bonus` counts until then.
+    pub achievements: u8, // Bitmask of reached `Achievement`s.
+    pub badges_minted: u8, // Bitmask of `Achievement`s whose badge was minted.
+    pub fee_tier: u8, // 1-based fee discount tier at the last calculation; 0 = none.
+    pub fees_paid: u64, // Net trading fees collected by `collect_trading_fee`.
+    pub fee_savings: u64, // Fees waived by discounts and fee credits.
+    pub staking_index_paid: u128, // `Config::staking_index` up to which yield was settled.
+    pub staking_rewards: u64, // Settled staking yield not yet claimed.
+    pub recent_trades: [i64; MAX_RATE_WINDOW_TRADES], // Ring of the latest trade timestamps.
+    pub recent_trade_index: u8, // Next slot of `recent_trades` to overwrite.
+    pub banned: bool, // Barred from trading, staking, ranking and rewards until unbanned.
+    pub execution_time_histogram: [u32; t2e_core::EXECUTION_TIME_BUCKETS], // Trades per execution-time bucket.
+    pub trade_pnl_sum: i64, // Sum of per-trade P&L, excluding funding.
+    pub trade_pnl_sum_squares: u128, // Saturating.
+    pub winning_trades: u64, // Trades with positive credited P&L.
+    pub losing_trades: u64, // Trades with negative credited P&L.
+    pub streak_day: i64, // Index of the `STREAK_DAY` of the latest trade.
+    pub streak_day_pnl: i64, // Credited P&L on `streak_day`.
+    pub trading_streak: u32, // Consecutive trading days before `streak_day`.
+    pub profitable_streak: u32, // Consecutive profitable days before `streak_day`.
+    pub stake_positions: u32, // Stake positions opened; the ID of the next one.
+    pub position_stake: u64, // Held in open stake positions; the rest of `staked_amount` is loose.
+    pub lock_bonus_since: i64, // Positions staked before this no longer count in `lock_bonus`.
+    pub unstake_penalty: u64, // Early-unstake penalty withheld, routed by `unstake_tokens`.
+    pub linked_wallets: u16, // Secondary wallets linked to this wallet as their primary.
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AmmSwapReceipt {
+    pub trader: Pubkey,
+    pub source_token_account: Pubkey,
+    pub source_balance_before: u64,
+    pub begin_index: u16, // Index of the `begin_amm_swap` instruction in the transaction.
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct LeaderboardRegistry {
+    #[max_len(MAX_REGISTERED_LEADERBOARDS)]
+    pub entries: Vec<RegistryEntry>,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimWindow {
+    pub epoch: i64, // Emission epoch (30-day periods) whose rewards it governs.
+    pub open_ts: i64,
+    pub close_ts: i64, // Exclusive.
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardClaim {
+    pub leaderboard: Pubkey,
+    pub trader: Pubkey,
+    pub claimable: u64, // Credited by `finalize_epoch`, not yet claimed.
+    pub epoch: i64, // Emission epoch of the latest credit; selects the claim window.
+    pub claimed_total: u64,
+    pub bump: u8,
+}
+
+/// Tokens a trader staked in one `stake_tokens` call, under a lock
+/// (PDA `["stake_position", trader, position_id]`).
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    pub trader: Pubkey,
+    pub funder: Pubkey, // Only they can withdraw the position; the trader unless sponsored.
+    pub position_id: u32,
+    pub amount: u64, // Still staked; also counted in `TraderStats::staked_amount`.
+    pub lock: LockTier,
+    pub multiplier_bps: u16, // Of `lock` when the position was opened.
+    pub staked_at: i64,
+    pub locked_until: i64,
+    pub unstake_pending: u64, // Requested by a sponsor; the trader's own requests go to `TraderStats`.
+    pub unstake_available_at: i64, // When `unstake_pending` can be withdrawn.
+    pub bump: u8,
+}
+
+/// Rewards of a trader on a leaderboard unlocking over time
+/// (PDA `["vesting", leaderboard, trader]`).
+#[account]
+#[derive(InitSpace)]
+pub struct VestingSchedule {
+    pub leaderboard: Pubkey,
+    pub trader: Pubkey,
+    pub total: u64, // Vesting since `start_ts`, including released amounts.
+    pub released: u64,
+    pub start_ts: i64,
+    pub cliff: i64, // Copied from the config whenever the schedule restarts.
+    pub duration: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardRoot {
+    pub leaderboard: Pubkey,
+    pub epoch: u64,
+    pub root: [u8; 32], // Merkle root of (trader, amount) leaves.
+    pub total_amount: u64, // Funded into the claim vault when posted.
+    pub claimed_amount: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RootClaimReceipt {
+    pub reward_root: Pubkey,
+    pub trader: Pubkey,
+    pub amount: u64,
+    pub claimed_at: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub mint: Pubkey,
+    pub feed_id: [u8; 32], // Pyth price feed id quoting the mint in USD.
+    pub bump: u8,
+}
+
+/// A market sanctioned for `record_trade`, with the multiplier applied to
+/// volume traded on it.
+#[account]
+#[derive(InitSpace)]
+pub struct Market {
+    pub market: Pubkey,
+    pub volume_multiplier_bps: u16, // 10_000 = volume counted as traded.
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl Market {
+    /// Volume credited for `volume` traded on this market.
+    pub fn scale_volume(&self, volume: u64) -> u64 {
+        (volume as u128 * self.volume_multiplier_bps as u128 / 10_000).min(u64::MAX as u128) as u64
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Partner {
+    pub program_id: Pubkey, // External trading program.
+    pub signer: Pubkey, // Its `PARTNER_SIGNER_SEED` PDA, which must sign CPIs.
+    pub integration_fee: u64, // $T2E per recorded trade, paid to the treasury.
+    pub active: bool,
+    pub trades_recorded: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Affiliate {
+    pub referrer: Pubkey,
+    pub tier: AffiliateTier,
+    pub revenue_share_bps: u16, // Share of referees' protocol fees at the current tier.
+    pub active_referees: u32,
+    pub referee_volume: u64, // Total volume of active referees at the last recalculation.
+    pub last_recalculated: i64,
+    pub bump: u8,
+}
+
+/// Reward emission schedule anchored at program launch (PDA
+/// `["emission_schedule"]`).
+#[account]
+#[derive(InitSpace)]
+pub struct EmissionSchedule {
+    pub genesis_ts: i64, // Launch; parameters are frozen from then on.
+    pub epoch_length: i64, // Seconds per emission epoch.
+    pub halving_interval: u32, // Emission epochs between halvings.
+    pub base_emission: u64, // Cap on a distribution's pool before halvings; 0 = uncapped.
+    pub bump: u8,
+}
+
+/// Config change put to a stake-weighted vote or queued behind the timelock.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ProposalAction {
+    SetScoringConfig(ScoringConfig),
+    SetHalvingPeriod(i64),
+    SetPauseFlags(u8),
+}
+
+/// Governance proposal (PDA `["proposal", id]`), tallied in staked $T2E.
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub created_at: i64,
+    pub voting_ends_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+/// A trader's vote on a proposal (PDA `["vote", proposal, voter]`).
+#[account]
+#[derive(InitSpace)]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub weight: u64, // Effective stake at the time of the vote.
+    pub support: bool,
+    pub bump: u8,
+}
+
+/// Link from a trader to their referrer (PDA `["referral", trader]`).
+#[account]
+#[derive(InitSpace)]
+pub struct Referral {
+    pub trader: Pubkey,
+    pub referrer: Pubkey,
+    pub registered_at: i64,
+    pub rewards_routed: u64, // Total credited to the referrer so far.
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ReferralCode {
+    pub owner: Pubkey,
+    #[max_len(MAX_REFERRAL_CODE_LEN)]
+    pub code: String,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Appeal {
+    pub trader: Pubkey,
+    pub trader_stats: Pubkey,
+    pub trader_token_account: Pubkey, // Receives the bond back if the penalty is lifted.
+    pub bond_amount: u64,
+    pub suspended_until: i64, // Suspension being appealed.
+    pub reason_hash: [u8; 32],
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+/// Record of a stake slash (PDA `["slash_record", trader, index]`, where
+/// `index` counts the trader's slashes from 0).
+#[account]
+#[derive(InitSpace)]
+pub struct SlashRecord {
+    pub trader: Pubkey,
+    pub admin: Pubkey,
+    pub amount: u64, // Confiscated into the treasury.
+    pub slash_bps: u16,
+    pub season: u32, // Season whose stats were zeroed; 0 = none active.
+    pub reason_hash: [u8; 32],
+    pub slashed_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Competition {
+    pub sponsor: Pubkey,
+    pub competition_id: u64,
+    pub prize_vault: Pubkey, // Token account owned by this PDA.
+    pub prize_amount: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub status: CompetitionStatus,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RankingSnapshot {
+    pub leaderboard: Pubkey,
+    pub sequence: u64,
+    pub ranking_update: i64, // `last_update` of the snapshotted ranking.
+    pub board_hash: [u8; 32], // See `Leaderboard::board_hash`.
+    pub traders_ranked: u32,
+    pub created_at: i64,
+    pub paid_at: i64, // 0 until a distribution is bound to this snapshot.
+    pub amount_paid: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AutomationSchedule {
+    pub leaderboard: Pubkey,
+    pub thread: Pubkey, // Automation signer, e.g. a Clockwork thread PDA.
+    pub update_interval: i64, // Seconds between scheduled updates; 0 = disabled.
+    pub snapshot_interval: i64, // Seconds between scheduled snapshots; 0 = disabled.
+    pub next_update: i64,
+    pub next_snapshot: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct WalletLink {
+    pub primary: Pubkey,
+    pub secondary: Pubkey,
+    pub linked_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Delegate {
+    pub trader: Pubkey,
+    pub signer: Pubkey, // Key allowed to record the trader's trades.
+    pub authorized_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct FundingCursor {
+    pub trader: Pubkey,
+    pub venue_user: Pubkey, // Drift `User` account whose funding is tracked.
+    pub last_cumulative_funding: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Season {
+    pub index: u32, // 1-based, in start order.
+    pub start_ts: i64,
+    pub end_ts: i64, // Exclusive.
+    pub ended: bool,
+    pub bump: u8,
+}
+
+/// A trader's working stats as of a season rollover
+/// (PDA `["trader_season_archive", season, trader]`).
+#[account]
+#[derive(InitSpace)]
+pub struct TraderSeasonArchive {
+    pub trader: Pubkey,
+    pub season: u32,
+    pub total_volume: u64,
+    pub average_execution_time: u64,
+    pub trade_count: u64,
+    pub pnl: i64,
+    pub spot_volume: u64,
+    pub spot_pnl: i64,
+    pub perp_volume: u64,
+    pub perp_pnl: i64,
+    pub archived_at: i64,
+    pub bump: u8,
+}
+
+/// A trader's stats accumulated over one season only.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct TraderSeasonStats {
+    pub trader: Pubkey,
+    pub season: u32,
+    pub total_volume: u64,
+    pub average_execution_time: u64,
+    pub trade_count: u64,
+    pub pnl: i64,
+    pub bump: u8,
+}
+
+/// A trader's stats on one market, ranked by leaderboards scoped to it.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct MarketStats {
+    pub trader: Pubkey,
+    pub market: Pubkey,
+    pub total_volume: u64,
+    pub average_execution_time: u64,
+    pub trade_count: u64,
+    pub pnl: i64,
+    pub last_trade: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PairVolume {
+    pub trader: Pubkey,
+    pub pair: Pubkey, // Market or pool identifying the token pair.
+    pub volume: u64,
+    pub trade_count: u64,
+    pub pnl: i64,
+    pub last_trade: i64,
+    pub bump: u8,
+}
+
+/// Header of a zero-copy ranking of up to `capacity` traders. The entries
+/// follow the header in the account data as parallel trader, score and
+/// tie-break arrays of `capacity` slots each; only the first `len` are
+/// meaningful. Load the whole board with `load_board` or `load_board_mut`.
+#[account(zero_copy)]
+pub struct Leaderboard {
+    pub market: Pubkey, // Ranked market; default = all markets.
+    pub last_update: i64,
+    pub last_paid_update: i64, // `last_update` of the last ranking paid out; each is paid once.
+    pub snapshot_sequence: u64, // Sequence number of the next RankingSnapshot.
+    pub history_count: u64, // Index of the next LeaderboardSnapshot.
+    pub len: u32,
+    pub capacity: u32, // Entry slots allocated; rankings are truncated to it.
+    pub pending_disputes: u16, // Unresolved score disputes against the current ranking.
+    pub pending_flags: u16, // Unresolved flags against the current ranking.
+    pub emergency_pause: u8, // Nonzero while rewards are paused.
+    pub live_ranking: u8, // Nonzero if `record_trade` keeps the ranking up to date.
+    pub reserved: [u8; 2],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey, // Super-authority: manages co-admins and config parameters.
+    #[max_len(MAX_CO_ADMINS)]
+    pub co_admins: Vec<Pubkey>,
+    pub distribution_delay: i64, // Fraud-proof window (seconds) between update and payout.
+    pub swap_escrow: Pubkey, // Collects rewards awaiting a USDC swap; default = disabled.
+    pub treasury: Pubkey, // Receives distribution rounding remainders; default = #1 trader.
+    pub min_dispute_bond: u64,
+    pub dispute_reward: u64, // Paid from the reward vault to successful challengers.
+    pub max_trades_per_epoch: u32, // 0 = no per-epoch cap.
+    pub trade_epoch_length: i64,
+    pub zero_weight_excess_trades: bool,
+    pub pause_flags: u8, // Bitmask of PAUSE_* scopes currently paused.
+    pub current_season: u32,
+    pub season_pass_price: u64, // 0 = season passes disabled.
+    pub season_pass_boost_bps: u16, // Reward-share boost for pass holders.
+    pub season_pass_treasury_bps: u16, // Share of pass proceeds sent to the treasury.
+    pub loyalty_points_per_epoch: u64,
+    pub fee_credit_per_point: u64,
+    pub points_per_boost_bps: u64, // 0 = reward-boost redemption disabled.
+    pub leader_share_bps: u16, // Share of followers' protocol fees routed to their leader.
+    pub max_ranking_age: i64, // Max seconds since the last update for a payout; 0 = no limit.
+    pub arbiter: Pubkey, // Resolves penalty appeals; default = appeals disabled.
+    pub insurance_fund: Pubkey, // Token account receiving bonds of rejected appeals.
+    pub min_appeal_bond: u64,
+    pub inactivity_horizon: i64, // Seconds without trading before pruning; 0 = disabled.
+    pub referral_code_fee: u64, // $T2E paid to the treasury to claim a referral code.
+    pub max_price_age: i64, // Max oracle price age in seconds; 0 = oracle normalization disabled.
+    pub max_price_confidence_bps: u16, // Max oracle confidence interval relative to price.
+    pub emit_score_breakdowns: bool, // Emit per-trader score components on ranking updates.
+    pub unstake_cooldown: i64, // Seconds between an unstake request and the withdrawal.
+    pub trade_cooldown: i64, // Minimum seconds between two trades of a trader.
+    pub update_interval: i64, // Minimum seconds between two leaderboard updates.
+    pub halving_period: i64, // Seconds between reward halvings.
+    pub fee_discount_tiers: [FeeDiscountTier; MAX_FEE_DISCOUNT_TIERS], // Ascending; first `fee_discount_tier_count` used.
+    pub fee_discount_tier_count: u8,
+    pub max_leaderboard_size: u32, // Entries kept per ranking, at most `MAX_LEADERBOARD_ENTRIES`.
+    pub operator: Pubkey, // May post rankings and snapshots besides the admins; default = none.
+    pub season_count: u32, // Index of the most recently started season.
+    pub active_season: u32, // 0 = no season running.
+    pub scoring: ScoringConfig,
+    pub score_half_life: i64, // Seconds for an inactive trader's score to halve; 0 = no decay.
+    pub vesting_cliff: i64, // Seconds after a credit before any of it unlocks.
+    pub vesting_duration: i64, // Seconds until a credit fully unlocks; 0 = no vesting.
+    pub trade_oracle: Pubkey, // Signs attested trades; default = attestations disabled.
+    pub snapshot_retention: u64, // History snapshots kept per leaderboard; 0 = unbounded.
+    pub referral_reward_bps: u16, // Share of referees' epoch rewards routed to referrers.
+    pub badge_mints: [Pubkey; ACHIEVEMENT_COUNT], // Indexed by `Achievement`; default = not set.
+    pub treasury_burn_bps: u16, // Share of the treasury balance burned per `burn_treasury`.
+    pub total_burned: u64,
+    pub emission_schedule: Pubkey, // EmissionSchedule PDA; default = halve per `halving_period`.
+    pub staking_apr_bps: u16, // Yearly staking yield on `staked_amount`.
+    pub staking_reward_index: u128, // Yield per staked token, scaled by `STAKING_INDEX_SCALE`.
+    pub staking_index_updated: i64, // Time `staking_reward_index` was last checkpointed.
+    pub voting_period: i64, // Seconds a proposal is open for votes; 0 = governance disabled.
+    pub quorum_votes: u64, // Minimum staked $T2E voting on a proposal for it to pass.
+    pub proposal_min_stake: u64, // Effective stake required to open a proposal.
+    pub proposal_count: u64,
+    pub config_change_count: u64, // Timelocked config changes queued so far.
+    pub admin_set: Pubkey, // AdminSet PDA gating sensitive actions; default = single admin.
+    pub min_trade_volume: u64, // Smallest volume of a recorded trade; 0 = no minimum.
+    pub rate_window_trades: u8, // Max trades per `rate_window`; 0 = use `trade_cooldown` instead.
+    pub rate_window: i64, // Seconds of the sliding rate-limit window.
+    pub min_stake_for_ranking: u64, // Stake an identity needs to be ranked; 0 = no minimum.
+    pub registered_markets: u32, // Markets in the registry; 0 = any market may be traded.
+    pub early_unstake_min_penalty_bps: u16, // Penalty as a lock is about to expire.
+    pub early_unstake_max_penalty_bps: u16, // Penalty right after locking; 0 = early unstaking disabled.
+    pub early_unstake_penalty_to_treasury: bool, // Else penalties go to the reward vault.
+    pub reward_pool: u64, // Pool of each distribution before halvings and emission caps.
+    pub burn_epoch: i64, // Emission epoch `epoch_burned` was accumulated in.
+    pub epoch_burned: u64, // Burned by `burn_treasury` during `burn_epoch`.
+    pub bump: u8,
+}
+
+impl Leaderboard {
+    /// Byte offset of the entry slots in the account data.
+    pub const ENTRIES_OFFSET: usize = 8 + std::mem::size_of::<Leaderboard>();
+    /// Bytes of one entry slot across the trader, score and tie-break arrays.
+    pub const ENTRY_SIZE: usize =
+        std::mem::size_of::<Pubkey>() + std::mem::size_of::<u64>() + std::mem::size_of::<TieBreak>();
+
+    /// Account size of a leaderboard with `capacity` entry slots.
+    pub fn space(capacity: u32) -> usize {
+        Self::ENTRIES_OFFSET + capacity as usize * Self::ENTRY_SIZE
+    }
+
+    /// Entry slots that fit in account data of `data_len` bytes.
+    pub fn capacity_for(data_len: usize) -> u32 {
+        (data_len.saturating_sub(Self::ENTRIES_OFFSET) / Self::ENTRY_SIZE)
+            .min(MAX_LEADERBOARD_ENTRIES as usize) as u32
+    }
+}
+
+/// A leaderboard's header together with its entry slots, read from the
+/// account data `D`. Dereferences to the `Leaderboard` header.
+pub struct Board<D> {
+    data: D,
+}
+
+/// Loads a leaderboard with its entries for reading.
+pub fn load_board<'a>(loader: &'a AccountLoader<'_, Leaderboard>) -> Result<Board<std::cell::Ref<'a, [u8]>>> {
+    let data = loader.as_ref().try_borrow_data()?;
+    Board::new(std::cell::Ref::map(data, |data| &**data))
+}
+
+/// Loads a leaderboard with its entries for writing.
+pub fn load_board_mut<'a>(
+    loader: &'a AccountLoader<'_, Leaderboard>,
+) -> Result<Board<std::cell::RefMut<'a, [u8]>>> {
+    let info = loader.as_ref();
+    if !info.is_writable {
+        return Err(anchor_lang::error::ErrorCode::AccountNotMutable.into());
+    }
+    let data = info.try_borrow_mut_data()?;
+    Board::new(std::cell::RefMut::map(data, |data| &mut **data))
+}
+
+impl<D: std::ops::Deref<Target = [u8]>> Board<D> {
+    /// Wraps leaderboard account data, checking that it is aligned and holds
+    /// the header's `capacity` entry slots.
+    pub fn new(data: D) -> Result<Self> {
+        if data.len() < Leaderboard::ENTRIES_OFFSET
+            || !(data.as_ptr() as usize).is_multiple_of(std::mem::align_of::<Leaderboard>())
+        {
+            return Err(ErrorCode::InvalidLeaderboardAccount.into());
+        }
+        let board = Board { data };
+        if board.data.len() < Leaderboard::space(board.capacity) {
+            return Err(ErrorCode::InvalidLeaderboardAccount.into());
+        }
+        Ok(board)
+    }
+
+    /// The underlying account data.
+    pub fn into_inner(self) -> D {
+        self.data
+    }
+
+    /// Byte ranges of the trader, score and tie-break arrays.
+    fn slot_ranges(&self) -> [std::ops::Range<usize>; 3] {
+        let capacity = self.capacity as usize;
+        let scores = Leaderboard::ENTRIES_OFFSET + capacity * std::mem::size_of::<Pubkey>();
+        let tiebreaks = scores + capacity * std::mem::size_of::<u64>();
+        let end = tiebreaks + capacity * std::mem::size_of::<TieBreak>();
+        [Leaderboard::ENTRIES_OFFSET..scores, scores..tiebreaks, tiebreaks..end]
+    }
+
+    /// Ranked traders, best first.
+    pub fn traders(&self) -> &[Pubkey] {
+        let [traders, _, _] = self.slot_ranges();
+        &bytemuck::cast_slice(&self.data[traders])[..self.len as usize]
+    }
+
+    /// Scores of `traders()`, in the same order.
+    pub fn ranking_scores(&self) -> &[u64] {
+        let [_, scores, _] = self.slot_ranges();
+        &bytemuck::cast_slice(&self.data[scores])[..self.len as usize]
+    }
+
+    /// Tie-break criteria of `traders()`, in the same order.
+    pub fn tiebreaks(&self) -> &[TieBreak] {
+        let [_, _, tiebreaks] = self.slot_ranges();
+        &bytemuck::cast_slice(&self.data[tiebreaks])[..self.len as usize]
+    }
+
+    /// SHA-256 commitment to the current ranking (traders and scores, in order).
+    pub fn board_hash(&self) -> [u8; 32] {
+        let mut hasher = anchor_lang::solana_program::hash::Hasher::default();
+        for (trader, score) in self.traders().iter().zip(self.ranking_scores().iter()) {
+            hasher.hash(trader.as_ref());
+            hasher.hash(&score.to_le_bytes());
+        }
+        hasher.result().to_bytes()
+    }
+}
+
+impl<D: std::ops::DerefMut<Target = [u8]>> Board<D> {
+    /// All `capacity` trader, score and tie-break slots.
+    fn slots_mut(&mut self) -> (&mut [Pubkey], &mut [u64], &mut [TieBreak]) {
+        let [traders, scores, tiebreaks] = self.slot_ranges();
+        let (trader_bytes, rest) = self.data[traders.start..tiebreaks.end].split_at_mut(traders.len());
+        let (score_bytes, tiebreak_bytes) = rest.split_at_mut(scores.len());
+        (
+            bytemuck::cast_slice_mut(trader_bytes),
+            bytemuck::cast_slice_mut(score_bytes),
+            bytemuck::cast_slice_mut(tiebreak_bytes),
+        )
+    }
+
+    /// Overwrites the board entries with an already sorted ranking, truncated
+    /// to the account's capacity.
+    pub fn set_entries(&mut self, ranked_traders: &[RankedTrader]) {
+        let len = ranked_traders.len().min(self.capacity as usize);
+        let (traders, scores, tiebreaks) = self.slots_mut();
+        for (i, rt) in ranked_traders[..len].iter().enumerate() {
+            traders[i] = rt.trader;
+            scores[i] = rt.score;
+            tiebreaks[i] = rt.tiebreak;
+        }
+        self.len = len as u32;
+    }
+
+    /// Overwrites the score of the entry at `index`.
+    pub fn set_score(&mut self, index: usize, score: u64) {
+        self.slots_mut().1[index] = score;
+    }
+
+    /// Moves `entry.trader` to its sorted position for the new score,
+    /// inserting the trader if absent. At most `max_len` entries (and the
+    /// capacity) are kept, dropping whoever falls off the end.
+    pub fn upsert(&mut self, entry: RankedTrader, max_len: usize) {
+        if let Some(index) = self.traders().iter().position(|t| *t == entry.trader) {
+            self.remove(index);
+        }
+        let key = rank_key(&entry.trader, entry.score, &entry.tiebreak);
+        let (mut rank, mut end) = (0, self.len as usize);
+        while rank < end {
+            let mid = (rank + end) / 2;
+            if rank_key(&self.traders()[mid], self.ranking_scores()[mid], &self.tiebreaks()[mid]) < key {
+                rank = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+        let max_len = max_len.min(self.capacity as usize);
+        if rank >= max_len {
+            return;
+        }
+        let len = (self.len as usize + 1).min(max_len);
+        let (traders, scores, tiebreaks) = self.slots_mut();
+        traders.copy_within(rank..len - 1, rank + 1);
+        scores.copy_within(rank..len - 1, rank + 1);
+        tiebreaks.copy_within(rank..len - 1, rank + 1);
+        traders[rank] = entry.trader;
+        scores[rank] = entry.score;
+        tiebreaks[rank] = entry.tiebreak;
+        self.len = len as u32;
+    }
+
+    /// Removes the entry at `index`, shifting lower ranks up.
+    pub fn remove(&mut self, index: usize) {
+        let len = self.len as usize;
+        let (traders, scores, tiebreaks) = self.slots_mut();
+        traders.copy_within(index + 1..len, index);
+        scores.copy_within(index + 1..len, index);
+        tiebreaks.copy_within(index + 1..len, index);
+        self.len -= 1;
+    }
+
+    /// Replaces the board with staged entries already sorted by the caller.
+    pub fn apply_staged_ranking(&mut self, entries: &[PendingEntry], config: &Config, current_time: i64) {
+        let len = entries
+            .len()
+            .min(config.max_leaderboard_size as usize)
+            .min(self.capacity as usize);
+        let (traders, scores, tiebreaks) = self.slots_mut();
+        for (i, entry) in entries[..len].iter().enumerate() {
+            traders[i] = entry.trader;
+            scores[i] = entry.score;
+            tiebreaks[i] = entry.tiebreak;
+        }
+        self.len = len as u32;
+        self.last_update = current_time;
+        self.pending_flags = 0;
+        self.pending_disputes = 0;
+    }
+
+    /// Replaces the board with an already sorted ranking.
+    pub fn apply_ranking(&mut self, ranked_traders: &[RankedTrader], config: &Config, current_time: i64) {
+        let ranked_traders = &ranked_traders[..ranked_traders.len().min(config.max_leaderboard_size as usize)];
+        self.set_entries(ranked_traders);
+        self.last_update = current_time;
+        self.pending_flags = 0;
+        self.pending_disputes = 0;
+    }
+
+    /// Spreads the entry arrays over `capacity` slots after the account data
+    /// has been grown to `Leaderboard::space(capacity)`.
+    pub fn grow(&mut self, capacity: u32) {
+        let len = self.len as usize;
+        let [_, old_scores, old_tiebreaks] = self.slot_ranges();
+        self.capacity = capacity;
+        let [_, scores, tiebreaks] = self.slot_ranges();
+        // Move the later array first, as the arrays only move towards the end.
+        let tiebreak_bytes = len * std::mem::size_of::<TieBreak>();
+        self.data
+            .copy_within(old_tiebreaks.start..old_tiebreaks.start + tiebreak_bytes, tiebreaks.start);
+        let score_bytes = len * std::mem::size_of::<u64>();
+        self.data
+            .copy_within(old_scores.start..old_scores.start + score_bytes, scores.start);
+    }
+}
+
+impl<D: std::ops::Deref<Target = [u8]>> std::ops::Deref for Board<D> {
+    type Target = Leaderboard;
+
+    fn deref(&self) -> &Leaderboard {
+        bytemuck::from_bytes(&self.data[8..Leaderboard::ENTRIES_OFFSET])
+    }
+}
+
+impl<D: std::ops::DerefMut<Target = [u8]>> std::ops::DerefMut for Board<D> {
+    fn deref_mut(&mut self) -> &mut Leaderboard {
+        bytemuck::from_bytes_mut(&mut self.data[8..Leaderboard::ENTRIES_OFFSET])
+    }
+}
+
+impl StakePosition {
+    /// Extra stake credited for the position's amount under its multiplier.
+    pub fn bonus_stake(&self) -> Result<u64> {
+        let bonus = self.amount as u128 * (self.multiplier_bps - 10_000) as u128 / 10_000;
+        u64::try_from(bonus).map_err(|_| ErrorCode::Overflow.into())
+    }
+}
+
+impl VestingSchedule {
+    /// Amount unlocked by `current_time`, including released amounts.
+    pub fn vested(&self, current_time: i64) -> u64 {
+        t2e_core::vested_amount(self.total, current_time - self.start_ts, self.cliff, self.duration)
+    }
+
+    /// Unlocked amount not yet released.
+    pub fn releasable(&self, current_time: i64) -> u64 {
+        self.vested(current_time).saturating_sub(self.released)
+    }
+
+    /// Restarts the schedule at `current_time` under the configured vesting,
+    /// vesting `amount` together with the still-locked remainder. Returns the
+    /// unlocked but unreleased amount, which the caller credits elsewhere.
+    pub fn restart(&mut self, amount: u64, config: &Config, current_time: i64) -> Result<u64> {
+        let vested = self.vested(current_time);
+        let unlocked = vested.saturating_sub(self.released);
+        self.total = (self.total - vested)
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.released = 0;
+        self.start_ts = current_time;
+        self.cliff = config.vesting_cliff;
+        self.duration = config.vesting_duration;
+        Ok(unlocked)
+    }
+
+    /// Stops the schedule at `current_time`, leaving what has vested so far
+    /// releasable. Returns the still-locked remainder, which is forfeited.
+    pub fn cancel(&mut self, current_time: i64) -> u64 {
+        let vested = self.vested(current_time);
+        let unvested = self.total - vested;
+        self.total = vested;
+        self.cliff = 0;
+        self.duration = 0;
+        unvested
+    }
+}
+
+impl ClaimWindow {
+    /// Fails unless claims are currently accepted.
+    pub fn require_open(&self, current_time: i64) -> Result<()> {
+        if current_time < self.open_ts || current_time >= self.close_ts {
+            return Err(ErrorCode::ClaimWindowClosed.into());
+        }
+        Ok(())
+    }
+
+    /// Returns true once the window has closed and unclaimed rewards can be swept.
+    pub fn is_expired(&self, current_time: i64) -> bool {
+        current_time >= self.close_ts
+    }
+}
+
+impl EmissionSchedule {
+    /// Validates and stores the schedule parameters; `genesis_ts` must lie
+    /// in the future.
+    pub fn set(
+        &mut self,
+        genesis_ts: i64,
+        epoch_length: i64,
+        halving_interval: u32,
+        base_emission: u64,
+        current_time: i64,
+    ) -> Result<()> {
+        if genesis_ts <= current_time || epoch_length <= 0 || halving_interval == 0 {
+            return Err(ErrorCode::InvalidEmissionSchedule.into());
+        }
+        self.genesis_ts = genesis_ts;
+        self.epoch_length = epoch_length;
+        self.halving_interval = halving_interval;
+        self.base_emission = base_emission;
+        Ok(())
+    }
+
+    /// Emission epoch since genesis; 0 before launch.
+    pub fn epoch(&self, current_time: i64) -> i64 {
+        (current_time - self.genesis_ts).max(0) / self.epoch_length
+    }
+
+    /// `reward_amount`, capped at `base_emission` if set, halved once per
+    /// `halving_interval` epochs since genesis.
+    pub fn adjusted_reward(&self, reward_amount: u64, current_time: i64) -> u64 {
+        let pool = if self.base_emission > 0 {
+            reward_amount.min(self.base_emission)
+        } else {
+            reward_amount
+        };
+        let halvings = self.epoch(current_time) / self.halving_interval as i64;
+        u32::try_from(halvings)
+            .ok()
+            .and_then(|halvings| pool.checked_shr(halvings))
+            .unwrap_or(0)
+    }
+}
+
+impl ProposalAction {
+    /// Checks the proposed values with the rules of the matching admin setter.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ProposalAction::SetScoringConfig(scoring) => {
+                if scoring.volume_divisor == 0 || scoring.staking_divisor == 0 {
+                    return Err(ErrorCode::InvalidScoringConfig.into());
+                }
+            }
+            ProposalAction::SetHalvingPeriod(halving_period) => {
+                if *halving_period <= 0 {
+                    return Err(ErrorCode::InvalidProtocolParams.into());
+                }
+            }
+            ProposalAction::SetPauseFlags(pause_flags) => {
+                if pause_flags & !PAUSE_ALL != 0 {
+                    return Err(ErrorCode::InvalidPauseFlags.into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Role {
+    /// Whether `role` is a grant of `flag` to `key`.
+    pub fn grants(role: Option<&Role>, key: &Pubkey, flag: u8) -> bool {
+        role.is_some_and(|role| role.grantee == *key && role.roles & flag != 0)
+    }
+}
+
+impl AdminSet {
+    /// Position of `authority` in the set, which is its approval bit.
+    pub fn authority_index(&self, authority: &Pubkey) -> Result<usize> {
+        self.authorities
+            .iter()
+            .position(|a| a == authority)
+            .ok_or(ErrorCode::NotAdminSetMember.into())
+    }
+}
+
+impl Proposal {
+    /// Whether at least `quorum_votes` were cast and the majority voted for.
+    pub fn passed(&self, quorum_votes: u64) -> bool {
+        let total = self.votes_for as u128 + self.votes_against as u128;
+        total >= quorum_votes as u128 && self.votes_for > self.votes_against
+    }
+}
+
+impl Config {
+    /// Reward pool after halving once per `halving_period` since the Unix epoch.
+    pub fn halved_reward(&self, reward_amount: u64, current_time: i64) -> u64 {
+        t2e_core::halved_reward_with_period(reward_amount, current_time, self.halving_period)
+    }
+
+    /// Reward pool of a distribution: `reward_pool` per the emission schedule
+    /// once one is configured, which must then be passed, else halved by
+    /// `halved_reward`.
+    pub fn adjusted_reward(
+        &self,
+        emission_schedule: Option<&EmissionSchedule>,
+        current_time: i64,
+    ) -> Result<u64> {
+        if self.emission_schedule == Pubkey::default() {
+            return Ok(self.halved_reward(self.reward_pool, current_time));
+        }
+        let schedule = emission_schedule.ok_or(ErrorCode::EmissionScheduleNotProvided)?;
+        Ok(schedule.adjusted_reward(self.reward_pool, current_time))
+    }
+
+    /// Staking yield per staked token accrued since launch, scaled by
+    /// `STAKING_INDEX_SCALE`: the checkpointed index plus the yield of
+    /// `staking_apr_bps` since the checkpoint.
+    pub fn staking_index(&self, current_time: i64) -> Result<u128> {
+        let elapsed = current_time.saturating_sub(self.staking_index_updated).max(0) as u128;
+        let accrued = elapsed * self.staking_apr_bps as u128 * STAKING_INDEX_SCALE
+            / (10_000 * SECONDS_PER_YEAR as u128);
+        self.staking_reward_index
+            .checked_add(accrued)
+            .ok_or(ErrorCode::Overflow.into())
+    }
+
+    /// Applies a governance or timelocked config change, revalidating it as
+    /// parameters may have become invalid since it was proposed or queued.
+    pub fn apply_action(
+        &mut self,
+        action: ProposalAction,
+        authority: Pubkey,
+        current_time: i64,
+    ) -> Result<()> {
+        action.validate()?;
+        match action {
+            ProposalAction::SetScoringConfig(scoring) => self.scoring = scoring,
+            ProposalAction::SetHalvingPeriod(halving_period) => self.halving_period = halving_period,
+            ProposalAction::SetPauseFlags(pause_flags) => {
+                self.pause_flags = pause_flags;
+                emit!(PauseToggled {
+                    authority,
+                    leaderboard: None,
+                    pause_flags,
+                    timestamp: current_time,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// PDA index of the history snapshot with sequence number `history_count`:
+    /// the sequence number itself, or its ring-buffer slot under a retention.
+    pub fn snapshot_slot(&self, history_count: u64) -> u64 {
+        if self.snapshot_retention == 0 {
+            history_count
+        } else {
+            history_count % self.snapshot_retention
+        }
+    }
+
+    /// Returns the 1-based index and discount of the highest fee discount tier
+    /// reached by `staked`, or `(0, 0)` below the first tier.
+    pub fn fee_tier(&self, staked: u64) -> (u8, u8) {
+        self.fee_discount_tiers[..self.fee_discount_tier_count as usize]
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, tier)| staked >= tier.min_stake)
+            .map_or((0, 0), |(i, tier)| (i as u8 + 1, tier.discount))
+    }
+
+    /// Stores a validated fee discount tier table.
+    pub fn set_fee_discount_tiers(&mut self, tiers: &[FeeDiscountTier]) {
+        self.fee_discount_tiers = [FeeDiscountTier::default(); MAX_FEE_DISCOUNT_TIERS];
+        self.fee_discount_tiers[..tiers.len()].copy_from_slice(tiers);
+        self.fee_discount_tier_count = tiers.len() as u8;
+    }
+
+    /// Returns true for the super-authority or any co-admin.
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admin == *key || self.co_admins.contains(key)
+    }
+
+    /// Whether `key` may submit a multisig-gated instruction: anyone while an
+    /// admin set exists, as the approvals are checked instead, otherwise any admin.
+    pub fn may_submit_admin_action(&self, key: &Pubkey) -> bool {
+        self.admin_set != Pubkey::default() || self.is_admin(key)
+    }
+
+    /// Returns true for any admin or the configured operator.
+    pub fn is_operator(&self, key: &Pubkey) -> bool {
+        self.is_admin(key) || (self.operator != Pubkey::default() && self.operator == *key)
+    }
+
+    /// Returns true if the given pause scope is active.
+    pub fn is_paused(&self, scope: u8) -> bool {
+        self.pause_flags & scope != 0
+    }
+
+    /// Amount burned by `burn_treasury` during emission epoch `epoch`, as
+    /// long as it is the epoch of the latest burn.
+    pub fn burned_in_epoch(&self, epoch: i64) -> u64 {
+        if self.burn_epoch == epoch {
+            self.epoch_burned
+        } else {
+            0
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RankingFlag {
+    pub leaderboard: Pubkey,
+    pub flagger: Pubkey,
+    pub flagger_token_account: Pubkey, // Receives the bond back if the flag is upheld.
+    pub ranking_update: i64, // `last_update` of the ranking being disputed.
+    pub reason_hash: [u8; 32],
+    #[max_len(MAX_FLAG_EVIDENCE)]
+    pub evidence: Vec<Pubkey>,
+    pub flagged_at: i64,
+    pub bond_amount: u64,
+    pub resolved: bool,
+    pub upheld: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ScoreDispute {
+    pub leaderboard: Pubkey,
+    pub challenger: Pubkey,
+    pub challenger_token_account: Pubkey,
+    pub trader_stats: Pubkey,
+    pub trader: Pubkey,
+    pub ranking_update: i64, // `last_update` of the ranking being disputed.
+    pub rank_index: u32,
+    pub posted_score: u64,
+    pub claimed_score: u64, // Score recomputed from the referenced TraderStats.
+    pub bond_amount: u64,
+    pub resolved: bool,
+    pub upheld: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SeasonPass {
+    pub trader: Pubkey,
+    pub season: u32,
+    pub price_paid: u64,
+    pub purchased_at: i64,
+    pub cosmetic_flags: u8, // Frontend cosmetics (badge frame, name color, ...).
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct FollowLink {
+    pub follower: Pubkey,
+    pub leader: Pubkey,
+    pub followed_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct LeaderRevenue {
+    pub leader: Pubkey,
+    pub epoch: i64, // Trade epoch (see `Config::trade_epoch_length`).
+    pub accrued: u64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl LeaderRevenue {
+    /// Credits the leader's share of a follower's protocol fee and returns the
+    /// amount credited. Called by `collect_trading_fee`.
+    pub fn accrue_share(&mut self, fee: u64, leader_share_bps: u16) -> Result<u64> {
+        let share = ((fee as u128) * (leader_share_bps as u128) / 10_000) as u64;
+        self.accrued = self.accrued.checked_add(share).ok_or(ErrorCode::Overflow)?;
+        Ok(share)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TraderProfile {
+    pub trader: Pubkey,
+    pub display_name: [u8; 32], // Zero-padded UTF-8.
+    #[max_len(MAX_PROFILE_URI_LEN)]
+    pub avatar_uri: String,
+    pub social_links_hash: [u8; 32],
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+/// Reserves a display name for the trader whose profile uses it.
+#[account]
+#[derive(InitSpace)]
+pub struct DisplayName {
+    pub owner: Pubkey,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Endorsement {
+    pub endorser: Pubkey,
+    pub endorsee: Pubkey,
+    pub weight: u64,
+    pub endorsed_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Quest {
+    pub quest_id: u64,
+    pub kind: QuestKind,
+    pub target: u64, // Volume or trades to reach within one period.
+    pub reward_amount: u64,
+    pub reward_mint: Pubkey, // Paid from the reward vault of this mint.
+    pub period: i64, // Seconds; progress restarts at each multiple.
+    pub end_ts: i64, // 0 while open-ended.
+    pub active: bool,
+    pub completions: u64, // Rewards claimed so far.
+    pub bump: u8,
+}
+
+impl Quest {
+    /// Whether trades still advance the quest.
+    pub fn is_live(&self, now: i64) -> bool {
+        self.active && (self.end_ts == 0 || now < self.end_ts)
+    }
+
+    /// Index of the period containing `now`.
+    pub fn period_index(&self, now: i64) -> i64 {
+        now.div_euclid(self.period)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct QuestProgress {
+    pub quest: Pubkey,
+    pub trader: Pubkey,
+    pub period: i64, // Period index `progress` counts towards.
+    pub progress: u64,
+    pub claimed: bool, // Reward of `period` paid.
+    pub bump: u8,
+}
+
+impl QuestProgress {
+    /// Counts a trade of credited `volume` towards the quest, starting over
+    /// once a new period has begun.
+    pub fn advance(&mut self, quest: &Quest, volume: u64, now: i64) {
+        let period = quest.period_index(now);
+        if self.period != period {
+            self.period = period;
+            self.progress = 0;
+            self.claimed = false;
+        }
+        let amount = match quest.kind {
+            QuestKind::Volume => volume,
+            QuestKind::TradeCount => 1,
+        };
+        self.progress = self.progress.saturating_add(amount);
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct EmergencyWithdrawal {
+    pub reward_vault: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub queued_at: i64,
+    pub executable_at: i64,
+    pub bump: u8,
+}
+
+/// Roles granted to a key (PDA `["role", grantee]`).
+#[account]
+#[derive(InitSpace)]
+pub struct Role {
+    pub grantee: Pubkey,
+    pub roles: u8, // Bitmask of ROLE_* flags.
+    pub granted_at: i64,
+    pub bump: u8,
+}
+
+/// Multisig of authorities approving sensitive actions (PDA `["admin_set"]`).
+#[account]
+#[derive(InitSpace)]
+pub struct AdminSet {
+    #[max_len(MAX_ADMIN_SET_SIZE)]
+    pub authorities: Vec<Pubkey>,
+    pub threshold: u8, // Approvals needed to execute an action.
+    pub action_count: u64,
+    pub bump: u8,
+}
+
+/// Sensitive instruction and arguments gated by the admin set.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum AdminAction {
+    PauseRewards { leaderboard: Pubkey, paused: bool },
+    SlashTrader { trader: Pubkey, slash_bps: u16, reason_hash: [u8; 32] },
+    WithdrawTreasury { destination: Pubkey, amount: u64 },
+    ClawbackVesting { trader: Pubkey, leaderboard: Pubkey, to_insurance_fund: bool },
+}
+
+/// Admin set approvals of one action (PDA `["admin_action", id]`).
+#[account]
+#[derive(InitSpace)]
+pub struct PendingAdminAction {
+    pub id: u64,
+    pub action: AdminAction,
+    pub proposer: Pubkey,
+    pub approvals: u16, // Bit i set = `authorities[i]` approved.
+    pub executed: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Timelocked config change (PDA `["config_change", id]`).
+#[account]
+#[derive(InitSpace)]
+pub struct PendingConfigChange {
+    pub id: u64,
+    pub action: ProposalAction,
+    pub queued_at: i64,
+    pub executable_at: i64,
+    pub bump: u8,
+}
+
+/// One entry of a history snapshot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SnapshotEntry {
+    pub trader: Pubkey,
+    pub score: u64,
+}
+
+/// A leaderboard's ranking as of one history snapshot
+/// (PDA `["snapshot", leaderboard, index]`). Keeps the top
+/// `MAX_SNAPSHOT_ENTRIES` entries and a commitment to the full ranking.
+#[account]
+#[derive(InitSpace)]
+pub struct LeaderboardSnapshot {
+    pub leaderboard: Pubkey,
+    pub index: u64, // Sequence number among the leaderboard's history snapshots.
+    pub taken_at: i64,
+    pub ranking_update: i64, // `last_update` of the snapshotted ranking.
+    pub traders_ranked: u32, // Full ranking length, possibly beyond `entries`.
+    pub board_hash: [u8; 32], // See `Leaderboard::board_hash`.
+    #[max_len(MAX_SNAPSHOT_ENTRIES)]
+    pub entries: Vec<SnapshotEntry>,
+    pub bump: u8,
+}
+
+/// One scored entry of a staged leaderboard update.
+#[zero_copy]
+pub struct PendingEntry {
+    pub trader: Pubkey,
+    pub score: u64,
+    pub tiebreak: TieBreak,
+}
+
+/// Header of a staged leaderboard update (PDA `["pending_update", leaderboard]`).
+/// The `len` staged `PendingEntry` values follow the header in the account data.
+#[account(zero_copy)]
+pub struct PendingUpdate {
+    pub leaderboard: Pubkey,
+    pub authority: Pubkey, // Admin or operator that began the update.
+    pub started_at: i64,
+    pub len: u32,
+    pub next_page: u32,
+}
+
+impl PendingUpdate {
+    /// Byte offset of the staged entries in the account data.
+    pub const ENTRIES_OFFSET: usize = 8 + std::mem::size_of::<PendingUpdate>();
+}
+
+/// The first `len` staged entries stored after a `PendingUpdate` header.
+pub fn pending_entries(data: &mut [u8], len: usize) -> Result<&mut [PendingEntry]> {
+    let end = PendingUpdate::ENTRIES_OFFSET + len * std::mem::size_of::<PendingEntry>();
+    let bytes = data
+        .get_mut(PendingUpdate::ENTRIES_OFFSET..end)
+        .ok_or(ErrorCode::InvalidPendingUpdate)?;
+    bytemuck::try_cast_slice_mut(bytes).map_err(|_| ErrorCode::InvalidPendingUpdate.into())
+}
+
+impl LeaderboardSnapshot {
+    /// Captures the current ranking of `leaderboard` as its next history snapshot.
+    pub fn capture<D: std::ops::Deref<Target = [u8]>>(
+        key: Pubkey,
+        leaderboard: &Board<D>,
+        current_time: i64,
+        bump: u8,
+    ) -> Self {
+        let entries = leaderboard
+            .traders()
+            .iter()
+            .zip(leaderboard.ranking_scores().iter())
+            .take(MAX_SNAPSHOT_ENTRIES)
+            .map(|(trader, score)| SnapshotEntry {
+                trader: *trader,
+                score: *score,
+            })
+            .collect();
+        LeaderboardSnapshot {
+            leaderboard: key,
+            index: leaderboard.history_count,
+            taken_at: current_time,
+            ranking_update: leaderboard.last_update,
+            traders_ranked: leaderboard.len,
+            board_hash: leaderboard.board_hash(),
+            entries,
+            bump,
+        }
+    }
+
+    /// Emits `SnapshotTaken` for this snapshot, stored at `key`. The entries
+    /// themselves stay in the account.
+    pub fn emit_taken(&self, key: Pubkey) {
+        emit!(SnapshotTaken {
+            leaderboard: self.leaderboard,
+            snapshot: key,
+            index: self.index,
+            ranking_update: self.ranking_update,
+            traders_ranked: self.traders_ranked,
+            board_hash: self.board_hash,
+            timestamp: self.taken_at,
+        });
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(zero)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+}
+
+#[derive(Accounts)]
+pub struct GrowLeaderboard<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_operator(&authority.key()) || Role::grants(role.as_deref(), &authority.key(), ROLE_OPERATOR) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    /// The authority's role grant, if it acts as an operator through one.
+    pub role: Option<Account<'info, Role>>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitLeaderboardRegistry<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + LeaderboardRegistry::INIT_SPACE,
+        seeds = [b"leaderboard_registry"],
+        bump
+    )]
+    pub registry: Account<'info, LeaderboardRegistry>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterLeaderboard<'info> {
+    #[account(mut, seeds = [b"leaderboard_registry"], bump = registry.bump)]
+    pub registry: Account<'info, LeaderboardRegistry>,
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(init, payer = admin, space = 8 + Config::INIT_SPACE, seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitEmissionSchedule<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EmissionSchedule::INIT_SPACE,
+        seeds = [b"emission_schedule"],
+        bump
+    )]
+    pub emission_schedule: Account<'info, EmissionSchedule>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEmissionSchedule<'info> {
+    #[account(mut, seeds = [b"emission_schedule"], bump = emission_schedule.bump)]
+    pub emission_schedule: Account<'info, EmissionSchedule>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", &config.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(seeds = [b"trader_stats", proposer.key().as_ref()], bump = proposer_stats.bump)]
+    pub proposer_stats: Account<'info, TraderStats>,
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut, seeds = [b"proposal", &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(seeds = [b"trader_stats", voter.key().as_ref()], bump = voter_stats.bump)]
+    pub voter_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut, seeds = [b"proposal", &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct InitTreasury<'info> {
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump
+    )]
+    pub treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the treasury; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitStakingRewardVault<'info> {
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+        seeds = [b"staking_rewards", mint.key().as_ref()],
+        bump
+    )]
+    pub staking_reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the vault; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitStakingVault<'info> {
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+        seeds = [b"staking_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the vault; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AccrueStakingRewards<'info> {
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStakingRewards<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump, constraint = !trader_stats.banned @ ErrorCode::TraderBanned)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        mut,
+        seeds = [b"staking_rewards", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub staking_reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Receives the claim when compounding.
+    #[account(
+        mut,
+        seeds = [b"staking_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing staking reward payouts.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct BurnTreasury<'info> {
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::TreasuryNotProvided,
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mut, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing treasury burns.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitTraderStats<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + TraderStats::INIT_SPACE,
+        seeds = [b"trader_stats", trader.key().as_ref()],
+        bump
+    )]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordTrade<'info> {
+    #[account(
+        mut,
+        seeds = [b"trader_stats", trader_stats.trader.as_ref()],
+        bump = trader_stats.bump,
+        constraint = trader_stats.trader == trader.key() || delegate.is_some() @ ErrorCode::Unauthorized
+    )]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub pair_volume: Option<Account<'info, PairVolume>>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub season: Option<Account<'info, Season>>,
+    #[account(mut)]
+    pub season_stats: Option<Account<'info, TraderSeasonStats>>,
+    #[account(mut)]
+    pub market_stats: Option<Account<'info, MarketStats>>,
+    #[account(seeds = [b"market", registered_market.market.as_ref()], bump = registered_market.bump)]
+    pub registered_market: Option<Account<'info, Market>>,
+    /// Live-ranked leaderboard to update with the trader's new score.
+    #[account(mut)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    /// Required when `trader` signs as a delegate of the stats' owner.
+    #[account(seeds = [b"delegate", trader_stats.trader.as_ref(), trader.key().as_ref()], bump = delegate.bump)]
+    pub delegate: Option<Account<'info, Delegate>>,
+    /// The trader, or a delegate key of theirs.
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLiveRanking<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(attestation: TradeAttestation)]
+pub struct RecordAttestedTrade<'info> {
+    #[account(
+        mut,
+        seeds = [b"trader_stats", attestation.trader.as_ref()],
+        bump = trader_stats.bump
+    )]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: Instructions sysvar, used to locate the signature verification.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u32)]
+pub struct StartSeason<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Season::INIT_SPACE,
+        seeds = [b"season".as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub season: Account<'info, Season>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EndSeason<'info> {
+    #[account(mut, seeds = [b"season".as_ref(), &season.index.to_le_bytes()], bump = season.bump)]
+    pub season: Account<'info, Season>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetTraderStats<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TraderSeasonArchive::INIT_SPACE,
+        seeds = [b"trader_season_archive", season.key().as_ref(), trader_stats.trader.as_ref()],
+        bump
+    )]
+    pub archive: Account<'info, TraderSeasonArchive>,
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"season".as_ref(), &season.index.to_le_bytes()], bump = season.bump)]
+    pub season: Account<'info, Season>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitTraderSeasonStats<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + TraderSeasonStats::INIT_SPACE,
+        seeds = [b"trader_season_stats", season.key().as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub season_stats: Account<'info, TraderSeasonStats>,
+    #[account(
+        seeds = [b"season".as_ref(), &season.index.to_le_bytes()],
+        bump = season.bump,
+        constraint = !season.ended @ ErrorCode::SeasonEnded
+    )]
+    pub season: Account<'info, Season>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct RegisterPartner<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Partner::INIT_SPACE,
+        seeds = [b"partner", program_id.as_ref()],
+        bump
+    )]
+    pub partner: Account<'info, Partner>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market: Pubkey)]
+pub struct RegisterMarket<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + Market::INIT_SPACE,
+        seeds = [b"market", market.as_ref()],
+        bump
+    )]
+    pub registered_market: Account<'info, Market>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterMarket<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"market", registered_market.market.as_ref()],
+        bump = registered_market.bump
+    )]
+    pub registered_market: Account<'info, Market>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePartner<'info> {
+    #[account(mut, seeds = [b"partner", partner.program_id.as_ref()], bump = partner.bump)]
+    pub partner: Account<'info, Partner>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordPartnerTrade<'info> {
+    #[account(
+        mut,
+        seeds = [b"partner", partner.program_id.as_ref()],
+        bump = partner.bump,
+        constraint = partner.signer == partner_signer.key() @ ErrorCode::Unauthorized
+    )]
+    pub partner: Account<'info, Partner>,
+    pub partner_signer: Signer<'info>,
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, token::authority = partner_signer)]
+    pub fee_payer_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryNotProvided)]
+    pub treasury: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    #[account(mut)]
+    pub pair_volume: Option<Account<'info, PairVolume>>,
+    pub season: Option<Account<'info, Season>>,
+    #[account(mut)]
+    pub season_stats: Option<Account<'info, TraderSeasonStats>>,
+    #[account(mut)]
+    pub market_stats: Option<Account<'info, MarketStats>>,
+    #[account(seeds = [b"market", registered_market.market.as_ref()], bump = registered_market.bump)]
+    pub registered_market: Option<Account<'info, Market>>,
+    /// Live-ranked leaderboard to update with the trader's new score.
+    #[account(mut)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+}
+
+#[derive(Accounts)]
+pub struct InitFeeVault<'info> {
+    #[account(
+        init,
+        payer = admin,
+        token::mint = fee_mint,
+        token::authority = vault_authority,
+        seeds = [b"fee_vault", fee_mint.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    pub fee_mint: Account<'info, token::Mint>,
+    /// CHECK: PDA owning the fee vault; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitRewardVault<'info> {
+    #[account(
+        init,
+        payer = admin,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+        seeds = [b"reward_vault", reward_mint.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the reward vault; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewardVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"reward_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = funder,
+        token::token_program = token_program
+    )]
+    pub funder_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    pub funder: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CollectTradingFee<'info> {
+    #[account(
+        seeds = [b"partner", partner.program_id.as_ref()],
+        bump = partner.bump,
+        constraint = partner.signer == partner_signer.key() @ ErrorCode::Unauthorized
+    )]
+    pub partner: Account<'info, Partner>,
+    pub partner_signer: Signer<'info>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut, token::mint = fee_vault.mint, token::authority = trader)]
+    pub trader_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"fee_vault", fee_vault.mint.as_ref()], bump)]
+    pub fee_vault: Account<'info, TokenAccount>,
+    /// CHECK: The trader's FollowLink PDA; may not exist. Deserialized in the
+    /// handler when it does.
+    #[account(seeds = [b"follow", trader.key().as_ref()], bump)]
+    pub follow_link: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"leader_revenue", leader_revenue.leader.as_ref(), &leader_revenue.epoch.to_le_bytes()],
+        bump = leader_revenue.bump
+    )]
+    pub leader_revenue: Option<Account<'info, LeaderRevenue>>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BeginAmmSwap<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + AmmSwapReceipt::INIT_SPACE,
+        seeds = [b"amm_swap", trader.key().as_ref()],
+        bump
+    )]
+    pub amm_swap_receipt: Account<'info, AmmSwapReceipt>,
+    #[account(token::authority = trader)]
+    pub source_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    /// CHECK: Instructions sysvar, used to locate the swap instruction.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordAmmSwap<'info> {
+    #[account(
+        mut,
+        close = trader,
+        seeds = [b"amm_swap", trader.key().as_ref()],
+        bump = amm_swap_receipt.bump,
+        has_one = trader,
+        has_one = source_token_account
+    )]
+    pub amm_swap_receipt: Account<'info, AmmSwapReceipt>,
+    pub source_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    /// CHECK: Instructions sysvar, used to locate the swap instruction.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    #[account(address = source_token_account.mint)]
+    pub source_mint: InterfaceAccount<'info, Mint>,
+    #[account(seeds = [b"price_feed", source_token_account.mint.as_ref()], bump = price_feed.bump)]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+    /// CHECK: Pyth `PriceUpdateV2` account, validated in `read_pyth_price`.
+    pub price_update: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct InitFundingCursor<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + FundingCursor::INIT_SPACE,
+        seeds = [b"funding", trader.key().as_ref(), venue_user.key().as_ref()],
+        bump
+    )]
+    pub funding_cursor: Account<'info, FundingCursor>,
+    /// CHECK: Drift `User` account; owner and authority are checked on read.
+    pub venue_user: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordFunding<'info> {
+    #[account(
+        mut,
+        seeds = [b"funding", trader_stats.trader.as_ref(), venue_user.key().as_ref()],
+        bump = funding_cursor.bump,
+        has_one = venue_user
+    )]
+    pub funding_cursor: Account<'info, FundingCursor>,
+    /// CHECK: Drift `User` account; owner and authority are checked on read.
+    pub venue_user: UncheckedAccount<'info>,
+    #[account(mut, constraint = trader_stats.trader == funding_cursor.trader)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(pair: Pubkey)]
+pub struct InitPairVolume<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + PairVolume::INIT_SPACE,
+        seeds = [b"pair_volume", trader.key().as_ref(), pair.as_ref()],
+        bump
+    )]
+    pub pair_volume: Account<'info, PairVolume>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market: Pubkey)]
+pub struct InitMarketStats<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + MarketStats::INIT_SPACE,
+        seeds = [b"market_stats", market.as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub market_stats: Account<'info, MarketStats>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LinkWallet<'info> {
+    #[account(
+        init,
+        payer = primary,
+        space = 8 + WalletLink::INIT_SPACE,
+        seeds = [b"wallet_link", secondary.key().as_ref()],
+        bump
+    )]
+    pub wallet_link: Account<'info, WalletLink>,
+    #[account(mut, constraint = primary_stats.trader == primary.key() @ ErrorCode::Unauthorized)]
+    pub primary_stats: Account<'info, TraderStats>,
+    #[account(mut, constraint = secondary_stats.trader == secondary.key() @ ErrorCode::Unauthorized)]
+    pub secondary_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub primary: Signer<'info>,
+    pub secondary: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(signer: Pubkey)]
+pub struct AuthorizeDelegate<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + Delegate::INIT_SPACE,
+        seeds = [b"delegate", trader.key().as_ref(), signer.as_ref()],
+        bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(
+        mut,
+        close = trader,
+        seeds = [b"delegate", trader.key().as_ref(), delegate.signer.as_ref()],
+        bump = delegate.bump,
+        has_one = trader
+    )]
+    pub delegate: Account<'info, Delegate>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkWallet<'info> {
+    #[account(
+        mut,
+        close = primary,
+        seeds = [b"wallet_link", secondary.key().as_ref()],
+        bump = wallet_link.bump,
+        has_one = primary,
+        has_one = secondary
+    )]
+    pub wallet_link: Account<'info, WalletLink>,
+    #[account(mut, constraint = primary_stats.trader == primary.key() @ ErrorCode::Unauthorized)]
+    pub primary_stats: Account<'info, TraderStats>,
+    #[account(mut, constraint = secondary_stats.trader == secondary.key() @ ErrorCode::Unauthorized)]
+    pub secondary_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub primary: Signer<'info>,
+    pub secondary: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLeaderboard<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_operator(&authority.key()) || Role::grants(role.as_deref(), &authority.key(), ROLE_OPERATOR) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    /// The authority's role grant, if it acts as an operator through one.
+    pub role: Option<Account<'info, Role>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BeginLeaderboardUpdate<'info> {
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(
+        init,
+        payer = authority,
+        space = PendingUpdate::ENTRIES_OFFSET,
+        seeds = [b"pending_update", leaderboard.key().as_ref()],
+        bump
+    )]
+    pub pending_update: AccountLoader<'info, PendingUpdate>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_operator(&authority.key()) || Role::grants(role.as_deref(), &authority.key(), ROLE_OPERATOR) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    /// The authority's role grant, if it acts as an operator through one.
+    pub role: Option<Account<'info, Role>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitRankingPage<'info> {
+    #[account(mut, has_one = authority)]
+    pub pending_update: AccountLoader<'info, PendingUpdate>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CommitLeaderboardUpdate<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(mut, has_one = leaderboard, has_one = authority, close = authority)]
+    pub pending_update: AccountLoader<'info, PendingUpdate>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_operator(&authority.key()) || Role::grants(role.as_deref(), &authority.key(), ROLE_OPERATOR) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    /// The authority's role grant, if it acts as an operator through one.
+    pub role: Option<Account<'info, Role>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLeaderboardUpdate<'info> {
+    #[account(mut, has_one = authority, close = authority)]
+    pub pending_update: AccountLoader<'info, PendingUpdate>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PruneInactive<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct InitAutomationSchedule<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AutomationSchedule::INIT_SPACE,
+        seeds = [b"automation", leaderboard.key().as_ref()],
+        bump
+    )]
+    pub automation_schedule: Account<'info, AutomationSchedule>,
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CrankUpdateLeaderboard<'info> {
+    #[account(
+        mut,
+        seeds = [b"automation", leaderboard.key().as_ref()],
+        bump = automation_schedule.bump,
+        has_one = leaderboard,
+        constraint = automation_schedule.thread == thread.key() || Role::grants(role.as_deref(), &thread.key(), ROLE_KEEPER) @ ErrorCode::Unauthorized
+    )]
+    pub automation_schedule: Account<'info, AutomationSchedule>,
+    /// The signer's role grant, if it cranks as a keeper instead of the thread.
+    pub role: Option<Account<'info, Role>>,
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub thread: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CrankSnapshot<'info> {
+    #[account(
+        mut,
+        seeds = [b"automation", leaderboard.key().as_ref()],
+        bump = automation_schedule.bump,
+        has_one = leaderboard,
+        constraint = automation_schedule.thread == thread.key() || Role::grants(role.as_deref(), &thread.key(), ROLE_KEEPER) @ ErrorCode::Unauthorized
+    )]
+    pub automation_schedule: Account<'info, AutomationSchedule>,
+    /// The signer's role grant, if it cranks as a keeper instead of the thread.
+    pub role: Option<Account<'info, Role>>,
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = thread,
+        space = 8 + LeaderboardSnapshot::INIT_SPACE,
+        seeds = [
+            b"snapshot",
+            leaderboard.key().as_ref(),
+            &config.snapshot_slot(leaderboard.load()?.history_count).to_le_bytes()
+        ],
+        bump
+    )]
+    pub snapshot: Account<'info, LeaderboardSnapshot>,
+    #[account(mut)]
+    pub thread: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRankingSnapshot<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RankingSnapshot::INIT_SPACE,
+        seeds = [
+            b"ranking_snapshot",
+            leaderboard.key().as_ref(),
+            &leaderboard.load()?.snapshot_sequence.to_le_bytes()
+        ],
+        bump
+    )]
+    pub ranking_snapshot: Account<'info, RankingSnapshot>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_operator(&authority.key()) || Role::grants(role.as_deref(), &authority.key(), ROLE_OPERATOR | ROLE_KEEPER) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    /// The authority's role grant, if it acts as an operator or keeper through one.
+    pub role: Option<Account<'info, Role>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: i64)]
+pub struct SetClaimWindow<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + ClaimWindow::INIT_SPACE,
+        seeds = [b"claim_window".as_ref(), &epoch.to_le_bytes()],
+        bump
+    )]
+    pub claim_window: Account<'info, ClaimWindow>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceFeed<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PriceFeed::INIT_SPACE,
+        seeds = [b"price_feed", mint.key().as_ref()],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewScores<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewPayout<'info> {
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader_stats: Account<'info, TraderStats>,
+    /// Required once `config.emission_schedule` is set.
+    #[account(address = config.emission_schedule @ ErrorCode::EmissionScheduleNotProvided)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeRewards<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"reward_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing reward vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// Receives payouts of traders who opted into auto-staking.
+    #[account(
+        mut,
+        seeds = [b"staking_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Receives payouts of traders who opted into USDC; `Pubkey::default()` while disabled.
+    #[account(mut, address = config.swap_escrow @ ErrorCode::SwapPayoutUnavailable)]
+    pub swap_escrow: UncheckedAccount<'info>,
+    /// Receives the rounding remainder when a treasury is configured.
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryNotProvided)]
+    pub treasury: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(mut, has_one = leaderboard)]
+    pub ranking_snapshot: Account<'info, RankingSnapshot>,
+    /// Required once `config.emission_schedule` is set.
+    #[account(address = config.emission_schedule @ ErrorCode::EmissionScheduleNotProvided)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitRewardClaim<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RewardClaim::INIT_SPACE,
+        seeds = [b"reward_claim", leaderboard.key().as_ref(), trader_stats.trader.as_ref()],
+        bump
+    )]
+    pub reward_claim: Account<'info, RewardClaim>,
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitVestingSchedule<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VestingSchedule::INIT_SPACE,
+        seeds = [b"vesting", leaderboard.key().as_ref(), trader_stats.trader.as_ref()],
+        bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeEpoch<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"reward_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Pays for the claim vault on the leaderboard's first epoch.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+        seeds = [b"claim_vault", leaderboard.key().as_ref()],
+        bump
+    )]
+    pub claim_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the claim vault and signing reward vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// Receives the rounding remainder when a treasury is configured.
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryNotProvided)]
+    pub treasury: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(mut, has_one = leaderboard)]
+    pub ranking_snapshot: Account<'info, RankingSnapshot>,
+    /// Required once `config.emission_schedule` is set.
+    #[account(address = config.emission_schedule @ ErrorCode::EmissionScheduleNotProvided)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"reward_claim", reward_claim.leaderboard.as_ref(), trader.key().as_ref()],
+        bump = reward_claim.bump
+    )]
+    pub reward_claim: Account<'info, RewardClaim>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump, constraint = !trader_stats.banned @ ErrorCode::TraderBanned)]
+    pub trader_stats: Account<'info, TraderStats>,
+    /// CHECK: ClaimWindow PDA of the claim's epoch; may not exist, in which
+    /// case claims are not time-limited.
+    #[account(seeds = [b"claim_window".as_ref(), &reward_claim.epoch.to_le_bytes()], bump)]
+    pub claim_window: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"claim_vault", reward_claim.leaderboard.as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub claim_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing claim vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Receives the claim of traders who opted into auto-staking or compound.
+    #[account(
+        mut,
+        seeds = [b"staking_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Receives the claim of traders who opted into USDC; `Pubkey::default()` while disabled.
+    #[account(mut, address = config.swap_escrow @ ErrorCode::SwapPayoutUnavailable)]
+    pub swap_escrow: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(
+        mut,
+        seeds = [b"vesting", vesting_schedule.leaderboard.as_ref(), trader.key().as_ref()],
+        bump = vesting_schedule.bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    #[account(seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump, constraint = !trader_stats.banned @ ErrorCode::TraderBanned)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        mut,
+        seeds = [b"claim_vault", vesting_schedule.leaderboard.as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub claim_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing claim vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SweepExpiredClaim<'info> {
+    #[account(mut)]
+    pub reward_claim: Account<'info, RewardClaim>,
+    #[account(seeds = [b"claim_window".as_ref(), &reward_claim.epoch.to_le_bytes()], bump = claim_window.bump)]
+    pub claim_window: Account<'info, ClaimWindow>,
+    #[account(
+        mut,
+        seeds = [b"claim_vault", reward_claim.leaderboard.as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub claim_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing claim vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryNotProvided)]
+    pub treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct PostRewardRoot<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RewardRoot::INIT_SPACE,
+        seeds = [b"reward_root".as_ref(), leaderboard.key().as_ref(), &epoch.to_le_bytes()],
+        bump
+    )]
+    pub reward_root: Account<'info, RewardRoot>,
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"reward_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Pays for the claim vault on the leaderboard's first epoch.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+        seeds = [b"claim_vault", leaderboard.key().as_ref()],
+        bump
+    )]
+    pub claim_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the claim vault and signing reward vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWithProof<'info> {
+    #[account(mut)]
+    pub reward_root: Account<'info, RewardRoot>,
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + RootClaimReceipt::INIT_SPACE,
+        seeds = [b"root_claim", reward_root.key().as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub claim_receipt: Account<'info, RootClaimReceipt>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump, constraint = !trader_stats.banned @ ErrorCode::TraderBanned)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        mut,
+        seeds = [b"claim_vault", reward_root.leaderboard.as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub claim_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing claim vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Receives the claim of traders who opted into auto-staking.
+    #[account(
+        mut,
+        seeds = [b"staking_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Receives the claim of traders who opted into USDC; `Pubkey::default()` while disabled.
+    #[account(mut, address = config.swap_escrow @ ErrorCode::SwapPayoutUnavailable)]
+    pub swap_escrow: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SuspendTrader<'info> {
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BanTrader<'info> {
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClawbackVesting<'info> {
+    #[account(
+        mut,
+        seeds = [b"vesting", vesting_schedule.leaderboard.as_ref(), trader_stats.trader.as_ref()],
+        bump = vesting_schedule.bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    #[account(
+        seeds = [b"trader_stats", trader_stats.trader.as_ref()],
+        bump = trader_stats.bump,
+        constraint = trader_stats.banned @ ErrorCode::TraderNotBanned
+    )]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        mut,
+        seeds = [b"claim_vault", vesting_schedule.leaderboard.as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub claim_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// The insurance fund or the reward vault of the reward mint.
+    #[account(mut, token::mint = reward_mint, token::token_program = token_program)]
+    pub destination: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA signing claim vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(address = config.admin_set @ ErrorCode::AdminSetNotProvided)]
+    pub admin_set: Option<Account<'info, AdminSet>>,
+    /// Approved action; required while an admin set exists.
+    #[account(mut)]
+    pub admin_action: Option<Account<'info, PendingAdminAction>>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.may_submit_admin_action(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SlashTrader<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + SlashRecord::INIT_SPACE,
+        seeds = [
+            b"slash_record".as_ref(),
+            trader_stats.trader.as_ref(),
+            &trader_stats.slash_count.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    /// The trader's stats for the active season; required while one is running.
+    #[account(mut)]
+    pub season_stats: Option<Account<'info, TraderSeasonStats>>,
+    #[account(
+        mut,
+        seeds = [b"staking_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::TreasuryNotProvided,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA signing staking vault withdrawals.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(address = config.admin_set @ ErrorCode::AdminSetNotProvided)]
+    pub admin_set: Option<Account<'info, AdminSet>>,
+    /// Approved action; required while an admin set exists.
+    #[account(mut)]
+    pub admin_action: Option<Account<'info, PendingAdminAction>>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.may_submit_admin_action(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenAppeal<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + Appeal::INIT_SPACE,
+        seeds = [b"appeal", trader_stats.key().as_ref()],
+        bump
+    )]
+    pub appeal: Account<'info, Appeal>,
+    #[account(seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = insurance_fund)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// Pins the bond to a reward mint.
+    #[account(
+        seeds = [b"reward_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Receives the bond if the penalty is upheld, so it must hold the same mint.
+    #[account(token::mint = mint, token::token_program = token_program)]
+    pub insurance_fund: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Emptied on resolution and reused by the trader's next appeal.
+    #[account(
+        init_if_needed,
+        payer = trader,
+        seeds = [b"appeal_bond", appeal.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub bond_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA owning the bond vault; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveAppeal<'info> {
+    #[account(
+        mut,
+        close = trader,
+        seeds = [b"appeal", trader_stats.key().as_ref()],
+        bump = appeal.bump,
+        has_one = trader,
+        has_one = trader_stats,
+        has_one = trader_token_account
+    )]
+    pub appeal: Account<'info, Appeal>,
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = arbiter, has_one = insurance_fund)]
+    pub config: Account<'info, Config>,
+    pub arbiter: Signer<'info>,
+    /// CHECK: Appellant; receives the appeal account's rent.
+    #[account(mut)]
+    pub trader: UncheckedAccount<'info>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub insurance_fund: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"appeal_bond", appeal.key().as_ref()],
+        bump,
+        token::token_program = token_program
+    )]
+    pub bond_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(address = bond_vault.mint, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing bond vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct ClaimReferralCode<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + ReferralCode::INIT_SPACE,
+        seeds = [b"referral_code", anchor_lang::solana_program::hash::hash(code.as_bytes()).as_ref()],
+        bump
+    )]
+    pub referral_code: Account<'info, ReferralCode>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, token::authority = trader)]
+    pub trader_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryNotProvided)]
+    pub treasury: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct ApplyReferralCode<'info> {
+    #[account(
+        seeds = [b"referral_code", anchor_lang::solana_program::hash::hash(code.as_bytes()).as_ref()],
+        bump = referral_code.bump
+    )]
+    pub referral_code: Account<'info, ReferralCode>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferral<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + Referral::INIT_SPACE,
+        seeds = [b"referral", trader.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitAffiliate<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + Affiliate::INIT_SPACE,
+        seeds = [b"affiliate", trader.key().as_ref()],
+        bump
+    )]
+    pub affiliate: Account<'info, Affiliate>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecalculateAffiliateTier<'info> {
+    #[account(
+        mut,
+        seeds = [b"affiliate", referrer.key().as_ref()],
+        bump = affiliate.bump,
+        has_one = referrer
+    )]
+    pub affiliate: Account<'info, Affiliate>,
+    pub referrer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyMarketMaker<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReviewMarketMaker<'info> {
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayoutPreference<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemPoints<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleSwapPayout<'info> {
+    #[account(mut)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut, token::authority = admin)]
+    pub keeper_usdc_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = keeper_usdc_account.mint,
+        token::authority = trader_stats.trader
+    )]
+    pub trader_usdc_account: Account<'info, TokenAccount>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StakeTokens<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump, constraint = !trader_stats.banned @ ErrorCode::TraderBanned)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + StakePosition::INIT_SPACE,
+        seeds = [b"stake_position", trader.key().as_ref(), &trader_stats.stake_positions.to_le_bytes()],
+        bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"staking_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the staking vault; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trader: Pubkey)]
+pub struct StakeFor<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.as_ref()], bump = trader_stats.bump, constraint = !trader_stats.banned @ ErrorCode::TraderBanned)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        init,
+        payer = funder,
+        space = 8 + StakePosition::INIT_SPACE,
+        seeds = [b"stake_position", trader.as_ref(), &trader_stats.stake_positions.to_le_bytes()],
+        bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = funder,
+        token::token_program = token_program
+    )]
+    pub funder_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"staking_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA owning the staking vault.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestSponsoredUnstake<'info> {
+    #[account(mut, seeds = [b"trader_stats", stake_position.trader.as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        mut,
+        seeds = [b"stake_position", stake_position.trader.as_ref(), &stake_position.position_id.to_le_bytes()],
+        bump = stake_position.bump,
+        has_one = funder @ ErrorCode::NotPositionFunder
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub funder: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSponsoredStake<'info> {
+    #[account(seeds = [b"trader_stats", stake_position.trader.as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        mut,
+        seeds = [b"stake_position", stake_position.trader.as_ref(), &stake_position.position_id.to_le_bytes()],
+        bump = stake_position.bump,
+        has_one = funder @ ErrorCode::NotPositionFunder
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = funder,
+        token::token_program = token_program
+    )]
+    pub funder_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"staking_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA signing staking vault withdrawals.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// Receives the position's rent once it is emptied.
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetBadgeMint<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(owner = spl_token_2022::ID @ ErrorCode::InvalidBadgeMint)]
+    pub badge_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA expected as the badge mint authority.
+    #[account(seeds = [BADGE_AUTHORITY_SEED], bump)]
+    pub badge_authority: UncheckedAccount<'info>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(achievement: Achievement)]
+pub struct MintAchievement<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        mut,
+        address = config.badge_mints[achievement as usize] @ ErrorCode::InvalidBadgeMint,
+        mint::token_program = token_program
+    )]
+    pub badge_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = badge_mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA signing badge mints.
+    #[account(seeds = [BADGE_AUTHORITY_SEED], bump)]
+    pub badge_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTraderStats<'info> {
+    #[account(
+        mut,
+        close = trader,
+        seeds = [b"trader_stats", trader.key().as_ref()],
+        bump = trader_stats.bump
+    )]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStakePosition<'info> {
+    #[account(
+        mut,
+        close = trader,
+        seeds = [b"stake_position", trader.key().as_ref(), &stake_position.position_id.to_le_bytes()],
+        bump = stake_position.bump,
+        constraint = stake_position.funder == trader.key() @ ErrorCode::NotPositionFunder
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    /// Position to unstake from; omitted to unstake loose stake.
+    #[account(
+        mut,
+        seeds = [b"stake_position", trader.key().as_ref(), &stake_position.position_id.to_le_bytes()],
+        bump = stake_position.bump,
+        constraint = stake_position.funder == trader.key() @ ErrorCode::NotPositionFunder
+    )]
+    pub stake_position: Option<Account<'info, StakePosition>>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeTokens<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"staking_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staking_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA signing staking vault withdrawals.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// Receives a withheld early-unstake penalty; see `unstake_tokens`.
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub penalty_destination: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CalculateFeeDiscount<'info> {
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotLeaderboard<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_operator(&admin.key()) || Role::grants(role.as_deref(), &admin.key(), ROLE_OPERATOR) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    /// The signer's role grant, if it acts as an operator through one.
+    pub role: Option<Account<'info, Role>>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + LeaderboardSnapshot::INIT_SPACE,
+        seeds = [
+            b"snapshot",
+            leaderboard.key().as_ref(),
+            &config.snapshot_slot(leaderboard.load()?.history_count).to_le_bytes()
+        ],
+        bump
+    )]
+    pub snapshot: Account<'info, LeaderboardSnapshot>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PruneSnapshots<'info> {
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLeaderboardSnapshot<'info> {
+    #[account(mut, close = admin)]
+    pub snapshot: Account<'info, LeaderboardSnapshot>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FlagRanking<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(init, payer = flagger, space = 8 + RankingFlag::INIT_SPACE)]
+    pub ranking_flag: Account<'info, RankingFlag>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = flagger,
+        token::token_program = token_program
+    )]
+    pub flagger_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// Pins the bond to a reward mint, whose vault receives forfeited bonds.
+    #[account(
+        seeds = [b"reward_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        init,
+        payer = flagger,
+        seeds = [b"flag_bond", ranking_flag.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub bond_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA owning the bond vault; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub flagger: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveRankingFlag<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(mut, has_one = leaderboard, has_one = flagger_token_account)]
+    pub ranking_flag: Account<'info, RankingFlag>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub flagger_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mut, seeds = [b"flag_bond", ranking_flag.key().as_ref()], bump, token::token_program = token_program)]
+    pub bond_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(address = bond_vault.mint, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing bond vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"reward_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct OpenScoreDispute<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(init, payer = challenger, space = 8 + ScoreDispute::INIT_SPACE)]
+    pub score_dispute: Account<'info, ScoreDispute>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = challenger,
+        token::token_program = token_program
+    )]
+    pub challenger_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// Pins the bond to a reward mint, whose vault receives forfeited bonds.
+    #[account(
+        seeds = [b"reward_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        init,
+        payer = challenger,
+        seeds = [b"dispute_bond", score_dispute.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub bond_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA owning the bond vault; only used as a constraint target.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveScoreDispute<'info> {
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(mut, has_one = leaderboard, has_one = challenger_token_account)]
+    pub score_dispute: Account<'info, ScoreDispute>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub challenger_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mut, seeds = [b"dispute_bond", score_dispute.key().as_ref()], bump, token::token_program = token_program)]
+    pub bond_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(address = bond_vault.mint, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing bond and reward vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"reward_vault", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct GetLeaderboardPage<'info> {
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+}
 
-    // Check for emergency pause.
-    if leaderboard.emergency_pause {
-        return Err(ErrorCode::EmergencyPaused.into());
-    }
+#[derive(Accounts)]
+pub struct GetTraderRank<'info> {
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+}
 
-    // Determine the halving factor on a monthly basis (halving every 6 months).
-    let current_time = Clock::get()?.unix_timestamp;
-    let current_epoch = current_time / (30 * 24 * 60 * 60);
-    let halving_periods = current_epoch / 6;
-    let halving_factor = 2_u64.pow(halving_periods as u32);
-    let adjusted_reward = reward_amount.checked_div(halving_factor).unwrap_or(1);
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
 
-    // Compute total score among the top N traders.
-    let top_n_usize = top_n as usize;
-    let num_traders = leaderboard.traders.len().min(top_n_usize);
-    let mut total_score: u64 = 0;
-    for score in leaderboard.ranking_scores.iter().take(num_traders) {
-        total_score = total_score.checked_add(*score).ok_or(ErrorCode::Overflow)?;
-    }
-    if total_score == 0 {
-        return Err(ErrorCode::NoValidScores.into());
-    }
+#[derive(Accounts)]
+pub struct PurchaseSeasonPass<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + SeasonPass::INIT_SPACE,
+        seeds = [b"season_pass", trader.key().as_ref(), &config.current_season.to_le_bytes()],
+        bump
+    )]
+    pub season_pass: Account<'info, SeasonPass>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"reward_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryNotProvided)]
+    pub treasury: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
 
-    // Loop over the top traders and distribute rewards scaled by their score.
-    for (i, trader) in leaderboard.traders.iter().take(num_traders).enumerate() {
-        let score = leaderboard.ranking_scores.get(i).unwrap();
-        let trader_reward = ( (*score as u128)
-            .checked_mul(adjusted_reward as u128)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(total_score as u128)
-            .ok_or(ErrorCode::Overflow)? ) as u64;
+#[derive(Accounts)]
+pub struct FollowLeader<'info> {
+    #[account(
+        init,
+        payer = follower,
+        space = 8 + FollowLink::INIT_SPACE,
+        seeds = [b"follow", follower.key().as_ref()],
+        bump
+    )]
+    pub follow_link: Account<'info, FollowLink>,
+    #[account(mut)]
+    pub follower: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        let trader_token_account = ctx
-            .remaining_accounts
-            .iter()
-            .find(|acc| acc.key == trader)
-            .ok_or(ErrorCode::TraderTokenAccountNotFound)?;
+#[derive(Accounts)]
+pub struct UnfollowLeader<'info> {
+    #[account(
+        mut,
+        close = follower,
+        seeds = [b"follow", follower.key().as_ref()],
+        bump = follow_link.bump,
+        has_one = follower
+    )]
+    pub follow_link: Account<'info, FollowLink>,
+    #[account(mut)]
+    pub follower: Signer<'info>,
+}
 
-        // Create transfer accounts
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.reward_vault.to_account_info(),
-            to: trader_token_account.to_account_info(),
-            authority: ctx.accounts.reward_authority.to_account_info(),
-        };
+#[derive(Accounts)]
+#[instruction(leader: Pubkey, epoch: i64)]
+pub struct InitLeaderRevenue<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + LeaderRevenue::INIT_SPACE,
+        seeds = [b"leader_revenue", leader.as_ref(), &epoch.to_le_bytes()],
+        bump
+    )]
+    pub leader_revenue: Account<'info, LeaderRevenue>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
-            trader_reward,
-        )?;
-    }
-    Ok(())
+#[derive(Accounts)]
+pub struct ClaimLeaderRevenue<'info> {
+    #[account(
+        mut,
+        seeds = [b"leader_revenue", leader.key().as_ref(), &leader_revenue.epoch.to_le_bytes()],
+        bump = leader_revenue.bump,
+        has_one = leader
+    )]
+    pub leader_revenue: Account<'info, LeaderRevenue>,
+    #[account(mut, seeds = [b"fee_vault", fee_vault.mint.as_ref()], bump)]
+    pub fee_vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = fee_vault.mint, token::authority = leader)]
+    pub leader_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA signing fee vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub leader: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
-    /// Allows traders to stake $T2E tokens.
-    ///
-    /// Tokens are transferred to a staking vault and the staked amount is updated.
-    pub fn stake_tokens(ctx: Context<StakeTokens>, amount: u64) -> Result<()> {
-        let trader_stats = &mut ctx.accounts.trader_stats;
-        let token_account = &mut ctx.accounts.trader_token_account;
 
-        let cpi_accounts = Transfer {
-            from: token_account.to_account_info(),
-            to: ctx.accounts.staking_vault.to_account_info(),
-            authority: ctx.accounts.trader.to_account_info(),
-        };
+#[derive(Accounts)]
+#[instruction(display_name: [u8; 32])]
+pub struct SetTraderProfile<'info> {
+    #[account(
+        init_if_needed,
+        payer = trader,
+        space = 8 + TraderProfile::INIT_SPACE,
+        seeds = [b"trader_profile", trader.key().as_ref()],
+        bump
+    )]
+    pub trader_profile: Account<'info, TraderProfile>,
+    #[account(
+        init_if_needed,
+        payer = trader,
+        space = 8 + DisplayName::INIT_SPACE,
+        seeds = [b"display_name", display_name.as_ref()],
+        bump
+    )]
+    pub display_name_claim: Account<'info, DisplayName>,
+    /// The DisplayName of the name being replaced, when renaming.
+    #[account(
+        mut,
+        seeds = [b"display_name", trader_profile.display_name.as_ref()],
+        bump = previous_display_name.bump,
+        constraint = previous_display_name.owner == trader.key() @ ErrorCode::DisplayNameTaken
+    )]
+    pub previous_display_name: Option<Account<'info, DisplayName>>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
-            amount,
-        )?;
+#[derive(Accounts)]
+pub struct EndorseTrader<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + Endorsement::INIT_SPACE,
+        seeds = [b"endorsement", trader.key().as_ref(), endorsee_stats.trader.as_ref()],
+        bump
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+    #[account(mut, seeds = [b"trader_stats", trader.key().as_ref()], bump = endorser_stats.bump)]
+    pub endorser_stats: Account<'info, TraderStats>,
+    #[account(mut, constraint = endorsee_stats.trader != trader.key() @ ErrorCode::CannotEndorseSelf)]
+    pub endorsee_stats: Account<'info, TraderStats>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        trader_stats.staked_amount = trader_stats
-            .staked_amount
-            .checked_add(amount)
-            .ok_or(ErrorCode::Overflow)?;
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct QueueEmergencyWithdraw<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EmergencyWithdrawal::INIT_SPACE,
+        seeds = [b"emergency_withdrawal", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub emergency_withdrawal: Account<'info, EmergencyWithdrawal>,
+    #[account(seeds = [b"reward_vault", reward_vault.mint.as_ref()], bump)]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(token::mint = reward_vault.mint)]
+    pub destination: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-    /// Calculates a fee discount based on the staked amount.
-    ///
-    /// For example, discount scales from 0% to 50% for staking between 0 and 10,000 $T2E.
-    pub fn calculate_fee_discount(ctx: Context<CalculateFeeDiscount>) -> Result<()> {
-        let trader_stats = &mut ctx.accounts.trader_stats;
-        trader_stats.fee_discount = std::cmp::min((trader_stats.staked_amount / 200) as u8, 50);
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct ExecuteEmergencyWithdraw<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"emergency_withdrawal", reward_vault.key().as_ref()],
+        bump = emergency_withdrawal.bump,
+        has_one = reward_vault,
+        has_one = destination
+    )]
+    pub emergency_withdrawal: Account<'info, EmergencyWithdrawal>,
+    #[account(
+        mut,
+        seeds = [b"reward_vault", reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub destination: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: PDA signing reward vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
-    /// Takes a snapshot of the current leaderboard ranking.
-    ///
-    /// Useful for creating daily, weekly, or monthly leaderboard history.
-    pub fn snapshot_leaderboard(ctx: Context<SnapshotLeaderboard>) -> Result<()> {
-        let leaderboard_history = &mut ctx.accounts.leaderboard_history;
-        let leaderboard = &ctx.accounts.leaderboard;
-        leaderboard_history.past_rankings.push((Clock::get()?.unix_timestamp, leaderboard.traders.clone()));
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct CancelEmergencyWithdraw<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"emergency_withdrawal", emergency_withdrawal.reward_vault.as_ref()],
+        bump = emergency_withdrawal.bump
+    )]
+    pub emergency_withdrawal: Account<'info, EmergencyWithdrawal>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
 
-    /// Allows an admin to pause or unpause reward distribution in emergencies.
-    pub fn admin_pause_rewards(ctx: Context<AdminPauseRewards>, paused: bool) -> Result<()> {
-        let leaderboard = &mut ctx.accounts.leaderboard;
-        leaderboard.emergency_pause = paused;
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PendingConfigChange::INIT_SPACE,
+        seeds = [b"config_change".as_ref(), &config.config_change_count.to_le_bytes()],
+        bump
+    )]
+    pub config_change: Account<'info, PendingConfigChange>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-/// Input structure for trader stats used during leaderboard updates.
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct TraderStatsInput {
-    pub trader: Pubkey,
-    pub total_volume: u64,
-    pub average_execution_time: u64,
-    pub pnl: i64,
-    pub staked_amount: u64, // Added for leaderboard boost
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"config_change".as_ref(), &config_change.id.to_le_bytes()],
+        bump = config_change.bump
+    )]
+    pub config_change: Account<'info, PendingConfigChange>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
 }
 
-/// Helper struct for ranking a trader.
-pub struct RankedTrader {
-    pub trader: Pubkey,
-    pub score: u64,
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"config_change".as_ref(), &config_change.id.to_le_bytes()],
+        bump = config_change.bump
+    )]
+    pub config_change: Account<'info, PendingConfigChange>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
 }
 
-#[account]
-pub struct TraderStats {
-    pub trader: Pubkey,
-    pub total_volume: u64,
-    pub average_execution_time: u64,
-    pub trade_count: u64,
-    pub pnl: i64,
-    pub staked_amount: u64,
-    pub fee_discount: u8, // Percentage discount (0-50)
-    pub last_trade: i64,
+#[derive(Accounts)]
+#[instruction(grantee: Pubkey)]
+pub struct SetRoles<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + Role::INIT_SPACE,
+        seeds = [b"role", grantee.as_ref()],
+        bump
+    )]
+    pub role: Account<'info, Role>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct Leaderboard {
-    pub traders: Vec<Pubkey>,
-    pub ranking_scores: Vec<u64>, // Parallel array holding ranking scores.
-    pub last_update: i64,
-    pub emergency_pause: bool,
+#[derive(Accounts)]
+pub struct RevokeRoles<'info> {
+    #[account(mut, close = admin, seeds = [b"role", role.grantee.as_ref()], bump = role.bump)]
+    pub role: Account<'info, Role>,
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
 }
 
-#[account]
-pub struct LeaderboardHistory {
-    // Stores snapshots of leaderboard rankings: (timestamp, list of trader Pubkeys)
-    pub past_rankings: Vec<(i64, Vec<Pubkey>)>,
+#[derive(Accounts)]
+pub struct InitAdminSet<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AdminSet::INIT_SPACE,
+        seeds = [b"admin_set"],
+        bump
+    )]
+    pub admin_set: Account<'info, AdminSet>,
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(init, payer = user, space = 8 + 8 + (4 + 32 * 1000) + (4 + 8 * 1000) + 1)]
-    pub leaderboard: Account<'info, Leaderboard>,
+pub struct ProposeAdminAction<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingAdminAction::INIT_SPACE,
+        seeds = [b"admin_action".as_ref(), &admin_set.action_count.to_le_bytes()],
+        bump
+    )]
+    pub admin_action: Account<'info, PendingAdminAction>,
+    #[account(mut, seeds = [b"admin_set"], bump = admin_set.bump)]
+    pub admin_set: Account<'info, AdminSet>,
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RecordTrade<'info> {
-    #[account(mut, has_one = trader)]
-    pub trader_stats: Account<'info, TraderStats>,
-    pub trader: Signer<'info>,
+pub struct ApproveAdminAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin_action".as_ref(), &admin_action.id.to_le_bytes()],
+        bump = admin_action.bump
+    )]
+    pub admin_action: Account<'info, PendingAdminAction>,
+    #[account(seeds = [b"admin_set"], bump = admin_set.bump)]
+    pub admin_set: Account<'info, AdminSet>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateLeaderboard<'info> {
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::TreasuryNotProvided,
+        seeds = [b"treasury", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub destination: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing treasury withdrawals.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(address = config.admin_set @ ErrorCode::AdminSetNotProvided)]
+    pub admin_set: Option<Account<'info, AdminSet>>,
+    /// Approved action; required while an admin set exists.
     #[account(mut)]
-    pub leaderboard: Account<'info, Leaderboard>,
+    pub admin_action: Option<Account<'info, PendingAdminAction>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin_set != Pubkey::default() || config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct DistributeRewards<'info> {
-    #[account(mut)]
-    pub leaderboard: Account<'info, Leaderboard>,
+pub struct ExportRankings<'info> {
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+}
+
+#[derive(Accounts)]
+#[instruction(competition_id: u64)]
+pub struct CreateCompetition<'info> {
+    #[account(
+        init,
+        payer = sponsor,
+        space = 8 + Competition::INIT_SPACE,
+        seeds = [b"competition", sponsor.key().as_ref(), &competition_id.to_le_bytes()],
+        bump
+    )]
+    pub competition: Account<'info, Competition>,
+    #[account(
+        init,
+        payer = sponsor,
+        token::mint = prize_mint,
+        token::authority = competition,
+        seeds = [b"competition_vault", competition.key().as_ref()],
+        bump
+    )]
+    pub prize_vault: Account<'info, TokenAccount>,
+    pub prize_mint: Account<'info, token::Mint>,
+    #[account(mut, token::mint = prize_mint, token::authority = sponsor)]
+    pub sponsor_token_account: Account<'info, TokenAccount>,
     #[account(mut)]
-    pub reward_vault: Account<'info, TokenAccount>,
-    /// CHECK: Authority for reward vault transfers.
-    pub reward_authority: Signer<'info>,
+    pub sponsor: Signer<'info>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct StakeTokens<'info> {
-    #[account(mut, has_one = trader)]
-    pub trader_stats: Account<'info, TraderStats>,
+pub struct CancelCompetition<'info> {
+    #[account(
+        mut,
+        seeds = [b"competition", sponsor.key().as_ref(), &competition.competition_id.to_le_bytes()],
+        bump = competition.bump,
+        has_one = sponsor,
+        has_one = prize_vault
+    )]
+    pub competition: Account<'info, Competition>,
     #[account(mut)]
-    pub trader_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub staking_vault: Account<'info, TokenAccount>,
-    pub trader: Signer<'info>,
+    pub prize_vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = prize_vault.mint, token::authority = sponsor)]
+    pub sponsor_token_account: Account<'info, TokenAccount>,
+    pub sponsor: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct CalculateFeeDiscount<'info> {
-    #[account(mut, has_one = trader)]
-    pub trader_stats: Account<'info, TraderStats>,
-    pub trader: Signer<'info>,
+pub struct FinalizeCompetition<'info> {
+    #[account(
+        mut,
+        seeds = [b"competition", competition.sponsor.as_ref(), &competition.competition_id.to_le_bytes()],
+        bump = competition.bump,
+        has_one = prize_vault
+    )]
+    pub competition: Account<'info, Competition>,
+    #[account(mut)]
+    pub prize_vault: Account<'info, TokenAccount>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct SnapshotLeaderboard<'info> {
+pub struct AdminPauseRewards<'info> {
     #[account(mut)]
-    pub leaderboard: Account<'info, Leaderboard>,
-    #[account(init_if_needed, payer = admin, space = 8 + (4 + 8 + (4 + 32 * 1000)))]
-    pub leaderboard_history: Account<'info, LeaderboardHistory>,
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(address = config.admin_set @ ErrorCode::AdminSetNotProvided)]
+    pub admin_set: Option<Account<'info, AdminSet>>,
+    /// Approved action; required while an admin set exists.
     #[account(mut)]
+    pub admin_action: Option<Account<'info, PendingAdminAction>>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.may_submit_admin_action(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
     pub admin: Signer<'info>,
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AdminPauseRewards<'info> {
+#[instruction(quest_id: u64)]
+pub struct CreateQuest<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Quest::INIT_SPACE,
+        seeds = [b"quest".as_ref(), &quest_id.to_le_bytes()],
+        bump
+    )]
+    pub quest: Account<'info, Quest>,
+    #[account(seeds = [b"reward_vault", reward_vault.mint.as_ref()], bump)]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
     #[account(mut)]
-    pub leaderboard: Account<'info, Leaderboard>,
     pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateQuest<'info> {
+    #[account(mut, seeds = [b"quest".as_ref(), &quest.quest_id.to_le_bytes()], bump = quest.bump)]
+    pub quest: Account<'info, Quest>,
+    #[account(seeds = [b"config"], bump = config.bump, constraint = config.is_admin(&admin.key()) @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitQuestProgress<'info> {
+    #[account(
+        init,
+        payer = trader,
+        space = 8 + QuestProgress::INIT_SPACE,
+        seeds = [b"quest_progress", quest.key().as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub quest_progress: Account<'info, QuestProgress>,
+    #[account(seeds = [b"quest".as_ref(), &quest.quest_id.to_le_bytes()], bump = quest.bump)]
+    pub quest: Account<'info, Quest>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimQuestReward<'info> {
+    #[account(mut, seeds = [b"quest".as_ref(), &quest.quest_id.to_le_bytes()], bump = quest.bump)]
+    pub quest: Account<'info, Quest>,
+    #[account(
+        mut,
+        seeds = [b"quest_progress", quest.key().as_ref(), trader.key().as_ref()],
+        bump = quest_progress.bump
+    )]
+    pub quest_progress: Account<'info, QuestProgress>,
+    #[account(seeds = [b"trader_stats", trader.key().as_ref()], bump = trader_stats.bump, constraint = !trader_stats.banned @ ErrorCode::TraderBanned)]
+    pub trader_stats: Account<'info, TraderStats>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"reward_vault", quest.reward_mint.as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(address = quest.reward_mint, mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signing reward vault transfers.
+    #[account(seeds = [VAULT_AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = trader,
+        token::token_program = token_program
+    )]
+    pub trader_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    pub trader: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Emitted once per distribution with the totals of the finalized epoch.
+#[event]
+pub struct EpochSummary {
+    pub leaderboard: Pubkey,
+    pub epoch: i64, // Emission epoch (30-day periods) of the distribution.
+    pub ranking_update: i64, // `last_update` of the ranking that was paid.
+    pub traders_ranked: u32,
+    pub traders_paid: u32,
+    pub total_score: u64, // Sum of the paid traders' scores.
+    pub total_distributed: u64, // Paid to traders, including any remainder sent to #1.
+    pub burned: u64, // Burned from the treasury so far in `epoch`.
+    pub treasury_cut: u64,
+    pub top_traders: [Pubkey; 3],
+    pub top_scores: [u64; 3],
+    pub timestamp: i64,
+}
+
+/// A trader banned or unbanned by an admin.
+#[event]
+pub struct TraderBanUpdated {
+    pub trader: Pubkey,
+    pub banned: bool,
+    pub timestamp: i64,
+}
+
+/// A trader left out of a ranking update for staking less than
+/// `Config::min_stake_for_ranking`.
+#[event]
+pub struct TraderExcludedFromRanking {
+    pub leaderboard: Pubkey,
+    pub trader: Pubkey, // Identity; linked wallets are aggregated.
+    pub staked_amount: u64,
+    pub min_stake: u64,
+    pub timestamp: i64,
+}
+
+/// Score components of one trader in a ranking update.
+#[event]
+pub struct ScoreBreakdownRecorded {
+    pub leaderboard: Pubkey,
+    pub trader: Pubkey, // Identity; linked wallets are aggregated.
+    pub volume_score: u64,
+    pub pnl_score: u64,
+    pub staking_bonus: u64,
+    pub win_rate_bonus: u64,
+    pub streak_bonus: u64,
+    pub volume_weight_bps: u16, // Multiplier already applied to `volume_score`.
+    pub pnl_weight_bps: u16, // Multiplier already applied to `pnl_score`.
+    pub score: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppealOpened {
+    pub trader: Pubkey,
+    pub bond_amount: u64,
+    pub suspended_until: i64,
+    pub reason_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppealResolved {
+    pub trader: Pubkey,
+    pub arbiter: Pubkey,
+    pub lifted: bool, // True if the penalty was lifted and the bond returned.
+    pub bond_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TraderSlashed {
+    pub trader: Pubkey,
+    pub amount: u64,
+    pub slash_bps: u16,
+    pub season: u32,
+    pub reason_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingClawedBack {
+    pub trader: Pubkey,
+    pub leaderboard: Pubkey,
+    pub amount: u64, // Unvested remainder returned.
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted for every trade credited to a trader's stats, whichever
+/// instruction recorded it.
+#[event]
+pub struct TradeRecorded {
+    pub trader: Pubkey,
+    pub venue: VenueType,
+    pub volume: u64, // As credited; zero for trades over the per-epoch cap.
+    pub execution_time: u64,
+    pub pnl: i64, // As credited.
+    pub total_volume: u64,
+    pub total_pnl: i64,
+    pub trade_count: u64,
+    pub winning_trades: u64,
+    pub losing_trades: u64,
+    pub win_rate_bps: u16, // Winning share of trades with non-zero P&L.
+    pub timestamp: i64,
+}
+
+/// Emitted whenever the ranking of a leaderboard changes. Boards longer than
+/// `MAX_PAGE_SIZE` are read in full with `export_rankings`.
+#[event]
+pub struct LeaderboardUpdated {
+    pub leaderboard: Pubkey,
+    pub ranking_update: i64, // `last_update` of the new ranking.
+    pub traders_ranked: u32,
+    pub board_hash: [u8; 32],
+    pub top_entries: Vec<LeaderboardEntry>,
+    pub timestamp: i64,
+}
+
+/// Emitted per trader paid by `distribute_rewards` or credited by
+/// `finalize_epoch`.
+#[event]
+pub struct RewardsDistributed {
+    pub leaderboard: Pubkey,
+    pub trader: Pubkey,
+    pub rank: u32,
+    pub amount: u64,
+    pub payout: PayoutPreference,
+    pub deferred: bool, // Credited to a RewardClaim instead of paid out.
+    pub epoch: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensStaked {
+    pub trader: Pubkey,
+    pub funder: Pubkey, // Differs from `trader` for sponsored stakes.
+    pub position_id: u32,
+    pub amount: u64, // Received by the vault, net of transfer fees.
+    pub lock: LockTier,
+    pub staked_amount: u64,
+    pub locked_until: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensUnstaked {
+    pub trader: Pubkey,
+    pub amount: u64,
+    pub penalty: u64, // Early-unstake penalty routed to the treasury or reward vault.
+    pub staked_amount: u64, // Still staked after the withdrawal.
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SnapshotTaken {
+    pub leaderboard: Pubkey,
+    pub snapshot: Pubkey,
+    pub index: u64,
+    pub ranking_update: i64,
+    pub traders_ranked: u32,
+    pub board_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseToggled {
+    pub authority: Pubkey,
+    pub leaderboard: Option<Pubkey>, // None for the config-wide pause flags.
+    pub pause_flags: u8, // New flags; a leaderboard pause maps to `PAUSE_REWARDS`.
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TradingFeeCollected {
+    pub trader: Pubkey,
+    pub partner: Pubkey, // Program id of the charging venue.
+    pub gross_fee: u64,
+    pub discount: u64, // Waived by the trader's fee discount.
+    pub credits_used: u64,
+    pub net_fee: u64, // Transferred to the fee vault.
+    pub leader_share: u64, // Of `net_fee`, accrued to the copied leader.
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub support: bool,
+    pub weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub action: ProposalAction,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub config_change: Pubkey,
+    pub id: u64,
+    pub action: ProposalAction,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct AdminActionApproved {
+    pub admin_action: Pubkey,
+    pub action: AdminAction,
+    pub authority: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct RewardVaultFunded {
+    pub reward_vault: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryBurned {
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub remaining: u64, // Treasury balance after the burn.
+    pub total_burned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakingRewardsClaimed {
+    pub trader: Pubkey,
+    pub amount: u64,
+    pub compounded: bool, // Moved into the staking vault instead of paid out.
+    pub staked_amount: u64,
+    pub timestamp: i64,
+}
+
+/// One chunk of a full ranking export, emitted via self-CPI.
+#[event]
+pub struct RankingChunk {
+    pub leaderboard: Pubkey,
+    pub ranking_update: i64, // `last_update` of the exported ranking.
+    pub total_ranked: u32,
+    pub entries: Vec<LeaderboardEntry>,
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Arithmetic overflow occurred.")]
     Overflow,
-    #[msg("Leaderboard update attempted too soon; wait at least 10 minutes.")]
+    #[msg("Leaderboard update attempted too soon.")]
     UpdateTooSoon,
     #[msg("Trader token account not found among provided accounts.")]
     TraderTokenAccountNotFound,
@@ -354,4 +11228,318 @@ pub enum ErrorCode {
     EmergencyPaused,
     #[msg("No valid ranking scores found for reward distribution.")]
     NoValidScores,
+    #[msg("Distribution delay must not be negative.")]
+    InvalidDistributionDelay,
+    #[msg("Fraud-proof window is still open; distribution not yet allowed.")]
+    DistributionWindowOpen,
+    #[msg("Fraud-proof window has closed; the ranking can no longer be flagged.")]
+    DistributionWindowClosed,
+    #[msg("The current ranking is flagged and awaiting admin review.")]
+    RankingFlagged,
+    #[msg("Ranking flag evidence must be 1-8 TraderStats accounts.")]
+    InvalidFlagEvidence,
+    #[msg("Ranking flag has already been resolved.")]
+    FlagAlreadyResolved,
+    #[msg("Dispute bond is below the configured minimum.")]
+    DisputeBondTooLow,
+    #[msg("Rank index is out of bounds for the current leaderboard.")]
+    InvalidRankIndex,
+    #[msg("Referenced TraderStats do not belong to the disputed entry.")]
+    DisputeTraderMismatch,
+    #[msg("On-chain TraderStats match the posted score; nothing to dispute.")]
+    ScoreNotContradicted,
+    #[msg("Score dispute has already been resolved.")]
+    DisputeAlreadyResolved,
+    #[msg("Signer is not an admin or co-admin.")]
+    Unauthorized,
+    #[msg("Co-admin list is full.")]
+    TooManyCoAdmins,
+    #[msg("Key is already a co-admin.")]
+    CoAdminAlreadyExists,
+    #[msg("Key is not a co-admin.")]
+    CoAdminNotFound,
+    #[msg("TraderStats for a ranked trader not found among provided accounts.")]
+    TraderStatsNotFound,
+    #[msg("USDC swap payouts are not configured.")]
+    SwapPayoutUnavailable,
+    #[msg("Swap settlement exceeds the trader's pending swap payout.")]
+    SwapSettlementExceedsPending,
+    #[msg("Pair volume account does not match the trader and pair.")]
+    PairVolumeMismatch,
+    #[msg("Trade epoch length must be positive.")]
+    InvalidTradeEpochLength,
+    #[msg("Per-epoch trade limit reached for this trader.")]
+    TradeLimitExceeded,
+    #[msg("Unknown pause flag bits set.")]
+    InvalidPauseFlags,
+    #[msg("Timelock has not expired yet.")]
+    TimelockNotExpired,
+    #[msg("Configured treasury account was not provided.")]
+    TreasuryNotProvided,
+    #[msg("Reward vault debit does not equal the adjusted reward.")]
+    DistributionAccountingMismatch,
+    #[msg("Basis points must not exceed 10,000.")]
+    InvalidBps,
+    #[msg("Season passes are not on sale.")]
+    SeasonPassUnavailable,
+    #[msg("Season numbers start at 1.")]
+    InvalidSeason,
+    #[msg("Not enough loyalty points.")]
+    InsufficientLoyaltyPoints,
+    #[msg("This redemption option is not enabled.")]
+    RedemptionUnavailable,
+    #[msg("Redeemed loyalty boost would exceed the cap.")]
+    LoyaltyBoostCapExceeded,
+    #[msg("A trader cannot follow themselves.")]
+    CannotFollowSelf,
+    #[msg("Nothing to claim.")]
+    NothingToClaim,
+    #[msg("Profile URI exceeds the maximum length.")]
+    ProfileUriTooLong,
+    #[msg("Display name must be valid UTF-8.")]
+    InvalidDisplayName,
+    #[msg("Endorsement cooldown has not elapsed.")]
+    EndorsementTooSoon,
+    #[msg("Staked amount too low to endorse.")]
+    InsufficientStakeToEndorse,
+    #[msg("A trader cannot endorse themselves.")]
+    CannotEndorseSelf,
+    #[msg("No supported AMM swap debiting the source account was found.")]
+    AmmSwapNotFound,
+    #[msg("Account is not a perp venue user account owned by the trader.")]
+    InvalidVenueAccount,
+    #[msg("Funding delta does not match the venue's cumulative funding.")]
+    FundingMismatch,
+    #[msg("A wallet cannot be linked to itself.")]
+    CannotLinkSelf,
+    #[msg("Wallet is already linked to a trader identity.")]
+    WalletAlreadyLinked,
+    #[msg("Market-maker status does not allow this transition.")]
+    InvalidMarketMakerStatus,
+    #[msg("Automation intervals must not be negative.")]
+    InvalidAutomationInterval,
+    #[msg("Scheduled job is disabled or not yet due.")]
+    AutomationNotDue,
+    #[msg("Maximum ranking age must not be negative.")]
+    InvalidMaxRankingAge,
+    #[msg("Leaderboard ranking is too old to distribute rewards.")]
+    RankingStale,
+    #[msg("Ranking snapshot does not match the current leaderboard.")]
+    SnapshotMismatch,
+    #[msg("A distribution has already been bound to this snapshot.")]
+    SnapshotAlreadyPaid,
+    #[msg("Competition must start in the future and end after it starts.")]
+    InvalidCompetitionWindow,
+    #[msg("Prize amount must be greater than zero.")]
+    InvalidPrizeAmount,
+    #[msg("Competition has already been finalized or cancelled.")]
+    CompetitionClosed,
+    #[msg("Competition has already started and can no longer be cancelled.")]
+    CompetitionAlreadyStarted,
+    #[msg("Competition has not ended yet.")]
+    CompetitionNotEnded,
+    #[msg("Payouts must match the winner accounts and add up to the prize pool.")]
+    InvalidCompetitionPayouts,
+    #[msg("Suspension must end in the future and within the maximum period.")]
+    InvalidSuspension,
+    #[msg("Appeals are not enabled.")]
+    AppealsDisabled,
+    #[msg("Appeal bond is below the minimum.")]
+    AppealBondTooLow,
+    #[msg("Trader is not currently suspended.")]
+    NotSuspended,
+    #[msg("Inactivity horizon must not be negative.")]
+    InvalidInactivityHorizon,
+    #[msg("Pruning of inactive traders is disabled.")]
+    PruningDisabled,
+    #[msg("Referral codes are 3-16 characters of a-z, 0-9 and '-'.")]
+    InvalidReferralCode,
+    #[msg("Referrer has already been set.")]
+    ReferrerAlreadySet,
+    #[msg("A trader cannot refer themselves.")]
+    CannotReferSelf,
+    #[msg("Affiliate tier was recalculated too recently.")]
+    AffiliateRecalcTooSoon,
+    #[msg("Account is not a referee of this referrer.")]
+    NotAReferee,
+    #[msg("Partner integration is disabled.")]
+    PartnerInactive,
+    #[msg("Claim window must close after it opens.")]
+    InvalidClaimWindow,
+    #[msg("Rewards of this epoch cannot be claimed at this time.")]
+    ClaimWindowClosed,
+    #[msg("Leaderboard name must not be empty.")]
+    InvalidLeaderboardName,
+    #[msg("Leaderboard registry is full.")]
+    RegistryFull,
+    #[msg("Leaderboard or name is already registered.")]
+    LeaderboardAlreadyRegistered,
+    #[msg("Invalid oracle parameters.")]
+    InvalidOracleParams,
+    #[msg("Oracle account is missing, invalid, or for the wrong feed.")]
+    InvalidOracleAccount,
+    #[msg("Oracle price is too old.")]
+    OraclePriceStale,
+    #[msg("Oracle confidence interval is too wide.")]
+    OracleConfidenceTooWide,
+    #[msg("Unstake cooldown must not be negative.")]
+    InvalidUnstakeCooldown,
+    #[msg("Unstake amount must be positive and within the staked balance.")]
+    InvalidUnstakeAmount,
+    #[msg("No unstake request is pending.")]
+    NoPendingUnstake,
+    #[msg("Unstake cooldown has not elapsed yet.")]
+    UnstakeCooldownActive,
+    #[msg("Reward claim account not found or does not match the trader.")]
+    RewardClaimNotFound,
+    #[msg("Claim window has not closed yet.")]
+    ClaimWindowNotExpired,
+    #[msg("Invalid Merkle proof.")]
+    InvalidMerkleProof,
+    #[msg("Invalid protocol parameters.")]
+    InvalidProtocolParams,
+    #[msg("Invalid season index or timestamps.")]
+    InvalidSeasonParams,
+    #[msg("The season has ended.")]
+    SeasonEnded,
+    #[msg("A season is already running.")]
+    SeasonAlreadyActive,
+    #[msg("The season has not reached its end timestamp.")]
+    SeasonNotOver,
+    #[msg("Season or season stats account missing or does not match the active season.")]
+    SeasonStatsMismatch,
+    #[msg("Ranking pages must be submitted in order.")]
+    InvalidPageIndex,
+    #[msg("Staged ranking exceeds the leaderboard capacity.")]
+    LeaderboardCapacityExceeded,
+    #[msg("A trader was submitted in more than one ranking page.")]
+    DuplicateRankingEntry,
+    #[msg("Staged update account data is malformed.")]
+    InvalidPendingUpdate,
+    #[msg("Market stats account missing or does not match the market or trader.")]
+    MarketStatsMismatch,
+    #[msg("Scoring divisors must be nonzero and the score half-life non-negative.")]
+    InvalidScoringConfig,
+    #[msg("Vesting cliff and duration must be non-negative, with the cliff within the duration.")]
+    InvalidVestingParams,
+    #[msg("Vesting schedule account missing or does not match the trader.")]
+    VestingScheduleNotFound,
+    #[msg("Stake position account required for locked stake.")]
+    StakePositionNotFound,
+    #[msg("Staked tokens are locked until the stake position expires.")]
+    StakeLocked,
+    #[msg("Slash share must be between 1 and 10,000 basis points.")]
+    InvalidSlashParams,
+    #[msg("No trade oracle is configured.")]
+    AttestationsDisabled,
+    #[msg("Trade attestation signature missing, invalid or replayed.")]
+    InvalidAttestation,
+    #[msg("The trader is suspended.")]
+    TraderSuspended,
+    #[msg("Account still holds balances and cannot be closed.")]
+    AccountNotEmpty,
+    #[msg("Snapshot is within the retention window or belongs to another leaderboard.")]
+    SnapshotNotExpired,
+    #[msg("Snapshot retention is not configured.")]
+    SnapshotRetentionDisabled,
+    #[msg("Referral account not found or does not belong to the trader.")]
+    ReferralNotFound,
+    #[msg("Badge mint must be a non-transferable Token-2022 mint with zero decimals owned by the badge authority.")]
+    InvalidBadgeMint,
+    #[msg("Achievement has not been reached.")]
+    AchievementNotReached,
+    #[msg("Badge for this achievement was already minted.")]
+    BadgeAlreadyMinted,
+    #[msg("Fee discount tiers must have increasing thresholds, non-decreasing discounts of at most 100%, and fit the table.")]
+    InvalidFeeDiscountTiers,
+    #[msg("Leader revenue must be passed exactly when the trader has a follow link and match its leader and the current trade epoch.")]
+    LeaderRevenueMismatch,
+    #[msg("Treasury burn share rounds to zero tokens.")]
+    NothingToBurn,
+    #[msg("Emission schedule needs a future genesis, a positive epoch length and a nonzero halving interval.")]
+    InvalidEmissionSchedule,
+    #[msg("Emission schedule can no longer be changed after genesis.")]
+    EmissionScheduleLocked,
+    #[msg("The configured emission schedule account was not provided.")]
+    EmissionScheduleNotProvided,
+    #[msg("Voting period must be non-negative and not exceed the unstake cooldown.")]
+    InvalidGovernanceParams,
+    #[msg("Governance is disabled while the voting period is zero.")]
+    GovernanceDisabled,
+    #[msg("Effective stake is below the minimum required to open a proposal.")]
+    InsufficientProposalStake,
+    #[msg("Voting on this proposal has ended.")]
+    VotingClosed,
+    #[msg("Voting on this proposal is still open.")]
+    VotingNotEnded,
+    #[msg("Voter has no effective stake.")]
+    NoVotingPower,
+    #[msg("Proposal did not reach the quorum or a majority.")]
+    ProposalRejected,
+    #[msg("Proposal has already been executed.")]
+    ProposalAlreadyExecuted,
+    #[msg("Admin set needs unique authorities, at most MAX_ADMIN_SET_SIZE, and a threshold between 1 and their count.")]
+    InvalidAdminSet,
+    #[msg("The configured admin set account was not provided.")]
+    AdminSetNotProvided,
+    #[msg("Signer is not a member of the admin set.")]
+    NotAdminSetMember,
+    #[msg("Authority has already approved this action.")]
+    AdminActionAlreadyApproved,
+    #[msg("No admin action with enough approvals matches this instruction.")]
+    AdminActionNotApproved,
+    #[msg("Admin action has already been executed.")]
+    AdminActionExecuted,
+    #[msg("Roles must be a nonzero combination of ROLE_* flags.")]
+    InvalidRoles,
+    #[msg("Token account is not owned by the ranked trader or does not hold the reward mint.")]
+    InvalidTraderTokenAccount,
+    #[msg("Trade volume is below the configured minimum.")]
+    TradeBelowMinimumVolume,
+    #[msg("Rate window needs at most MAX_RATE_WINDOW_TRADES trades and a positive length.")]
+    InvalidRateWindow,
+    #[msg("The trader is banned.")]
+    TraderBanned,
+    #[msg("The trade must reference a registered market.")]
+    MarketNotRegistered,
+    #[msg("The display name is taken by another trader.")]
+    DisplayNameTaken,
+    #[msg("Renaming requires the DisplayName account of the previous name.")]
+    DisplayNameNotProvided,
+    #[msg("Leaderboard account data does not hold its entry slots.")]
+    InvalidLeaderboardAccount,
+    #[msg("Leaderboard growth must add 1-MAX_LEADERBOARD_GROWTH slots, up to MAX_LEADERBOARD_ENTRIES.")]
+    InvalidLeaderboardGrowth,
+    #[msg("Live ranking is not enabled on this leaderboard.")]
+    LiveRankingDisabled,
+    #[msg("Quest target, reward and period must be greater than zero.")]
+    InvalidQuest,
+    #[msg("QuestProgress does not belong to the quest and trader.")]
+    QuestProgressMismatch,
+    #[msg("The quest has not been completed in the current period.")]
+    QuestNotCompleted,
+    #[msg("The quest reward for this period has already been claimed.")]
+    QuestRewardClaimed,
+    #[msg("Early-unstake penalties go to the treasury or the reward vault of the mint, per the config.")]
+    InvalidPenaltyDestination,
+    #[msg("Only the funder of a stake position can withdraw it.")]
+    NotPositionFunder,
+    #[msg("This ranking has already been paid out.")]
+    RankingAlreadyPaid,
+    #[msg("Only a banned trader's vesting can be clawed back.")]
+    TraderNotBanned,
+    #[msg("Clawed-back vesting must go to the insurance fund or the reward vault.")]
+    InvalidClawbackDestination,
+    #[msg("The same trader stats account was passed more than once.")]
+    DuplicateTraderStats,
+    #[msg("The AMM swap receipt was not opened by begin_amm_swap earlier in this transaction.")]
+    AmmSwapReceiptMismatch,
+    #[msg("Only the AMM swap may touch the source token account between begin and record.")]
+    UnexpectedSourceAccountUse,
+    #[msg("Reward mints with a transfer hook are not supported by this instruction.")]
+    TransferHookUnsupported,
+    #[msg("Wallet has other wallets linked to it and cannot be linked as a secondary.")]
+    WalletIsPrimary,
+    #[msg("Every open stake position of the trader must be passed exactly once.")]
+    StakePositionsIncomplete,
 }
@@ -3,6 +3,20 @@ import * as anchor from "@coral-xyz/anchor";
 import BN from "bn.js";
 import assert from "assert";
 import * as web3 from "@solana/web3.js";
+import { createHash } from "crypto";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeTransferHookInstruction,
+  createMint,
+  getAccount,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  transfer,
+} from "@solana/spl-token";
 import type { T2eLeaderboard } from "../target/types/t2e_leaderboard";
 
 describe("Trade-to-Earn Leaderboard Token ($T2E)", () => {
@@ -12,19 +26,71 @@ describe("Trade-to-Earn Leaderboard Token ($T2E)", () => {
   const program = anchor.workspace.T2eLeaderboard as anchor.Program<T2eLeaderboard>;
   
   let leaderboardAccount = new web3.Keypair();
-  let traderAccount = new web3.Keypair();
-  let rewardVault = new web3.Keypair();
-  let stakingVault = new web3.Keypair();
   let traderTokenAccount = new web3.Keypair();
+  let t2eMint = new web3.Keypair();
+  const [stakingVault] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("staking_vault"), t2eMint.publicKey.toBuffer()],
+    program.programId
+  );
+  let rankingSnapshot: web3.PublicKey;
+  // Accounts of the Token-2022 reward claim paid out by `finalizeEpoch`.
+  let token2022Claim: Record<string, web3.PublicKey>;
+  const [rewardVault] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("reward_vault"), t2eMint.publicKey.toBuffer()],
+    program.programId
+  );
+  const [vaultAuthorityPda] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault_authority")],
+    program.programId
+  );
+  const [configPda] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  const [traderStatsPda] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("trader_stats"), program.provider.publicKey.toBuffer()],
+    program.programId
+  );
+
+  const stakePositionAddress = (trader: web3.PublicKey, positionId: number) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_position"), trader.toBuffer(), new BN(positionId).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    )[0];
+  const nextStakePosition = async () => {
+    const { stakePositions } = await program.account.traderStats.fetch(traderStatsPda);
+    return stakePositionAddress(program.provider.publicKey, stakePositions);
+  };
+
+  // A leaderboard header is followed by `capacity` slots in each of the
+  // trader (32-byte), score (8-byte) and tie-break (24-byte) arrays.
+  const leaderboardSpace = (capacity: number) =>
+    program.account.leaderboard.size + capacity * (32 + 8 + 24);
+  const fetchBoardEntries = async (board: web3.PublicKey) => {
+    const { len, capacity } = await program.account.leaderboard.fetch(board);
+    const { data } = await program.provider.connection.getAccountInfo(board);
+    const traders = program.account.leaderboard.size;
+    const scores = traders + 32 * capacity;
+    const tiebreaks = scores + 8 * capacity;
+    return Array.from({ length: len }, (_, i) => ({
+      trader: new web3.PublicKey(data.subarray(traders + 32 * i, traders + 32 * (i + 1))),
+      score: new BN(data.subarray(scores + 8 * i, scores + 8 * (i + 1)), "le"),
+      firstTrade: new BN(data.subarray(tiebreaks + 24 * i + 16, tiebreaks + 24 * (i + 1)), "le"),
+    }));
+  };
 
   it("Initializes the leaderboard", async () => {
     const txHash = await program.methods
-      .initialize()
+      .initialize(web3.PublicKey.default)
       .accounts({
         leaderboard: leaderboardAccount.publicKey,
-        user: program.provider.publicKey,
-        systemProgram: web3.SystemProgram.programId,
       })
+      .preInstructions([
+        await program.account.leaderboard.createInstruction(
+          leaderboardAccount,
+          leaderboardSpace(16)
+        ),
+      ])
       .signers([leaderboardAccount])
       .rpc();
 
@@ -34,138 +100,3973 @@ describe("Trade-to-Earn Leaderboard Token ($T2E)", () => {
     const leaderboard = await program.account.leaderboard.fetch(
       leaderboardAccount.publicKey
     );
-    assert(leaderboard.traders.length === 0);
+    assert(leaderboard.len === 0);
+    assert(leaderboard.capacity === 16);
     assert(leaderboard.lastUpdate.gt(new BN(0))); // FIXED: BN comparison
   });
 
-  it("Records a trade", async () => {
-    const volume = new BN(5000);
-    const executionTime = new BN(250);
-    const pnl = new BN(200);
+  it("Initializes the config", async () => {
+    const txHash = await program.methods
+      .initializeConfig(new BN(0), new BN(100), new BN(10))
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Config initialized: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.admin.toBase58() === program.provider.publicKey.toBase58());
+    assert(config.distributionDelay.eq(new BN(0)));
+    assert(config.minDisputeBond.eq(new BN(100)));
+  });
+
+  it("Adds and removes a co-admin", async () => {
+    const coAdmin = new web3.Keypair();
+
+    const addTx = await program.methods
+      .addCoAdmin(coAdmin.publicKey)
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Co-admin added: ${addTx}`);
+    await program.provider.connection.confirmTransaction(addTx);
+
+    let config = await program.account.config.fetch(configPda);
+    assert(config.coAdmins.some((key) => key.equals(coAdmin.publicKey)));
+
+    const removeTx = await program.methods
+      .removeCoAdmin(coAdmin.publicKey)
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Co-admin removed: ${removeTx}`);
+    await program.provider.connection.confirmTransaction(removeTx);
+
+    config = await program.account.config.fetch(configPda);
+    assert(config.coAdmins.length === 0);
+  });
 
+  it("Sets the maximum ranking age for payouts", async () => {
     const txHash = await program.methods
-      .recordTrade(volume, executionTime, pnl)
+      .setMaxRankingAge(new BN(7 * 24 * 60 * 60))
       .accounts({
-        traderStats: traderAccount.publicKey,
-        trader: program.provider.publicKey,
+        config: configPda,
+        admin: program.provider.publicKey,
       })
-      .signers([traderAccount])
       .rpc();
 
-    console.log(`✅ Trade recorded: ${txHash}`);
+    console.log(`✅ Max ranking age set: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
 
-    const traderStats = await program.account.traderStats.fetch(
-      traderAccount.publicKey
+    const config = await program.account.config.fetch(configPda);
+    assert(config.maxRankingAge.eq(new BN(7 * 24 * 60 * 60)));
+  });
+
+  it("Configures oracle staleness and confidence limits", async () => {
+    const txHash = await program.methods
+      .setOracleParams(new BN(60), 100)
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Oracle limits set: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.maxPriceAge.eq(new BN(60)));
+    assert(config.maxPriceConfidenceBps === 100);
+  });
+
+  it("Enables score breakdown events", async () => {
+    const txHash = await program.methods
+      .setScoreBreakdowns(true)
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Score breakdowns enabled: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.emitScoreBreakdowns === true);
+  });
+
+  it("Updates the protocol parameters", async () => {
+    const txHash = await program.methods
+      .setProtocolParams(new BN(10), new BN(600), new BN(180 * 86400), 1000)
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Protocol parameters updated: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.tradeCooldown.eq(new BN(10)));
+    assert(config.halvingPeriod.eq(new BN(180 * 86400)));
+    assert(config.maxLeaderboardSize === 1000);
+  });
+
+  it("Sets the scoring weights", async () => {
+    const scoring = {
+      volumeWeightBps: 10_000,
+      executionTimeWeightBps: 10_000,
+      pnlWeightBps: 10_000,
+      stakingWeightBps: 10_000,
+      volumeDivisor: new BN(1),
+      stakingDivisor: new BN(1000),
+      executionTimeMetric: { average: {} },
+      pnlMode: { absolute: {} },
+      winRateWeightBps: 0,
+      streakMode: { tradingDays: {} },
+      streakBonusBps: 0,
+      maxStreakDays: 0,
+    };
+
+    const txHash = await program.methods
+      .setScoringConfig(scoring)
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Scoring weights set: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.scoring.pnlWeightBps === 10_000);
+    assert(config.scoring.stakingDivisor.eq(new BN(1000)));
+    assert(config.scoring.executionTimeMetric.average !== undefined);
+    assert(config.scoring.pnlMode.absolute !== undefined);
+
+    try {
+      await program.methods
+        .setScoringConfig({ ...scoring, volumeDivisor: new BN(0) })
+        .accounts({
+          config: configPda,
+          admin: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("a zero divisor should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidScoringConfig");
+    }
+  });
+
+  it("Sets the score decay half-life", async () => {
+    const txHash = await program.methods
+      .setScoreHalfLife(new BN(30 * 24 * 60 * 60))
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+
+    console.log(`✅ Score half-life set: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    let config = await program.account.config.fetch(configPda);
+    assert(config.scoreHalfLife.eq(new BN(30 * 24 * 60 * 60)));
+
+    try {
+      await program.methods
+        .setScoreHalfLife(new BN(-1))
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+      assert.fail("a negative half-life should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidScoringConfig");
+    }
+
+    // Later tests rank synthetic stats without trade timestamps.
+    await program.methods
+      .setScoreHalfLife(new BN(0))
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    config = await program.account.config.fetch(configPda);
+    assert(config.scoreHalfLife.eq(new BN(0)));
+  });
+
+  it("Configures penalty appeals", async () => {
+    const arbiter = new web3.Keypair().publicKey;
+    const insuranceFund = new web3.Keypair().publicKey;
+
+    const txHash = await program.methods
+      .setAppealParams(arbiter, insuranceFund, new BN(50))
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Appeal params set: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.arbiter.equals(arbiter));
+    assert(config.insuranceFund.equals(insuranceFund));
+    assert(config.minAppealBond.eq(new BN(50)));
+  });
+
+  it("Registers a partner trading program", async () => {
+    const partnerProgram = new web3.Keypair().publicKey;
+    const [partner] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("partner"), partnerProgram.toBuffer()],
+      program.programId
     );
-    assert(traderStats.totalVolume.eq(volume));
-    assert(traderStats.averageExecutionTime.eq(executionTime));
-    assert(traderStats.pnl.eq(pnl));
+    const [partnerSigner] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("t2e_partner")],
+      partnerProgram
+    );
+
+    const txHash = await program.methods
+      .registerPartner(partnerProgram, new BN(0))
+      .accounts({
+        partner,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Partner registered: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const account = await program.account.partner.fetch(partner);
+    assert(account.programId.equals(partnerProgram));
+    assert(account.signer.equals(partnerSigner));
+    assert(account.active === true);
   });
 
-  it("Updates the leaderboard", async () => {
-    const traderStatsList = [
-      {
-        trader: traderAccount.publicKey,
-        totalVolume: new BN(5000),
-        averageExecutionTime: new BN(250),
-        pnl: new BN(200),
-        stakedAmount: new BN(0),
-      },
-    ];
+  it("Creates the protocol fee vault", async () => {
+    const [feeVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault"), t2eMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const [vaultAuthority] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_authority")],
+      program.programId
+    );
 
     const txHash = await program.methods
-      .updateLeaderboard(traderStatsList)
+      .initFeeVault()
       .accounts({
-        leaderboard: leaderboardAccount.publicKey,
+        feeVault,
+        feeMint: t2eMint.publicKey,
+        vaultAuthority,
+        config: configPda,
+        admin: program.provider.publicKey,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        systemProgram: web3.SystemProgram.programId,
       })
       .rpc();
 
-    console.log(`✅ Leaderboard updated: ${txHash}`);
+    console.log(`✅ Fee vault created: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
 
-    const leaderboard = await program.account.leaderboard.fetch(
-      leaderboardAccount.publicKey
+    const info = await program.provider.connection.getAccountInfo(feeVault);
+    assert(info !== null);
+  });
+
+  it("Creates the PDA-owned reward vault", async () => {
+    const txHash = await program.methods
+      .initRewardVault()
+      .accounts({
+        rewardVault,
+        rewardMint: t2eMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        config: configPda,
+        admin: program.provider.publicKey,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Reward vault created: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const info = await program.provider.connection.getAccountInfo(rewardVault);
+    assert(info !== null);
+  });
+
+  it("Creates the PDA-owned staking vault", async () => {
+    const txHash = await program.methods
+      .initStakingVault()
+      .accounts({
+        stakingVault,
+        mint: t2eMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        config: configPda,
+        admin: program.provider.publicKey,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Staking vault created: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const info = await program.provider.connection.getAccountInfo(stakingVault);
+    assert(info !== null);
+  });
+
+  it("Rejects an emission schedule starting in the past", async () => {
+    const [emissionSchedule] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("emission_schedule")],
+      program.programId
     );
-    assert(leaderboard.traders.length > 0);
-    assert(leaderboard.traders[0].toBase58() === traderAccount.publicKey.toBase58());
+    try {
+      await program.methods
+        .initEmissionSchedule(new BN(1), new BN(30 * 86400), 6, new BN(0))
+        .accounts({
+          emissionSchedule,
+          config: configPda,
+          admin: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("genesis must lie in the future");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "InvalidEmissionSchedule");
+      console.log("✅ Past genesis rejected");
+    }
   });
 
-  it("Distributes rewards", async () => {
-    const topN = new BN(1);
-    const rewardAmount = new BN(100);
+  it("Configures the per-epoch trade cap", async () => {
+    const txHash = await program.methods
+      .setTradeRateLimit(100, new BN(86400), true)
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Trade rate limit configured: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.maxTradesPerEpoch === 100);
+    assert(config.zeroWeightExcessTrades === true);
+  });
+
+  it("Initializes trader stats", async () => {
+    const txHash = await program.methods
+      .initTraderStats()
+      .accounts({
+        traderStats: traderStatsPda,
+        trader: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
 
+    console.log(`✅ Trader stats initialized: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(traderStats.trader.equals(program.provider.publicKey));
+    assert(traderStats.totalVolume.eq(new BN(0)));
+  });
+
+  it("Creates the trader's reward claim account", async () => {
+    const [rewardClaim] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("reward_claim"),
+        leaderboardAccount.publicKey.toBuffer(),
+        program.provider.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
     const txHash = await program.methods
-      .distributeRewards(topN, rewardAmount)
+      .initRewardClaim()
       .accounts({
+        rewardClaim,
         leaderboard: leaderboardAccount.publicKey,
-        rewardVault: rewardVault.publicKey,
-        rewardAuthority: program.provider.publicKey,
-        tokenProgram: web3.PublicKey.default, // FIXED: Removed 'spl' reference
+        traderStats: traderStatsPda,
+        payer: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
       })
       .rpc();
 
-    console.log(`✅ Rewards distributed: ${txHash}`);
+    console.log(`✅ Reward claim created: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
+
+    const account = await program.account.rewardClaim.fetch(rewardClaim);
+    assert(account.trader.equals(program.provider.publicKey));
+    assert(account.claimable.eq(new BN(0)));
   });
 
-  it("Allows traders to stake tokens", async () => {
-    const stakeAmount = new BN(1000);
+  it("Configures reward vesting and creates a vesting schedule", async () => {
+    const txHash = await program.methods
+      .setVestingParams(new BN(7 * 24 * 60 * 60), new BN(90 * 24 * 60 * 60))
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+
+    console.log(`✅ Reward vesting configured: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.vestingCliff.eq(new BN(7 * 24 * 60 * 60)));
+    assert(config.vestingDuration.eq(new BN(90 * 24 * 60 * 60)));
+
+    try {
+      await program.methods
+        .setVestingParams(new BN(10), new BN(5))
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+      assert.fail("a cliff past the duration should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidVestingParams");
+    }
+
+    const [vestingSchedule] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("vesting"),
+        leaderboardAccount.publicKey.toBuffer(),
+        program.provider.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    await program.methods
+      .initVestingSchedule()
+      .accounts({
+        vestingSchedule,
+        leaderboard: leaderboardAccount.publicKey,
+        traderStats: traderStatsPda,
+        payer: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    const schedule = await program.account.vestingSchedule.fetch(vestingSchedule);
+    assert(schedule.trader.equals(program.provider.publicKey));
+    assert(schedule.total.eq(new BN(0)));
+
+    // Later payout tests credit rewards directly.
+    await program.methods
+      .setVestingParams(new BN(0), new BN(0))
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+  });
+
+  it("Records a trade", async () => {
+    const volume = new BN(5000);
+    const executionTime = new BN(250);
+    const pnl = new BN(200);
 
     const txHash = await program.methods
-      .stakeTokens(stakeAmount)
+      .recordTrade(volume, executionTime, pnl, { spot: {} }, null, null)
       .accounts({
-        traderStats: traderAccount.publicKey,
-        traderTokenAccount: traderTokenAccount.publicKey,
-        stakingVault: stakingVault.publicKey,
+        traderStats: traderStatsPda,
+        pairVolume: null,
+        config: configPda,
+        season: null,
+        seasonStats: null,
+        marketStats: null,
+        registeredMarket: null,
+        leaderboard: null,
+        delegate: null,
         trader: program.provider.publicKey,
-        tokenProgram: web3.PublicKey.default, // FIXED: Removed 'spl' reference
       })
       .rpc();
 
-    console.log(`✅ Tokens staked: ${txHash}`);
+    console.log(`✅ Trade recorded: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
 
     const traderStats = await program.account.traderStats.fetch(
-      traderAccount.publicKey
+      traderStatsPda
     );
-    assert(traderStats.stakedAmount.eq(stakeAmount));
+    assert(traderStats.totalVolume.eq(volume));
+    assert(traderStats.averageExecutionTime.eq(executionTime));
+    // One trade counted in the bucket of its execution time.
+    assert(traderStats.executionTimeHistogram.reduce((sum, count) => sum + count, 0) === 1);
+    assert(traderStats.pnl.eq(pnl));
+    assert(traderStats.tradePnlSum.eq(pnl));
+    assert(traderStats.tradePnlSumSquares.eq(pnl.mul(pnl)));
+    assert(traderStats.winningTrades.eq(new BN(1)));
+    assert(traderStats.losingTrades.eq(new BN(0)));
+    // The first trade opens a streak day with no earlier streak.
+    assert(traderStats.streakDay.eq(traderStats.lastTrade.divn(86400)));
+    assert(traderStats.streakDayPnl.eq(pnl));
+    assert(traderStats.tradingStreak === 0);
+    assert(traderStats.spotVolume.eq(volume));
+    assert(traderStats.perpVolume.eq(new BN(0)));
   });
 
-  it("Calculates fee discounts based on staking", async () => {
+  it("Records trades past the per-epoch cap at zero weight", async () => {
+    const setTradeRateLimit = (maxTrades: number) =>
+      program.methods
+        .setTradeRateLimit(maxTrades, new BN(86400), true)
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+    const setTradeRateWindow = (maxTrades: number, window: number) =>
+      program.methods
+        .setTradeRateWindow(maxTrades, new BN(window))
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+    const recordTrade = (pnl: number) =>
+      program.methods
+        .recordTrade(new BN(5_000), new BN(100), new BN(pnl), { spot: {} }, null, null)
+        .accounts({
+          traderStats: traderStatsPda,
+          pairVolume: null,
+          config: configPda,
+          season: null,
+          seasonStats: null,
+          marketStats: null,
+          registeredMarket: null,
+          leaderboard: null,
+          delegate: null,
+          trader: program.provider.publicKey,
+        })
+        .rpc();
+
+    // The trade recorded above used up this epoch's single trade; a rate
+    // window instead of the cooldown lets the next ones through at once.
+    await setTradeRateLimit(1);
+    await setTradeRateWindow(5, 300);
+    const before = await program.account.traderStats.fetch(traderStatsPda);
+    await recordTrade(50);
+    let after = await program.account.traderStats.fetch(traderStatsPda);
+    assert(after.tradeCount.eq(before.tradeCount));
+    assert(after.totalVolume.eq(before.totalVolume));
+    assert(after.winningTrades.eq(before.winningTrades));
+    assert(after.tradingStreak === before.tradingStreak);
+    assert(after.streakDayPnl.eq(before.streakDayPnl));
+    assert(after.loyaltyPoints.eq(before.loyaltyPoints));
+    assert(after.pnl.eq(before.pnl));
+    assert(after.lastTrade.gte(before.lastTrade));
+
+    // Losses past the cap still count against the P&L.
+    await recordTrade(-20);
+    after = await program.account.traderStats.fetch(traderStatsPda);
+    assert(after.tradeCount.eq(before.tradeCount));
+    assert(after.losingTrades.eq(before.losingTrades));
+    assert(after.pnl.eq(before.pnl.subn(20)));
+
+    await setTradeRateWindow(0, 0);
+    const txHash = await setTradeRateLimit(100);
+    console.log(`✅ Over-cap trades recorded at zero weight: ${txHash}`);
+  });
+
+  it("Initializes a per-pair volume account", async () => {
+    const pair = new web3.Keypair().publicKey;
+    const [pairVolume] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pair_volume"), program.provider.publicKey.toBuffer(), pair.toBuffer()],
+      program.programId
+    );
+
     const txHash = await program.methods
-      .calculateFeeDiscount()
+      .initPairVolume(pair)
       .accounts({
-        traderStats: traderAccount.publicKey,
+        pairVolume,
         trader: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
       })
       .rpc();
 
-    console.log(`✅ Fee discount calculated: ${txHash}`);
+    console.log(`✅ Pair volume initialized: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
 
-    const traderStats = await program.account.traderStats.fetch(
-      traderAccount.publicKey
-    );
-    assert(traderStats.feeDiscount >= 0 && traderStats.feeDiscount <= 50);
+    const account = await program.account.pairVolume.fetch(pairVolume);
+    assert(account.pair.equals(pair));
+    assert(account.volume.eq(new BN(0)));
   });
 
-  it("Takes a snapshot of the leaderboard history", async () => {
-    const leaderboardHistoryAccount = new web3.Keypair();
+  it("Initializes per-market stats", async () => {
+    const market = new web3.Keypair().publicKey;
+    const [marketStats] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("market_stats"), market.toBuffer(), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
 
     const txHash = await program.methods
-      .snapshotLeaderboard()
+      .initMarketStats(market)
       .accounts({
-        leaderboard: leaderboardAccount.publicKey,
-        leaderboardHistory: leaderboardHistoryAccount.publicKey,
-        admin: program.provider.publicKey,
+        marketStats,
+        trader: program.provider.publicKey,
         systemProgram: web3.SystemProgram.programId,
       })
-      .signers([leaderboardHistoryAccount])
       .rpc();
 
-    console.log(`✅ Leaderboard snapshot saved: ${txHash}`);
+    console.log(`✅ Market stats initialized: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
+
+    const account = await program.account.marketStats.fetch(marketStats);
+    assert(account.market.equals(market));
+    assert(account.totalVolume.eq(new BN(0)));
+  });
+
+  it("Rejects a funding cursor for a non-Drift account", async () => {
+    const venueUser = program.provider.publicKey;
+    const [fundingCursor] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("funding"), program.provider.publicKey.toBuffer(), venueUser.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .initFundingCursor()
+        .accounts({
+          fundingCursor,
+          venueUser,
+          trader: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("a non-Drift venue account should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidVenueAccount");
+    }
+  });
+
+  it("Rejects unstaking more than the staked balance", async () => {
+    try {
+      await program.methods
+        .requestUnstake(new BN(1_000_000))
+        .accounts({
+          traderStats: traderStatsPda,
+          stakePosition: null,
+          config: configPda,
+          trader: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("unstaking more than the stake should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidUnstakeAmount");
+    }
+  });
+
+  it("Rejects linking a wallet to itself", async () => {
+    const [walletLink] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("wallet_link"), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .linkWallet()
+        .accounts({
+          walletLink,
+          primaryStats: traderStatsPda,
+          secondaryStats: traderStatsPda,
+          primary: program.provider.publicKey,
+          secondary: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("self-linking should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "CannotLinkSelf");
+    }
+  });
+
+  it("Rejects linking a wallet that other wallets are linked to", async () => {
+    const [head, member, outsider] = [0, 1, 2].map(() => new web3.Keypair());
+    const statsOf = (wallet: web3.Keypair) =>
+      web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("trader_stats"), wallet.publicKey.toBuffer()],
+        program.programId
+      )[0];
+    const linkOf = (secondary: web3.Keypair) =>
+      web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("wallet_link"), secondary.publicKey.toBuffer()],
+        program.programId
+      )[0];
+    await program.provider.sendAndConfirm(
+      new web3.Transaction().add(
+        ...[head, member, outsider].map((wallet) =>
+          web3.SystemProgram.transfer({
+            fromPubkey: program.provider.publicKey,
+            toPubkey: wallet.publicKey,
+            lamports: web3.LAMPORTS_PER_SOL / 10,
+          })
+        )
+      )
+    );
+    for (const wallet of [head, member, outsider]) {
+      await program.methods
+        .initTraderStats()
+        .accounts({
+          traderStats: statsOf(wallet),
+          trader: wallet.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    await program.methods
+      .linkWallet()
+      .accounts({
+        walletLink: linkOf(member),
+        primaryStats: statsOf(head),
+        secondaryStats: statsOf(member),
+        primary: head.publicKey,
+        secondary: member.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([head, member])
+      .rpc();
+    const headStats = await program.account.traderStats.fetch(statsOf(head));
+    assert.equal(headStats.linkedWallets, 1);
+
+    try {
+      await program.methods
+        .linkWallet()
+        .accounts({
+          walletLink: linkOf(head),
+          primaryStats: statsOf(outsider),
+          secondaryStats: statsOf(head),
+          primary: outsider.publicKey,
+          secondary: head.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([outsider, head])
+        .rpc();
+      assert.fail("a primary wallet should not be linked as a secondary");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "WalletIsPrimary");
+    }
+  });
+
+  it("Redeems loyalty points for fee credits", async () => {
+    const before = await program.account.traderStats.fetch(traderStatsPda);
+
+    const txHash = await program.methods
+      .redeemPoints(before.loyaltyPoints, { feeCredit: {} })
+      .accounts({
+        traderStats: traderStatsPda,
+        config: configPda,
+        trader: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Loyalty points redeemed: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const after = await program.account.traderStats.fetch(traderStatsPda);
+    assert(after.loyaltyPoints.eq(new BN(0)));
+  });
+
+  it("Follows and unfollows a copy-trading leader", async () => {
+    const leader = new web3.Keypair().publicKey;
+    const [followLink] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("follow"), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const followTx = await program.methods
+      .followLeader(leader)
+      .accounts({
+        followLink,
+        follower: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Leader followed: ${followTx}`);
+    await program.provider.connection.confirmTransaction(followTx);
+
+    const link = await program.account.followLink.fetch(followLink);
+    assert(link.leader.equals(leader));
+
+    const unfollowTx = await program.methods
+      .unfollowLeader()
+      .accounts({
+        followLink,
+        follower: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Leader unfollowed: ${unfollowTx}`);
+    await program.provider.connection.confirmTransaction(unfollowTx);
+  });
+
+  it("Claims a vanity referral code", async () => {
+    const code = "t2e-alpha";
+    const [referralCode] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("referral_code"), createHash("sha256").update(code).digest()],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .claimReferralCode(code)
+      .accounts({
+        referralCode,
+        config: configPda,
+        traderTokenAccount: null,
+        treasury: null,
+        trader: program.provider.publicKey,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Referral code claimed: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const account = await program.account.referralCode.fetch(referralCode);
+    assert(account.code === code);
+    assert(account.owner.equals(program.provider.publicKey));
+
+    try {
+      await program.methods
+        .applyReferralCode(code)
+        .accounts({
+          referralCode,
+          traderStats: traderStatsPda,
+          trader: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("self-referral should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "CannotReferSelf");
+    }
+  });
+
+  it("Registers a referral link to a referrer", async () => {
+    const referrer = web3.Keypair.generate().publicKey;
+    const [referral] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("referral"), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .registerReferral(referrer)
+      .accounts({
+        referral,
+        traderStats: traderStatsPda,
+        trader: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Referral registered: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const link = await program.account.referral.fetch(referral);
+    const stats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(link.referrer.equals(referrer));
+    assert(link.rewardsRouted.eqn(0));
+    assert(stats.referrer.equals(referrer));
+  });
+
+  it("Rejects a transferable badge mint", async () => {
+    const [badgeAuthority] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("badge_authority")],
+      program.programId
+    );
+    try {
+      await program.methods
+        .setBadgeMint({ hundredTrades: {} })
+        .accounts({
+          config: configPda,
+          badgeMint: t2eMint.publicKey,
+          badgeAuthority,
+          admin: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("a classic SPL mint should be rejected");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "InvalidBadgeMint");
+      console.log("✅ Transferable badge mint rejected");
+    }
+  });
+
+  it("Initializes and recalculates an affiliate tier", async () => {
+    const [affiliate] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("affiliate"), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const initTx = await program.methods
+      .initAffiliate()
+      .accounts({
+        affiliate,
+        trader: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Affiliate account created: ${initTx}`);
+    await program.provider.connection.confirmTransaction(initTx);
+
+    const recalcTx = await program.methods
+      .recalculateAffiliateTier()
+      .accounts({
+        affiliate,
+        referrer: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Affiliate tier recalculated: ${recalcTx}`);
+    await program.provider.connection.confirmTransaction(recalcTx);
+
+    const account = await program.account.affiliate.fetch(affiliate);
+    assert("bronze" in account.tier);
+    assert(account.revenueShareBps === 500);
+    assert(account.activeReferees === 0);
+  });
+
+  it("Sets the trader profile", async () => {
+    const [traderProfile] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_profile"), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+    const displayName = Buffer.alloc(32);
+    displayName.write("speedy");
+    const [displayNameClaim] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("display_name"), displayName],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .setTraderProfile([...displayName], "https://example.com/avatar.png", Array(32).fill(0))
+      .accounts({
+        traderProfile,
+        displayNameClaim,
+        previousDisplayName: null,
+        trader: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Trader profile set: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const profile = await program.account.traderProfile.fetch(traderProfile);
+    assert(profile.avatarUri === "https://example.com/avatar.png");
+    const claim = await program.account.displayName.fetch(displayNameClaim);
+    assert(claim.owner.equals(program.provider.publicKey));
+  });
+
+  it("Rejects a display name held by another trader", async () => {
+    const impostor = web3.Keypair.generate();
+    const [traderProfile] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_profile"), impostor.publicKey.toBuffer()],
+      program.programId
+    );
+    const displayName = Buffer.alloc(32);
+    displayName.write("speedy");
+    const [displayNameClaim] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("display_name"), displayName],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .setTraderProfile([...displayName], "", Array(32).fill(0))
+        .accounts({
+          traderProfile,
+          displayNameClaim,
+          previousDisplayName: null,
+          trader: impostor.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .preInstructions([
+          web3.SystemProgram.transfer({
+            fromPubkey: program.provider.publicKey,
+            toPubkey: impostor.publicKey,
+            lamports: web3.LAMPORTS_PER_SOL / 10,
+          }),
+        ])
+        .signers([impostor])
+        .rpc();
+      assert.fail("a taken display name should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "DisplayNameTaken");
+    }
+    console.log("✅ Display names are unique");
+  });
+
+  it("Rejects self-endorsement", async () => {
+    const [endorsement] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("endorsement"),
+        program.provider.publicKey.toBuffer(),
+        program.provider.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .endorseTrader()
+        .accounts({
+          endorsement,
+          endorserStats: traderStatsPda,
+          endorseeStats: traderStatsPda,
+          trader: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("self-endorsement should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "CannotEndorseSelf");
+    }
+  });
+
+  it("Suspends and reinstates a trader", async () => {
+    const until = new BN(Math.floor(Date.now() / 1000) + 24 * 60 * 60);
+
+    const suspendTx = await program.methods
+      .suspendTrader(until)
+      .accounts({
+        traderStats: traderStatsPda,
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Trader suspended: ${suspendTx}`);
+    await program.provider.connection.confirmTransaction(suspendTx);
+
+    let traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(traderStats.suspendedUntil.eq(until));
+
+    const liftTx = await program.methods
+      .suspendTrader(new BN(0))
+      .accounts({
+        traderStats: traderStatsPda,
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Suspension lifted: ${liftTx}`);
+    await program.provider.connection.confirmTransaction(liftTx);
+
+    traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(traderStats.suspendedUntil.eq(new BN(0)));
+  });
+
+  it("Applies for and revokes market-maker status", async () => {
+    const applyTx = await program.methods
+      .applyMarketMaker()
+      .accounts({
+        traderStats: traderStatsPda,
+        trader: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Market-maker application submitted: ${applyTx}`);
+    await program.provider.connection.confirmTransaction(applyTx);
+
+    let traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert("pending" in traderStats.marketMaker);
+
+    const reviewTx = await program.methods
+      .reviewMarketMaker(false)
+      .accounts({
+        traderStats: traderStatsPda,
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Market-maker application rejected: ${reviewTx}`);
+    await program.provider.connection.confirmTransaction(reviewTx);
+
+    traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert("none" in traderStats.marketMaker);
+  });
+
+  it("Updates the leaderboard", async () => {
+    const traderStatsList = [
+      {
+        trader: traderStatsPda,
+        totalVolume: new BN(5000),
+        averageExecutionTime: new BN(250),
+        pnl: new BN(200),
+        stakedAmount: new BN(0),
+        reputation: new BN(0),
+        firstTrade: new BN(0),
+        lastTrade: new BN(0),
+        marketMaker: false,
+        tradeCount: new BN(0),
+        tradePnlSum: new BN(0),
+        tradePnlSumSquares: new BN(0),
+        winningTrades: new BN(0),
+        losingTrades: new BN(0),
+        streakDays: 0,
+      },
+    ];
+
+    const txHash = await program.methods
+      .updateLeaderboard(traderStatsList)
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        role: null,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Leaderboard updated: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    assert(leaderboard.len > 0);
+    const [top] = await fetchBoardEntries(leaderboardAccount.publicKey);
+    assert(top.trader.toBase58() === traderStatsPda.toBase58());
+  });
+
+  it("Rejects leaderboard updates from non-operators", async () => {
+    const outsider = new web3.Keypair();
+
+    try {
+      await program.methods
+        .updateLeaderboard([])
+        .accounts({
+          leaderboard: leaderboardAccount.publicKey,
+          config: configPda,
+          role: null,
+          authority: outsider.publicKey,
+        })
+        .signers([outsider])
+        .rpc();
+      assert.fail("an outsider should not be able to post rankings");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "Unauthorized");
+    }
+  });
+
+  it("Breaks score ties deterministically", async () => {
+    const board = new web3.Keypair();
+    await program.methods
+      .initialize(web3.PublicKey.default)
+      .accounts({ leaderboard: board.publicKey })
+      .preInstructions([
+        await program.account.leaderboard.createInstruction(board, leaderboardSpace(4)),
+      ])
+      .signers([board])
+      .rpc();
+
+    // Equal scores, reputation and volume: the earlier first trade ranks higher.
+    const entry = (trader: web3.PublicKey, firstTrade: number) => ({
+      trader,
+      totalVolume: new BN(5000),
+      averageExecutionTime: new BN(250),
+      pnl: new BN(200),
+      stakedAmount: new BN(0),
+      reputation: new BN(0),
+      firstTrade: new BN(firstTrade),
+      lastTrade: new BN(0),
+      marketMaker: false,
+      tradeCount: new BN(0),
+      tradePnlSum: new BN(0),
+      tradePnlSumSquares: new BN(0),
+      winningTrades: new BN(0),
+      losingTrades: new BN(0),
+      streakDays: 0,
+    });
+    const late = web3.Keypair.generate().publicKey;
+    const early = web3.Keypair.generate().publicKey;
+
+    const txHash = await program.methods
+      .updateLeaderboard([entry(late, 2_000), entry(early, 1_000)])
+      .accounts({
+        leaderboard: board.publicKey,
+        config: configPda,
+        role: null,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Tied ranking updated: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const entries = await fetchBoardEntries(board.publicKey);
+    assert(entries.length === 2);
+    assert(entries[0].score.eq(entries[1].score));
+    assert(entries[0].trader.equals(early));
+    assert(entries[0].firstTrade.eq(new BN(1_000)));
+  });
+
+  it("Stages a ranking update across pages", async () => {
+    const [pendingUpdate] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_update"), leaderboardAccount.publicKey.toBuffer()],
+      program.programId
+    );
+    const entry = {
+      trader: traderStatsPda,
+      totalVolume: new BN(5000),
+      averageExecutionTime: new BN(250),
+      pnl: new BN(200),
+      stakedAmount: new BN(0),
+      reputation: new BN(0),
+      firstTrade: new BN(0),
+      lastTrade: new BN(0),
+      marketMaker: false,
+      tradeCount: new BN(0),
+      tradePnlSum: new BN(0),
+      tradePnlSumSquares: new BN(0),
+      winningTrades: new BN(0),
+      losingTrades: new BN(0),
+      streakDays: 0,
+    };
+
+    await program.methods
+      .beginLeaderboardUpdate()
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        pendingUpdate,
+        config: configPda,
+        role: null,
+        authority: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const txHash = await program.methods
+      .submitRankingPage(0, [entry])
+      .accounts({
+        pendingUpdate,
+        config: configPda,
+        authority: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Ranking page submitted: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const pending = await program.account.pendingUpdate.fetch(pendingUpdate);
+    assert(pending.len === 1);
+    assert(pending.nextPage === 1);
+
+    try {
+      await program.methods
+        .submitRankingPage(2, [entry])
+        .accounts({
+          pendingUpdate,
+          config: configPda,
+          authority: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("pages should be submitted in order");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidPageIndex");
+    }
+
+    await program.methods
+      .cancelLeaderboardUpdate()
+      .accounts({
+        pendingUpdate,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    assert(
+      (await program.provider.connection.getAccountInfo(pendingUpdate)) === null
+    );
+  });
+
+  it("Sets the trader's payout preference", async () => {
+    const txHash = await program.methods
+      .setPayoutPreference({ autoStake: {} })
+      .accounts({
+        traderStats: traderStatsPda,
+        config: configPda,
+        trader: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Payout preference set: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const traderStats = await program.account.traderStats.fetch(
+      traderStatsPda
+    );
+    assert("autoStake" in traderStats.payoutPreference);
+  });
+
+  it("Reads a page of the leaderboard", async () => {
+    const page = await program.methods
+      .getLeaderboardPage(0, 10)
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+      })
+      .view();
+
+    assert(page.total === 1);
+    assert(page.entries.length === 1);
+    assert(page.entries[0].trader.equals(traderStatsPda));
+  });
+
+  it("Returns a trader's rank via return data", async () => {
+    const rank = await program.methods
+      .getTraderRank(traderStatsPda)
+      .accounts({ leaderboard: leaderboardAccount.publicKey })
+      .view();
+
+    assert(rank.ranked);
+    assert(rank.rank === 0);
+    assert(rank.total === 1);
+
+    const unranked = await program.methods
+      .getTraderRank(web3.Keypair.generate().publicKey)
+      .accounts({ leaderboard: leaderboardAccount.publicKey })
+      .view();
+    assert(!unranked.ranked);
+    assert(unranked.score.eq(new BN(0)));
+  });
+
+  it("Grows the leaderboard without losing its ranking", async () => {
+    const before = await fetchBoardEntries(leaderboardAccount.publicKey);
+    const txHash = await program.methods
+      .growLeaderboard(16)
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        role: null,
+        authority: program.provider.publicKey,
+        payer: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Leaderboard grown: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const leaderboard = await program.account.leaderboard.fetch(leaderboardAccount.publicKey);
+    assert(leaderboard.capacity === 32);
+    const after = await fetchBoardEntries(leaderboardAccount.publicKey);
+    assert(after.length === before.length);
+    after.forEach((entry, i) => {
+      assert(entry.trader.equals(before[i].trader));
+      assert(entry.score.eq(before[i].score));
+    });
+
+    try {
+      await program.methods
+        .growLeaderboard(161)
+        .accounts({
+          leaderboard: leaderboardAccount.publicKey,
+          config: configPda,
+          role: null,
+          authority: program.provider.publicKey,
+          payer: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("growth beyond the per-call limit should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidLeaderboardGrowth");
+    }
+  });
+
+  it("Rejects pruning while no inactivity horizon is set", async () => {
+    try {
+      await program.methods
+        .pruneInactive()
+        .accounts({
+          leaderboard: leaderboardAccount.publicKey,
+          config: configPda,
+        })
+        .remainingAccounts([
+          { pubkey: traderStatsPda, isWritable: false, isSigner: false },
+        ])
+        .rpc();
+      assert.fail("pruning should be disabled by default");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "PruningDisabled");
+    }
+  });
+
+  it("Previews scores without touching the leaderboard", async () => {
+    const before = await program.account.leaderboard.fetch(leaderboardAccount.publicKey);
+
+    const preview = await program.methods
+      .previewScores()
+      .accounts({ config: configPda })
+      .remainingAccounts([
+        { pubkey: traderStatsPda, isWritable: false, isSigner: false },
+      ])
+      .view();
+
+    assert(preview.total === 1);
+    assert(preview.entries.length === 1);
+
+    const after = await program.account.leaderboard.fetch(leaderboardAccount.publicKey);
+    assert(after.lastUpdate.eq(before.lastUpdate));
+  });
+
+  it("Rejects the same trader stats passed twice to a preview", async () => {
+    try {
+      await program.methods
+        .previewScores()
+        .accounts({ config: configPda })
+        .remainingAccounts([
+          { pubkey: traderStatsPda, isWritable: false, isSigner: false },
+          { pubkey: traderStatsPda, isWritable: false, isSigner: false },
+        ])
+        .view();
+      assert.fail("duplicate trader stats should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "DuplicateTraderStats");
+    }
+  });
+
+  it("Previews a trader's projected payout", async () => {
+    const preview = await program.methods
+      .previewPayout(new BN(10))
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        traderStats: traderStatsPda,
+        emissionSchedule: null,
+      })
+      .view();
+
+    assert(preview.shareBps <= 10000);
+    if (!preview.ranked) {
+      assert(preview.estimatedAmount.eq(new BN(0)));
+    }
+  });
+
+  it("Configures the season pass", async () => {
+    const txHash = await program.methods
+      .setSeasonPassParams(1, new BN(500), 1000, 2000)
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Season pass configured: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.currentSeason === 1);
+    assert(config.seasonPassPrice.eq(new BN(500)));
+    assert(config.seasonPassBoostBps === 1000);
+  });
+
+  it("Exports the full ranking as chunked events", async () => {
+    const [eventAuthority] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("__event_authority")],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .exportRankings(0, 100)
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        eventAuthority,
+        program: program.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Rankings exported: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+  });
+
+  it("Sets the claim window of a reward epoch", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const epoch = new BN(Math.floor(now / (30 * 24 * 60 * 60)));
+    const [claimWindow] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("claim_window"), epoch.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .setClaimWindow(epoch, new BN(now), new BN(now + 30 * 24 * 60 * 60))
+      .accounts({
+        claimWindow,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Claim window set: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const account = await program.account.claimWindow.fetch(claimWindow);
+    assert(account.epoch.eq(epoch));
+    assert(account.closeTs.gt(account.openTs));
+  });
+
+  it("Snapshots the ranking for distribution", async () => {
+    const leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    [rankingSnapshot] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("ranking_snapshot"),
+        leaderboardAccount.publicKey.toBuffer(),
+        leaderboard.snapshotSequence.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .createRankingSnapshot()
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        rankingSnapshot,
+        config: configPda,
+        role: null,
+        authority: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Ranking snapshot created: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const snapshot = await program.account.rankingSnapshot.fetch(rankingSnapshot);
+    assert(snapshot.sequence.eq(leaderboard.snapshotSequence));
+    assert(snapshot.rankingUpdate.eq(leaderboard.lastUpdate));
+    assert(snapshot.paidAt.eq(new BN(0)));
+  });
+
+  it("Distributes rewards", async () => {
+    const topN = new BN(1);
+    const setPoolTx = await program.methods
+      .setRewardPool(new BN(100))
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    await program.provider.connection.confirmTransaction(setPoolTx);
+    const config = await program.account.config.fetch(configPda);
+    assert(config.rewardPool.eq(new BN(100)));
+
+    const txHash = await program.methods
+      .distributeRewards(topN)
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        rewardVault,
+        rewardMint: t2eMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        stakingVault,
+        swapEscrow: web3.PublicKey.default,
+        treasury: null,
+        rankingSnapshot,
+        emissionSchedule: null,
+        tokenProgram: web3.PublicKey.default, // FIXED: Removed 'spl' reference
+      })
+      .remainingAccounts([
+        { pubkey: traderStatsPda, isWritable: true, isSigner: false },
+        { pubkey: traderTokenAccount.publicKey, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    console.log(`✅ Rewards distributed: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    // A fresh snapshot of the same ranking cannot be paid again.
+    const leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    assert(leaderboard.lastPaidUpdate.eq(leaderboard.lastUpdate));
+    const [replaySnapshot] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("ranking_snapshot"),
+        leaderboardAccount.publicKey.toBuffer(),
+        leaderboard.snapshotSequence.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    await program.methods
+      .createRankingSnapshot()
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        rankingSnapshot: replaySnapshot,
+        config: configPda,
+        role: null,
+        authority: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    try {
+      await program.methods
+        .distributeRewards(topN)
+        .accounts({
+          leaderboard: leaderboardAccount.publicKey,
+          config: configPda,
+          rewardVault,
+          rewardMint: t2eMint.publicKey,
+          vaultAuthority: vaultAuthorityPda,
+          stakingVault,
+          swapEscrow: web3.PublicKey.default,
+          treasury: null,
+          rankingSnapshot: replaySnapshot,
+          emissionSchedule: null,
+          tokenProgram: web3.PublicKey.default,
+        })
+        .remainingAccounts([
+          { pubkey: traderStatsPda, isWritable: true, isSigner: false },
+          { pubkey: traderTokenAccount.publicKey, isWritable: true, isSigner: false },
+        ])
+        .rpc();
+      assert.fail("a paid ranking should not be paid again");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "RankingAlreadyPaid");
+    }
+  });
+
+  it("Pays a Token-2022 transfer-fee reward mint through finalize and claim", async () => {
+    const connection = program.provider.connection;
+    const payer = (program.provider as anchor.AnchorProvider).wallet as anchor.Wallet;
+    const trader = program.provider.publicKey;
+    const feeBps = 100;
+
+    // A 6-decimal mint charging 1% on every transfer.
+    const feeMint = new web3.Keypair();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await web3.sendAndConfirmTransaction(
+      connection,
+      new web3.Transaction().add(
+        web3.SystemProgram.createAccount({
+          fromPubkey: trader,
+          newAccountPubkey: feeMint.publicKey,
+          space: mintLen,
+          lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          feeMint.publicKey,
+          trader,
+          trader,
+          feeBps,
+          BigInt(1_000_000_000),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(feeMint.publicKey, 6, trader, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [payer.payer, feeMint]
+    );
+    const fee = (amount: number) => Math.ceil((amount * feeBps) / 10_000);
+
+    const traderAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer.payer,
+      feeMint.publicKey,
+      trader,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      connection,
+      payer.payer,
+      feeMint.publicKey,
+      traderAccount.address,
+      trader,
+      1_000_000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const [feeRewardVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_vault"), feeMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const [feeStakingVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("staking_vault"), feeMint.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initRewardVault()
+      .accounts({
+        rewardVault: feeRewardVault,
+        rewardMint: feeMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        config: configPda,
+        admin: trader,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .initStakingVault()
+      .accounts({
+        stakingVault: feeStakingVault,
+        mint: feeMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        config: configPda,
+        admin: trader,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // Funding credits the vault net of the transfer fee.
+    await program.methods
+      .fundRewardVault(new BN(500_000))
+      .accounts({
+        rewardVault: feeRewardVault,
+        funderTokenAccount: traderAccount.address,
+        rewardMint: feeMint.publicKey,
+        funder: trader,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+    let vault = await getAccount(connection, feeRewardVault, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(vault.amount), 500_000 - fee(500_000));
+
+    // Rank the trader on a fresh board, paid from the vault without vesting.
+    await program.methods
+      .setVestingParams(new BN(0), new BN(0))
+      .accounts({ config: configPda, admin: trader })
+      .rpc();
+    await program.methods
+      .setProtocolParams(new BN(10), new BN(0), new BN(180 * 86400), 1000)
+      .accounts({ config: configPda, admin: trader })
+      .rpc();
+    await program.methods
+      .setPayoutPreference({ t2e: {} })
+      .accounts({ traderStats: traderStatsPda, config: configPda, trader })
+      .rpc();
+    const board = new web3.Keypair();
+    await program.methods
+      .initialize(web3.PublicKey.default)
+      .accounts({ leaderboard: board.publicKey })
+      .preInstructions([
+        await program.account.leaderboard.createInstruction(board, leaderboardSpace(4)),
+      ])
+      .signers([board])
+      .rpc();
+    await program.methods
+      .updateLeaderboard([
+        {
+          trader,
+          totalVolume: new BN(5000),
+          averageExecutionTime: new BN(250),
+          pnl: new BN(200),
+          stakedAmount: new BN(0),
+          reputation: new BN(0),
+          firstTrade: new BN(0),
+          lastTrade: new BN(0),
+          marketMaker: false,
+          tradeCount: new BN(0),
+          tradePnlSum: new BN(0),
+          tradePnlSumSquares: new BN(0),
+          winningTrades: new BN(0),
+          losingTrades: new BN(0),
+          streakDays: 0,
+        },
+      ])
+      .accounts({ leaderboard: board.publicKey, config: configPda, role: null, authority: trader })
+      .rpc();
+
+    const [snapshot] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("ranking_snapshot"), board.publicKey.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .createRankingSnapshot()
+      .accounts({
+        leaderboard: board.publicKey,
+        rankingSnapshot: snapshot,
+        config: configPda,
+        role: null,
+        authority: trader,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    const [rewardClaim] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_claim"), board.publicKey.toBuffer(), trader.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initRewardClaim()
+      .accounts({
+        rewardClaim,
+        leaderboard: board.publicKey,
+        traderStats: traderStatsPda,
+        payer: trader,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const [claimVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("claim_vault"), board.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .finalizeEpoch(new BN(1))
+      .accounts({
+        leaderboard: board.publicKey,
+        config: configPda,
+        rewardVault: feeRewardVault,
+        payer: trader,
+        claimVault,
+        rewardMint: feeMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        treasury: null,
+        rankingSnapshot: snapshot,
+        emissionSchedule: null,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: traderStatsPda, isWritable: true, isSigner: false },
+        { pubkey: rewardClaim, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    // The reward vault covers the fee, so the whole claim arrives in the claim vault.
+    const { claimable } = await program.account.rewardClaim.fetch(rewardClaim);
+    assert(claimable.gt(new BN(0)));
+    const claimVaultAccount = await getAccount(connection, claimVault, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(Number(claimVaultAccount.amount), claimable.toNumber());
+
+    const now = Math.floor(Date.now() / 1000);
+    const [claimWindow] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("claim_window"),
+        new BN(Math.floor(now / (30 * 24 * 60 * 60))).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const before = await getAccount(connection, traderAccount.address, undefined, TOKEN_2022_PROGRAM_ID);
+    await program.methods
+      .claimRewards(false)
+      .accounts({
+        rewardClaim,
+        traderStats: traderStatsPda,
+        claimWindow,
+        claimVault,
+        rewardMint: feeMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        traderTokenAccount: traderAccount.address,
+        stakingVault: feeStakingVault,
+        swapEscrow: web3.PublicKey.default,
+        config: configPda,
+        trader,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+
+    // The trader receives the claim net of the fee on the payout transfer.
+    const after = await getAccount(connection, traderAccount.address, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(
+      Number(after.amount - before.amount),
+      claimable.toNumber() - fee(claimable.toNumber())
+    );
+    const claim = await program.account.rewardClaim.fetch(rewardClaim);
+    assert(claim.claimable.eq(new BN(0)));
+    token2022Claim = {
+      rewardClaim,
+      claimWindow,
+      claimVault,
+      rewardMint: feeMint.publicKey,
+      traderTokenAccount: traderAccount.address,
+      stakingVault: feeStakingVault,
+    };
+
+    // Restore the settings later tests rely on.
+    await program.methods
+      .setPayoutPreference({ autoStake: {} })
+      .accounts({ traderStats: traderStatsPda, config: configPda, trader })
+      .rpc();
+    await program.methods
+      .setProtocolParams(new BN(10), new BN(600), new BN(180 * 86400), 1000)
+      .accounts({ config: configPda, admin: trader })
+      .rpc();
+    await program.methods
+      .setVestingParams(new BN(7 * 24 * 60 * 60), new BN(90 * 24 * 60 * 60))
+      .accounts({ config: configPda, admin: trader })
+      .rpc();
+
+    // Payouts pass no extra accounts, so a transfer-hook mint is refused up front.
+    const hookMint = new web3.Keypair();
+    const hookMintLen = getMintLen([ExtensionType.TransferHook]);
+    await web3.sendAndConfirmTransaction(
+      connection,
+      new web3.Transaction().add(
+        web3.SystemProgram.createAccount({
+          fromPubkey: trader,
+          newAccountPubkey: hookMint.publicKey,
+          space: hookMintLen,
+          lamports: await connection.getMinimumBalanceForRentExemption(hookMintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferHookInstruction(
+          hookMint.publicKey,
+          trader,
+          program.programId,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(hookMint.publicKey, 6, trader, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [payer.payer, hookMint]
+    );
+    const [hookRewardVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_vault"), hookMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const [hookStakingVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("staking_vault"), hookMint.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initRewardVault()
+      .accounts({
+        rewardVault: hookRewardVault,
+        rewardMint: hookMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        config: configPda,
+        admin: trader,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .initStakingVault()
+      .accounts({
+        stakingVault: hookStakingVault,
+        mint: hookMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        config: configPda,
+        admin: trader,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    try {
+      await program.methods
+        .distributeRewards(new BN(1))
+        .accounts({
+          leaderboard: board.publicKey,
+          config: configPda,
+          rewardVault: hookRewardVault,
+          rewardMint: hookMint.publicKey,
+          vaultAuthority: vaultAuthorityPda,
+          stakingVault: hookStakingVault,
+          swapEscrow: web3.PublicKey.default,
+          treasury: null,
+          rankingSnapshot: snapshot,
+          emissionSchedule: null,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("a transfer-hook reward mint should be rejected");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "TransferHookUnsupported");
+    }
+  });
+
+  it("Flags a ranking during the fraud-proof window", async () => {
+    const setDelayTx = await program.methods
+      .setDistributionDelay(new BN(3600))
+      .accounts({
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+    await program.provider.connection.confirmTransaction(setDelayTx);
+
+    const rankingFlag = new web3.Keypair();
+    const [flagBondVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("flag_bond"), rankingFlag.publicKey.toBuffer()],
+      program.programId
+    );
+    const txHash = await program.methods
+      .flagRanking(Array(32).fill(1), new BN(100))
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        rankingFlag: rankingFlag.publicKey,
+        flaggerTokenAccount: traderTokenAccount.publicKey,
+        mint: t2eMint.publicKey,
+        rewardVault,
+        bondVault: flagBondVault,
+        vaultAuthority: vaultAuthorityPda,
+        flagger: program.provider.publicKey,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: traderStatsPda, isWritable: false, isSigner: false },
+      ])
+      .signers([rankingFlag])
+      .rpc();
+
+    console.log(`✅ Ranking flagged: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    let leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    assert(leaderboard.pendingFlags === 1);
+
+    const resolveTx = await program.methods
+      .resolveRankingFlag(false)
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        rankingFlag: rankingFlag.publicKey,
+        config: configPda,
+        admin: program.provider.publicKey,
+        flaggerTokenAccount: traderTokenAccount.publicKey,
+        bondVault: flagBondVault,
+        mint: t2eMint.publicKey,
+        vaultAuthority: vaultAuthorityPda,
+        rewardVault,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+      })
+      .rpc();
+
+    console.log(`✅ Ranking flag dismissed: ${resolveTx}`);
+    await program.provider.connection.confirmTransaction(resolveTx);
+
+    leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    assert(leaderboard.pendingFlags === 0);
+    assert(leaderboard.len > 0);
+  });
+
+  it("Opens a score dispute with a bond", async () => {
+    const scoreDispute = new web3.Keypair();
+    const [bondVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("dispute_bond"), scoreDispute.publicKey.toBuffer()],
+      program.programId
+    );
+    const [vaultAuthority] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_authority")],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .openScoreDispute(0, new BN(100))
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        traderStats: traderStatsPda,
+        scoreDispute: scoreDispute.publicKey,
+        challengerTokenAccount: traderTokenAccount.publicKey,
+        mint: t2eMint.publicKey,
+        rewardVault,
+        bondVault,
+        vaultAuthority,
+        challenger: program.provider.publicKey,
+        tokenProgram: web3.PublicKey.default,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([scoreDispute])
+      .rpc();
+
+    console.log(`✅ Score dispute opened: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const dispute = await program.account.scoreDispute.fetch(scoreDispute.publicKey);
+    assert(dispute.bondAmount.eq(new BN(100)));
+    assert(dispute.resolved === false);
+
+    const leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    assert(leaderboard.pendingDisputes === 1);
+  });
+
+  it("Rejects a score dispute bonded in a mint without a reward vault", async () => {
+    const connection = program.provider.connection;
+    const payer = (program.provider as anchor.AnchorProvider).wallet as anchor.Wallet;
+    const junkMint = await createMint(connection, payer.payer, payer.publicKey, null, 0);
+    const junkAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer.payer,
+      junkMint,
+      payer.publicKey
+    );
+    await mintTo(connection, payer.payer, junkMint, junkAccount.address, payer.publicKey, 1_000);
+    const [junkRewardVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_vault"), junkMint.toBuffer()],
+      program.programId
+    );
+
+    const scoreDispute = new web3.Keypair();
+    const [bondVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("dispute_bond"), scoreDispute.publicKey.toBuffer()],
+      program.programId
+    );
+    try {
+      await program.methods
+        .openScoreDispute(0, new BN(100))
+        .accounts({
+          leaderboard: leaderboardAccount.publicKey,
+          config: configPda,
+          traderStats: traderStatsPda,
+          scoreDispute: scoreDispute.publicKey,
+          challengerTokenAccount: junkAccount.address,
+          mint: junkMint,
+          rewardVault: junkRewardVault,
+          bondVault,
+          vaultAuthority: vaultAuthorityPda,
+          challenger: program.provider.publicKey,
+          tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([scoreDispute])
+        .rpc();
+      assert.fail("a bond in a mint without a reward vault should be rejected");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "AccountNotInitialized");
+    }
+  });
+
+  it("Allows traders to stake tokens", async () => {
+    const stakeAmount = new BN(1000);
+
+    const txHash = await program.methods
+      .stakeTokens(stakeAmount, { none: {} })
+      .accounts({
+        traderStats: traderStatsPda,
+        stakePosition: await nextStakePosition(),
+        traderTokenAccount: traderTokenAccount.publicKey,
+        stakingVault,
+        vaultAuthority: vaultAuthorityPda,
+        mint: t2eMint.publicKey,
+        config: configPda,
+        trader: program.provider.publicKey,
+        tokenProgram: web3.PublicKey.default, // FIXED: Removed 'spl' reference
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Tokens staked: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    // The trader's own token account cannot stand in for the vault.
+    try {
+      await program.methods
+        .stakeTokens(stakeAmount, { none: {} })
+        .accounts({
+          traderStats: traderStatsPda,
+          stakePosition: await nextStakePosition(),
+          traderTokenAccount: traderTokenAccount.publicKey,
+          stakingVault: traderTokenAccount.publicKey,
+          vaultAuthority: vaultAuthorityPda,
+          mint: t2eMint.publicKey,
+          config: configPda,
+          trader: program.provider.publicKey,
+          tokenProgram: web3.PublicKey.default,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("a trader-owned account should not be accepted as the vault");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "ConstraintSeeds");
+    }
+
+    const traderStats = await program.account.traderStats.fetch(
+      traderStatsPda
+    );
+    assert(traderStats.stakedAmount.eq(stakeAmount));
+  });
+
+  it("Locks staked tokens in a stake position", async () => {
+    const stakePosition = await nextStakePosition();
+    const txHash = await program.methods
+      .stakeTokens(new BN(100), { days30: {} })
+      .accounts({
+        traderStats: traderStatsPda,
+        stakePosition,
+        traderTokenAccount: traderTokenAccount.publicKey,
+        stakingVault,
+        vaultAuthority: vaultAuthorityPda,
+        mint: t2eMint.publicKey,
+        config: configPda,
+        trader: program.provider.publicKey,
+        tokenProgram: web3.PublicKey.default,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Tokens staked with a 30-day lock: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const position = await program.account.stakePosition.fetch(stakePosition);
+    assert(position.amount.eq(new BN(100)));
+    assert("days30" in position.lock);
+    assert(position.multiplierBps === 12_500);
+    const traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(traderStats.lockBonus.eq(new BN(25)));
+    assert(traderStats.lockedUntil.eq(position.lockedUntil));
+
+    try {
+      await program.methods
+        .requestUnstake(traderStats.stakedAmount)
+        .accounts({
+          traderStats: traderStatsPda,
+          stakePosition,
+          config: configPda,
+          trader: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("locked stake should not be withdrawable");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "StakeLocked");
+    }
+
+    // The unlocked position from the first stake can be unstaked in part.
+    const unlocked = stakePositionAddress(program.provider.publicKey, position.positionId - 1);
+    await program.methods
+      .requestUnstake(new BN(400))
+      .accounts({
+        traderStats: traderStatsPda,
+        stakePosition: unlocked,
+        config: configPda,
+        trader: program.provider.publicKey,
+      })
+      .rpc();
+    const remaining = await program.account.stakePosition.fetch(unlocked);
+    assert(remaining.amount.eq(new BN(600)));
+    const after = await program.account.traderStats.fetch(traderStatsPda);
+    assert(after.stakedAmount.eq(traderStats.stakedAmount.sub(new BN(400))));
+    assert(after.lockBonus.eq(new BN(25)));
+  });
+
+  it("Withholds a penalty when unstaking a locked position early", async () => {
+    const setPenalty = (minBps: number, maxBps: number) =>
+      program.methods
+        .setEarlyUnstakePenalty(minBps, maxBps, false)
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+    await setPenalty(1_000, 3_000);
+
+    const before = await program.account.traderStats.fetch(traderStatsPda);
+    const locked = stakePositionAddress(program.provider.publicKey, before.stakePositions - 1);
+    const txHash = await program.methods
+      .requestUnstake(new BN(100))
+      .accounts({
+        traderStats: traderStatsPda,
+        stakePosition: locked,
+        config: configPda,
+        trader: program.provider.publicKey,
+      })
+      .rpc();
+    await setPenalty(0, 0);
+
+    // The lock has just started, so close to the 30% maximum is withheld.
+    const after = await program.account.traderStats.fetch(traderStatsPda);
+    const penalty = after.unstakePenalty.sub(before.unstakePenalty);
+    assert(penalty.gte(new BN(29)) && penalty.lte(new BN(30)));
+    assert(after.unstakePending.sub(before.unstakePending).eq(new BN(100).sub(penalty)));
+    assert(after.lockBonus.isZero());
+    console.log(`✅ Early unstake penalized: ${txHash}`);
+  });
+
+  it("Stakes on behalf of another trader and keeps withdrawal with the funder", async () => {
+    const trader = web3.Keypair.generate();
+    const [stats] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_stats"), trader.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initTraderStats()
+      .accounts({
+        traderStats: stats,
+        trader: trader.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .preInstructions([
+        web3.SystemProgram.transfer({
+          fromPubkey: program.provider.publicKey,
+          toPubkey: trader.publicKey,
+          lamports: web3.LAMPORTS_PER_SOL / 10,
+        }),
+      ])
+      .signers([trader])
+      .rpc();
+
+    const stakePosition = stakePositionAddress(trader.publicKey, 0);
+    const txHash = await program.methods
+      .stakeFor(trader.publicKey, new BN(200), { none: {} })
+      .accounts({
+        traderStats: stats,
+        stakePosition,
+        funderTokenAccount: traderTokenAccount.publicKey,
+        stakingVault,
+        vaultAuthority: vaultAuthorityPda,
+        mint: t2eMint.publicKey,
+        config: configPda,
+        funder: program.provider.publicKey,
+        tokenProgram: web3.PublicKey.default,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const position = await program.account.stakePosition.fetch(stakePosition);
+    assert(position.funder.equals(program.provider.publicKey));
+    const traderStats = await program.account.traderStats.fetch(stats);
+    assert(traderStats.stakedAmount.eq(position.amount));
+
+    try {
+      await program.methods
+        .requestUnstake(position.amount)
+        .accounts({
+          traderStats: stats,
+          stakePosition,
+          config: configPda,
+          trader: trader.publicKey,
+        })
+        .signers([trader])
+        .rpc();
+      assert.fail("the trader should not withdraw a sponsored position");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "NotPositionFunder");
+    }
+
+    await program.methods
+      .requestSponsoredUnstake(position.amount)
+      .accounts({
+        traderStats: stats,
+        stakePosition,
+        config: configPda,
+        funder: program.provider.publicKey,
+      })
+      .rpc();
+    const requested = await program.account.stakePosition.fetch(stakePosition);
+    assert(requested.amount.isZero());
+    assert(requested.unstakePending.eq(position.amount));
+    console.log(`✅ Tokens staked for another trader: ${txHash}`);
+  });
+
+  it("Calculates fee discounts based on staking", async () => {
+    const txHash = await program.methods
+      .calculateFeeDiscount()
+      .accounts({
+        traderStats: traderStatsPda,
+        config: configPda,
+        trader: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Fee discount calculated: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const traderStats = await program.account.traderStats.fetch(
+      traderStatsPda
+    );
+    assert(traderStats.feeDiscount >= 0 && traderStats.feeDiscount <= 50);
+  });
+
+  it("Applies a custom fee discount tier table", async () => {
+    await program.methods
+      .setFeeDiscountTiers([
+        { minStake: new BN(0), discount: 15 },
+        { minStake: new BN(1_000_000_000), discount: 40 },
+      ])
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+
+    const txHash = await program.methods
+      .calculateFeeDiscount()
+      .accounts({
+        traderStats: traderStatsPda,
+        config: configPda,
+        trader: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Fee tier evaluated: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(traderStats.feeTier === 1);
+    assert(traderStats.feeDiscount === 15);
+
+    try {
+      await program.methods
+        .setFeeDiscountTiers([
+          { minStake: new BN(500), discount: 20 },
+          { minStake: new BN(100), discount: 30 },
+        ])
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+      assert.fail("unordered tiers should be rejected");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "InvalidFeeDiscountTiers");
+    }
+  });
+
+  it("Takes a snapshot of the leaderboard history", async () => {
+    const before = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    const [snapshot] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("snapshot"),
+        leaderboardAccount.publicKey.toBuffer(),
+        before.historyCount.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .snapshotLeaderboard()
+      .accounts({
+        leaderboard: leaderboardAccount.publicKey,
+        snapshot,
+        config: configPda,
+        role: null,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Leaderboard snapshot saved: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const history = await program.account.leaderboardSnapshot.fetch(snapshot);
+    const leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    assert(history.index.eq(before.historyCount));
+    assert(history.tradersRanked === leaderboard.len);
+    assert(history.entries.length === leaderboard.len);
+    assert(leaderboard.historyCount.eq(before.historyCount.add(new BN(1))));
+  });
+
+  it("Closes a leaderboard snapshot and refunds its rent", async () => {
+    const leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    const [snapshot] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("snapshot"),
+        leaderboardAccount.publicKey.toBuffer(),
+        leaderboard.historyCount.subn(1).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .closeLeaderboardSnapshot()
+      .accounts({
+        snapshot,
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Leaderboard snapshot closed: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    assert((await program.provider.connection.getAccountInfo(snapshot)) === null);
+  });
+
+  it("Overwrites the oldest snapshot under a retention window", async () => {
+    await program.methods
+      .setSnapshotRetention(new BN(1))
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+
+    const [slot] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("snapshot"),
+        leaderboardAccount.publicKey.toBuffer(),
+        new BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    for (let i = 0; i < 2; i++) {
+      await program.methods
+        .snapshotLeaderboard()
+        .accounts({
+          leaderboard: leaderboardAccount.publicKey,
+          snapshot: slot,
+          config: configPda,
+          role: null,
+          admin: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    const leaderboard = await program.account.leaderboard.fetch(
+      leaderboardAccount.publicKey
+    );
+    const latest = await program.account.leaderboardSnapshot.fetch(slot);
+    assert(latest.index.eq(leaderboard.historyCount.subn(1)));
+    console.log(`✅ Ring buffer slot holds snapshot ${latest.index.toString()}`);
+
+    try {
+      await program.methods
+        .pruneSnapshots()
+        .accounts({
+          leaderboard: leaderboardAccount.publicKey,
+          config: configPda,
+          admin: program.provider.publicKey,
+        })
+        .remainingAccounts([{ pubkey: slot, isWritable: true, isSigner: false }])
+        .rpc();
+      assert.fail("Pruning a retained snapshot should fail");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "SnapshotNotExpired");
+      console.log("✅ Retained snapshot cannot be pruned");
+    }
+
+    await program.methods
+      .setSnapshotRetention(new BN(0))
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+  });
+
+  it("Rejects closing trader stats that still hold a stake", async () => {
+    try {
+      await program.methods
+        .closeTraderStats()
+        .accounts({
+          traderStats: traderStatsPda,
+          trader: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("staked trader stats should not be closable");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "AccountNotEmpty");
+    }
+  });
+
+  it("Creates an automation schedule for the leaderboard", async () => {
+    const thread = new web3.Keypair();
+    const [automationSchedule] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("automation"), leaderboardAccount.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .initAutomationSchedule(thread.publicKey, new BN(3600), new BN(0))
+      .accounts({
+        automationSchedule,
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Automation schedule created: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const schedule = await program.account.automationSchedule.fetch(automationSchedule);
+    assert(schedule.thread.equals(thread.publicKey));
+    assert(schedule.updateInterval.eq(new BN(3600)));
+    assert(schedule.snapshotInterval.eq(new BN(0)));
+  });
+
+  describe("Scoped pause flags", () => {
+    const PAUSE_TRADING = 1;
+    const PAUSE_STAKING = 2;
+    const PAUSE_REWARDS = 4;
+
+    const setPauseFlags = async (flags: number) => {
+      const txHash = await program.methods
+        .setPauseFlags(flags)
+        .accounts({
+          config: configPda,
+          admin: program.provider.publicKey,
+        })
+        .rpc();
+      await program.provider.connection.confirmTransaction(txHash);
+    };
+
+    const expectPaused = async (call: Promise<string>) => {
+      try {
+        await call;
+        assert.fail("instruction should have been rejected while paused");
+      } catch (err) {
+        assert(err.error?.errorCode?.code === "EmergencyPaused");
+      }
+    };
+
+    afterEach(async () => {
+      await setPauseFlags(0);
+    });
+
+    it("record_trade respects the trading flag", async () => {
+      await setPauseFlags(PAUSE_TRADING);
+      await expectPaused(
+        program.methods
+          .recordTrade(new BN(1), new BN(1), new BN(0), { spot: {} }, null, null)
+          .accounts({
+            traderStats: traderStatsPda,
+            pairVolume: null,
+            config: configPda,
+            season: null,
+            seasonStats: null,
+            marketStats: null,
+            registeredMarket: null,
+            leaderboard: null,
+            delegate: null,
+            trader: program.provider.publicKey,
+          })
+          .rpc()
+      );
+    });
+
+    it("stake_tokens respects the staking flag", async () => {
+      await setPauseFlags(PAUSE_STAKING);
+      await expectPaused(
+        program.methods
+          .stakeTokens(new BN(1), { none: {} })
+          .accounts({
+            traderStats: traderStatsPda,
+            stakePosition: await nextStakePosition(),
+            traderTokenAccount: traderTokenAccount.publicKey,
+            stakingVault,
+            vaultAuthority: vaultAuthorityPda,
+            mint: t2eMint.publicKey,
+            config: configPda,
+            trader: program.provider.publicKey,
+            tokenProgram: web3.PublicKey.default,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .rpc()
+      );
+    });
+
+    it("stake_for respects the staking flag", async () => {
+      await setPauseFlags(PAUSE_STAKING);
+      await expectPaused(
+        program.methods
+          .stakeFor(program.provider.publicKey, new BN(1), { none: {} })
+          .accounts({
+            traderStats: traderStatsPda,
+            stakePosition: await nextStakePosition(),
+            funderTokenAccount: traderTokenAccount.publicKey,
+            stakingVault,
+            vaultAuthority: vaultAuthorityPda,
+            mint: t2eMint.publicKey,
+            config: configPda,
+            funder: program.provider.publicKey,
+            tokenProgram: web3.PublicKey.default,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .rpc()
+      );
+    });
+
+    it("unstake_tokens respects the staking flag", async () => {
+      await setPauseFlags(PAUSE_STAKING);
+      await expectPaused(
+        program.methods
+          .unstakeTokens()
+          .accounts({
+            traderStats: traderStatsPda,
+            traderTokenAccount: traderTokenAccount.publicKey,
+            stakingVault,
+            vaultAuthority: vaultAuthorityPda,
+            penaltyDestination: null,
+            mint: t2eMint.publicKey,
+            config: configPda,
+            trader: program.provider.publicKey,
+            tokenProgram: web3.PublicKey.default,
+          })
+          .rpc()
+      );
+    });
+
+    it("claim_staking_rewards respects the staking flag", async () => {
+      const [stakingRewardVault] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("staking_rewards"), t2eMint.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initStakingRewardVault()
+        .accounts({
+          stakingRewardVault,
+          mint: t2eMint.publicKey,
+          vaultAuthority: vaultAuthorityPda,
+          config: configPda,
+          admin: program.provider.publicKey,
+          tokenProgram: web3.PublicKey.default,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      await setPauseFlags(PAUSE_STAKING);
+      await expectPaused(
+        program.methods
+          .claimStakingRewards(false)
+          .accounts({
+            traderStats: traderStatsPda,
+            stakingRewardVault,
+            traderTokenAccount: traderTokenAccount.publicKey,
+            stakingVault,
+            mint: t2eMint.publicKey,
+            vaultAuthority: vaultAuthorityPda,
+            config: configPda,
+            trader: program.provider.publicKey,
+            tokenProgram: web3.PublicKey.default,
+          })
+          .rpc()
+      );
+    });
+
+    const claimRewards = (compound: boolean) =>
+      program.methods
+        .claimRewards(compound)
+        .accounts({
+          ...token2022Claim,
+          traderStats: traderStatsPda,
+          vaultAuthority: vaultAuthorityPda,
+          swapEscrow: web3.PublicKey.default,
+          config: configPda,
+          trader: program.provider.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+    it("claim_rewards respects the rewards flag", async () => {
+      await setPauseFlags(PAUSE_REWARDS);
+      await expectPaused(claimRewards(false));
+    });
+
+    it("compounding claim_rewards respects the staking flag", async () => {
+      await setPauseFlags(PAUSE_STAKING);
+      await expectPaused(claimRewards(true));
+    });
+
+    it("distribute_rewards respects the rewards flag", async () => {
+      await setPauseFlags(PAUSE_REWARDS);
+      await expectPaused(
+        program.methods
+          .distributeRewards(new BN(1))
+          .accounts({
+            leaderboard: leaderboardAccount.publicKey,
+            config: configPda,
+            rewardVault,
+            rewardMint: t2eMint.publicKey,
+            vaultAuthority: vaultAuthorityPda,
+            stakingVault,
+            swapEscrow: web3.PublicKey.default,
+            treasury: null,
+            rankingSnapshot,
+            emissionSchedule: null,
+            tokenProgram: web3.PublicKey.default,
+          })
+          .rpc()
+      );
+    });
+
+    it("emits PauseToggled with the new flags", async () => {
+      let event = null;
+      const listener = program.addEventListener("pauseToggled", (e) => {
+        event = e;
+      });
+      await setPauseFlags(PAUSE_TRADING | PAUSE_STAKING);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      assert(event !== null);
+      assert(event.pauseFlags === (PAUSE_TRADING | PAUSE_STAKING));
+      assert(event.leaderboard === null);
+      console.log("✅ PauseToggled emitted");
+    });
+  });
+
+  it("Queues and cancels an emergency vault withdrawal", async () => {
+    const [emergencyWithdrawal] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("emergency_withdrawal"), rewardVault.toBuffer()],
+      program.programId
+    );
+
+    const queueTx = await program.methods
+      .queueEmergencyWithdraw(new BN(100))
+      .accounts({
+        emergencyWithdrawal,
+        rewardVault,
+        destination: traderTokenAccount.publicKey,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Emergency withdrawal queued: ${queueTx}`);
+    await program.provider.connection.confirmTransaction(queueTx);
+
+    const withdrawal = await program.account.emergencyWithdrawal.fetch(
+      emergencyWithdrawal
+    );
+    assert(withdrawal.executableAt.sub(withdrawal.queuedAt).eq(new BN(3 * 24 * 60 * 60)));
+
+    const cancelTx = await program.methods
+      .cancelEmergencyWithdraw()
+      .accounts({
+        emergencyWithdrawal,
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Emergency withdrawal cancelled: ${cancelTx}`);
+    await program.provider.connection.confirmTransaction(cancelTx);
+
+    const closed = await program.provider.connection.getAccountInfo(emergencyWithdrawal);
+    assert(closed === null);
+  });
+
+  it("Creates and cancels an escrowed competition", async () => {
+    const competitionId = new BN(1);
+    const [competition] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("competition"),
+        program.provider.publicKey.toBuffer(),
+        competitionId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [prizeVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("competition_vault"), competition.toBuffer()],
+      program.programId
+    );
+    const now = Math.floor(Date.now() / 1000);
+
+    const createTx = await program.methods
+      .createCompetition(competitionId, new BN(now + 3600), new BN(now + 7200), new BN(500))
+      .accounts({
+        competition,
+        prizeVault,
+        prizeMint: t2eMint.publicKey,
+        sponsorTokenAccount: traderTokenAccount.publicKey,
+        sponsor: program.provider.publicKey,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Competition created: ${createTx}`);
+    await program.provider.connection.confirmTransaction(createTx);
+
+    const cancelTx = await program.methods
+      .cancelCompetition()
+      .accounts({
+        competition,
+        prizeVault,
+        sponsorTokenAccount: traderTokenAccount.publicKey,
+        sponsor: program.provider.publicKey,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+      })
+      .rpc();
+
+    console.log(`✅ Competition cancelled and refunded: ${cancelTx}`);
+    await program.provider.connection.confirmTransaction(cancelTx);
+
+    const account = await program.account.competition.fetch(competition);
+    assert("cancelled" in account.status);
+  });
+
+  it("Registers the leaderboard in the global registry", async () => {
+    const [registry] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("leaderboard_registry")],
+      program.programId
+    );
+    const initTx = await program.methods
+      .initLeaderboardRegistry()
+      .accounts({
+        registry,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.provider.connection.confirmTransaction(initTx);
+
+    const name = Buffer.alloc(32);
+    name.write("global-weekly");
+    const txHash = await program.methods
+      .registerLeaderboard([...name], web3.PublicKey.default, new BN(7 * 86400))
+      .accounts({
+        registry,
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        admin: program.provider.publicKey,
+      })
+      .rpc();
+
+    console.log(`✅ Leaderboard registered: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const account = await program.account.leaderboardRegistry.fetch(registry);
+    assert(account.entries.length === 1);
+    assert(account.entries[0].leaderboard.equals(leaderboardAccount.publicKey));
+  });
+
+  it("Posts a Merkle reward root", async () => {
+    const epoch = new BN(1);
+    const amount = new BN(1000);
+    // A single-leaf tree: the root is the leaf itself.
+    const root = createHash("sha256")
+      .update(Buffer.from([0]))
+      .update(program.provider.publicKey.toBuffer())
+      .update(amount.toArrayLike(Buffer, "le", 8))
+      .digest();
+    const [rewardRoot] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("reward_root"),
+        leaderboardAccount.publicKey.toBuffer(),
+        epoch.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [claimVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("claim_vault"), leaderboardAccount.publicKey.toBuffer()],
+      program.programId
+    );
+    const [vaultAuthority] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_authority")],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .postRewardRoot(epoch, [...root], amount)
+      .accounts({
+        rewardRoot,
+        leaderboard: leaderboardAccount.publicKey,
+        config: configPda,
+        admin: program.provider.publicKey,
+        rewardVault,
+        payer: program.provider.publicKey,
+        claimVault,
+        rewardMint: t2eMint.publicKey,
+        vaultAuthority,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Reward root posted: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const account = await program.account.rewardRoot.fetch(rewardRoot);
+    assert(Buffer.from(account.root).equals(root));
+    assert(account.totalAmount.eq(amount));
+  });
+
+  it("Starts a season and opens the trader's season stats", async () => {
+    const index = 1;
+    const now = Math.floor(Date.now() / 1000);
+    const [season] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("season"), new BN(index).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    const [seasonStats] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("trader_season_stats"),
+        season.toBuffer(),
+        program.provider.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .startSeason(index, new BN(now + 3600), new BN(now + 30 * 86400))
+      .accounts({
+        season,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`✅ Season started: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    await program.methods
+      .initTraderSeasonStats()
+      .accounts({
+        seasonStats,
+        season,
+        trader: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.activeSeason === index);
+    const stats = await program.account.traderSeasonStats.fetch(seasonStats);
+    assert(stats.season === index);
+    assert(stats.totalVolume.eq(new BN(0)));
+
+    try {
+      await program.methods
+        .endSeason()
+        .accounts({
+          season,
+          config: configPda,
+          admin: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("a season should not end before its end timestamp");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "SeasonNotOver");
+    }
+  });
+
+  it("Rejects rolling trader stats over before the season ends", async () => {
+    const [season] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("season"), new BN(1).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    const [archive] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("trader_season_archive"),
+        season.toBuffer(),
+        program.provider.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .resetTraderStats()
+        .accounts({
+          archive,
+          traderStats: traderStatsPda,
+          season,
+          payer: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("stats should only roll over once the season has ended");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidSeasonParams");
+    }
+  });
+
+  it("Rejects slashing while no treasury is configured", async () => {
+    const traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    const [slashRecord] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("slash_record"),
+        program.provider.publicKey.toBuffer(),
+        new BN(traderStats.slashCount).toArrayLike(Buffer, "le", 4),
+      ],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .slashTrader(5_000, Array(32).fill(0))
+        .accounts({
+          slashRecord,
+          traderStats: traderStatsPda,
+          seasonStats: null,
+          stakingVault,
+          treasury: traderTokenAccount.publicKey,
+          mint: t2eMint.publicKey,
+          adminSet: null,
+          adminAction: null,
+          config: configPda,
+          admin: program.provider.publicKey,
+          tokenProgram: web3.PublicKey.default,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("slashed stake needs a treasury to go to");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "TreasuryNotProvided");
+    }
+  });
+
+  it("Slashes loose stake first and leaves every stake position withdrawable", async () => {
+    const connection = program.provider.connection;
+    const payer = (program.provider as anchor.AnchorProvider).wallet as anchor.Wallet;
+    const trader = web3.Keypair.generate();
+    const [stats] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_stats"), trader.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initTraderStats()
+      .accounts({
+        traderStats: stats,
+        trader: trader.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .preInstructions([
+        web3.SystemProgram.transfer({
+          fromPubkey: program.provider.publicKey,
+          toPubkey: trader.publicKey,
+          lamports: web3.LAMPORTS_PER_SOL / 10,
+        }),
+      ])
+      .signers([trader])
+      .rpc();
+    const { activeSeason } = await program.account.config.fetch(configPda);
+    const [season] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("season"), new BN(activeSeason).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    const [seasonStats] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_season_stats"), season.toBuffer(), trader.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initTraderSeasonStats()
+      .accounts({
+        seasonStats,
+        season,
+        trader: trader.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+
+    const traderAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer.payer,
+      t2eMint.publicKey,
+      trader.publicKey
+    );
+    await transfer(
+      connection,
+      payer.payer,
+      traderTokenAccount.publicKey,
+      traderAccount.address,
+      payer.publicKey,
+      200
+    );
+    const stakeOwn = (positionId: number) =>
+      program.methods
+        .stakeTokens(new BN(100), { none: {} })
+        .accounts({
+          traderStats: stats,
+          stakePosition: stakePositionAddress(trader.publicKey, positionId),
+          traderTokenAccount: traderAccount.address,
+          stakingVault,
+          vaultAuthority: vaultAuthorityPda,
+          mint: t2eMint.publicKey,
+          config: configPda,
+          trader: trader.publicKey,
+          tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([trader])
+        .rpc();
+
+    // Position 0 is closed so its 100 stay staked outside positions;
+    // position 1 is sponsored with 200 and position 2 holds 100.
+    await stakeOwn(0);
+    await program.methods
+      .closeStakePosition()
+      .accounts({
+        stakePosition: stakePositionAddress(trader.publicKey, 0),
+        traderStats: stats,
+        trader: trader.publicKey,
+      })
+      .signers([trader])
+      .rpc();
+    const sponsored = stakePositionAddress(trader.publicKey, 1);
+    await program.methods
+      .stakeFor(trader.publicKey, new BN(200), { none: {} })
+      .accounts({
+        traderStats: stats,
+        stakePosition: sponsored,
+        funderTokenAccount: traderTokenAccount.publicKey,
+        stakingVault,
+        vaultAuthority: vaultAuthorityPda,
+        mint: t2eMint.publicKey,
+        config: configPda,
+        funder: program.provider.publicKey,
+        tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    const own = stakePositionAddress(trader.publicKey, 2);
+    await stakeOwn(2);
+
+    const { treasury: previousTreasury } = await program.account.config.fetch(configPda);
+    const setTreasury = (treasury: web3.PublicKey) =>
+      program.methods
+        .setTreasury(treasury)
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+    await setTreasury(traderTokenAccount.publicKey);
+    const [slashRecord] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("slash_record"), trader.publicKey.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    const slash = (positions: web3.PublicKey[]) =>
+      program.methods
+        .slashTrader(5_000, Array(32).fill(0))
+        .accounts({
+          slashRecord,
+          traderStats: stats,
+          seasonStats,
+          stakingVault,
+          treasury: traderTokenAccount.publicKey,
+          mint: t2eMint.publicKey,
+          adminSet: null,
+          adminAction: null,
+          config: configPda,
+          admin: program.provider.publicKey,
+          tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          positions.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc();
+    try {
+      // Half of 400 reaches into the positions, so all of them are needed.
+      await slash([sponsored]);
+      assert.fail("a slash into positions needs every open position");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "StakePositionsIncomplete");
+    }
+    await slash([sponsored, own]);
+    await setTreasury(previousTreasury);
+
+    // 100 come from the loose stake and 100 from the positions, pro rata.
+    const slashed = await program.account.traderStats.fetch(stats);
+    assert(slashed.stakedAmount.eq(new BN(200)));
+    assert(slashed.positionStake.eq(new BN(200)));
+    const sponsoredLeft = (await program.account.stakePosition.fetch(sponsored)).amount;
+    const ownLeft = (await program.account.stakePosition.fetch(own)).amount;
+    assert(sponsoredLeft.eq(new BN(133)));
+    assert(ownLeft.eq(new BN(67)));
+
+    await program.methods
+      .requestSponsoredUnstake(sponsoredLeft)
+      .accounts({
+        traderStats: stats,
+        stakePosition: sponsored,
+        config: configPda,
+        funder: program.provider.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .requestUnstake(ownLeft)
+      .accounts({
+        traderStats: stats,
+        stakePosition: own,
+        config: configPda,
+        trader: trader.publicKey,
+      })
+      .signers([trader])
+      .rpc();
+    const unstaked = await program.account.traderStats.fetch(stats);
+    assert(unstaked.stakedAmount.isZero());
+    assert(unstaked.positionStake.isZero());
+    console.log("✅ Every stake position withdrawable after a slash");
+  });
+
+  it("Rejects a treasury burn without a program-owned treasury", async () => {
+    const [treasury] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), t2eMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const [vaultAuthority] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_authority")],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .burnTreasury()
+        .accounts({
+          treasury,
+          mint: t2eMint.publicKey,
+          vaultAuthority,
+          config: configPda,
+          admin: program.provider.publicKey,
+          tokenProgram: web3.PublicKey.default,
+        })
+        .rpc();
+      assert.fail("burning needs config.treasury to be the treasury PDA");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "TreasuryNotProvided");
+      console.log("✅ Treasury burn requires the program treasury");
+    }
+  });
+
+  it("Accrues staking yield at the configured APR", async () => {
+    try {
+      await program.methods
+        .setStakingApr(10_001)
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+      assert.fail("an APR above 100% should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidBps");
+    }
+
+    await program.methods
+      .setStakingApr(1_000)
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    const txHash = await program.methods
+      .accrueStakingRewards()
+      .accounts({ traderStats: traderStatsPda, config: configPda })
+      .rpc();
+    console.log(`✅ Staking yield accrued: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const config = await program.account.config.fetch(configPda);
+    assert(config.stakingAprBps === 1_000);
+    const traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(traderStats.stakingIndexPaid.gte(config.stakingRewardIndex));
+  });
+
+  it("Opens a governance proposal that executes only after voting", async () => {
+    try {
+      await program.methods
+        .setGovernanceParams(new BN(365 * 86400), new BN(0), new BN(0))
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+      assert.fail("a voting period longer than the unstake cooldown should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidGovernanceParams");
+    }
+    await program.methods
+      .setGovernanceParams(new BN(3600), new BN(0), new BN(0))
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+
+    const config = await program.account.config.fetch(configPda);
+    const [proposal] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), config.proposalCount.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const txHash = await program.methods
+      .createProposal({ setPauseFlags: { 0: 0 } })
+      .accounts({
+        proposal,
+        proposerStats: traderStatsPda,
+        config: configPda,
+        proposer: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    console.log(`✅ Proposal created: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+
+    const account = await program.account.proposal.fetch(proposal);
+    assert(account.id.eq(config.proposalCount));
+    assert(!account.executed);
+
+    try {
+      await program.methods
+        .executeProposal()
+        .accounts({ proposal, config: configPda })
+        .rpc();
+      assert.fail("a proposal should not execute while voting is open");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "VotingNotEnded");
+    }
+  });
+
+  it("Timelocks queued config changes until they are cancelled or due", async () => {
+    const config = await program.account.config.fetch(configPda);
+    const [configChange] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config_change"), config.configChangeCount.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .queueConfigChange({ setHalvingPeriod: { 0: new BN(86400) } })
+      .accounts({
+        configChange,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    const change = await program.account.pendingConfigChange.fetch(configChange);
+    assert(change.executableAt.sub(change.queuedAt).eq(new BN(48 * 3600)));
+
+    try {
+      await program.methods
+        .executeConfigChange()
+        .accounts({ configChange, config: configPda, admin: program.provider.publicKey })
+        .rpc();
+      assert.fail("a config change should not apply before its delay");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "TimelockNotExpired");
+    }
+
+    const txHash = await program.methods
+      .cancelConfigChange()
+      .accounts({ configChange, config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    console.log(`✅ Config change cancelled: ${txHash}`);
+  });
+
+  it("Rejects an admin set whose threshold exceeds its size", async () => {
+    const [adminSet] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("admin_set")],
+      program.programId
+    );
+    try {
+      await program.methods
+        .initAdminSet([program.provider.publicKey, web3.Keypair.generate().publicKey], 3)
+        .accounts({
+          adminSet,
+          config: configPda,
+          admin: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("a 3-of-2 admin set should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidAdminSet");
+      console.log("✅ Admin set threshold validated");
+    }
+  });
+
+  it("Lets a granted operator post rankings until the role is revoked", async () => {
+    const operator = web3.Keypair.generate();
+    const [role] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("role"), operator.publicKey.toBuffer()],
+      program.programId
+    );
+    const grant = (roles: number) =>
+      program.methods
+        .setRoles(operator.publicKey, roles)
+        .accounts({
+          role,
+          config: configPda,
+          admin: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    try {
+      await grant(0);
+      assert.fail("an empty grant should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidRoles");
+    }
+    await grant(1);
+    const account = await program.account.role.fetch(role);
+    assert(account.grantee.equals(operator.publicKey));
+    assert(account.roles === 1);
+
+    const txHash = await program.methods
+      .revokeRoles()
+      .accounts({ role, config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    console.log(`✅ Operator role granted and revoked: ${txHash}`);
+    assert((await program.provider.connection.getAccountInfo(role)) === null);
+  });
+
+  it("Rejects dust trades below the minimum trade volume", async () => {
+    const setMinTradeVolume = (minTradeVolume: number) =>
+      program.methods
+        .setMinTradeVolume(new BN(minTradeVolume))
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+
+    await setMinTradeVolume(1_000);
+    try {
+      await program.methods
+        .recordTrade(new BN(1), new BN(1), new BN(0), { spot: {} }, null, null)
+        .accounts({
+          traderStats: traderStatsPda,
+          pairVolume: null,
+          config: configPda,
+          season: null,
+          seasonStats: null,
+          marketStats: null,
+          registeredMarket: null,
+          leaderboard: null,
+          delegate: null,
+          trader: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("a dust trade should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "TradeBelowMinimumVolume");
+    }
+
+    const txHash = await setMinTradeVolume(0);
+    console.log(`✅ Minimum trade volume enforced and reset: ${txHash}`);
+  });
+
+  it("Configures a sliding-window trade rate limit", async () => {
+    const setTradeRateWindow = (maxTrades: number, window: number) =>
+      program.methods
+        .setTradeRateWindow(maxTrades, new BN(window))
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+
+    try {
+      await setTradeRateWindow(17, 300);
+      assert.fail("a window larger than the timestamp ring should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidRateWindow");
+    }
+
+    await setTradeRateWindow(5, 300);
+    let config = await program.account.config.fetch(configPda);
+    assert(config.rateWindowTrades === 5);
+    assert(config.rateWindow.toNumber() === 300);
+
+    const txHash = await setTradeRateWindow(0, 0);
+    config = await program.account.config.fetch(configPda);
+    assert(config.rateWindowTrades === 0);
+    console.log(`✅ Trade rate window configured and reset: ${txHash}`);
+  });
+
+  it("Leaves traders below the ranking stake minimum unranked", async () => {
+    const board = new web3.Keypair();
+    await program.methods
+      .initialize(web3.PublicKey.default)
+      .accounts({ leaderboard: board.publicKey })
+      .preInstructions([
+        await program.account.leaderboard.createInstruction(board, leaderboardSpace(4)),
+      ])
+      .signers([board])
+      .rpc();
+    const setMinStakeForRanking = (minStake: number) =>
+      program.methods
+        .setMinStakeForRanking(new BN(minStake))
+        .accounts({ config: configPda, admin: program.provider.publicKey })
+        .rpc();
+
+    const entry = (trader: web3.PublicKey, stakedAmount: number) => ({
+      trader,
+      totalVolume: new BN(5000),
+      averageExecutionTime: new BN(250),
+      pnl: new BN(200),
+      stakedAmount: new BN(stakedAmount),
+      reputation: new BN(0),
+      firstTrade: new BN(1_000),
+      lastTrade: new BN(0),
+      marketMaker: false,
+      tradeCount: new BN(0),
+      tradePnlSum: new BN(0),
+      tradePnlSumSquares: new BN(0),
+      winningTrades: new BN(0),
+      losingTrades: new BN(0),
+      streakDays: 0,
+    });
+    const staker = web3.Keypair.generate().publicKey;
+    const sybil = web3.Keypair.generate().publicKey;
+
+    await setMinStakeForRanking(1_000);
+    const txHash = await program.methods
+      .updateLeaderboard([entry(staker, 1_000), entry(sybil, 0)])
+      .accounts({
+        leaderboard: board.publicKey,
+        config: configPda,
+        role: null,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    await setMinStakeForRanking(0);
+
+    const entries = await fetchBoardEntries(board.publicKey);
+    assert(entries.length === 1);
+    assert(entries[0].trader.equals(staker));
+    console.log(`✅ Understaked trader excluded from ranking: ${txHash}`);
+  });
+
+  it("Bans and unbans a trader", async () => {
+    const banAccounts = {
+      traderStats: traderStatsPda,
+      config: configPda,
+      admin: program.provider.publicKey,
+    };
+    const [vestingSchedule] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("vesting"),
+        leaderboardAccount.publicKey.toBuffer(),
+        program.provider.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const [claimVault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("claim_vault"), leaderboardAccount.publicKey.toBuffer()],
+      program.programId
+    );
+    const clawback = () =>
+      program.methods
+        .clawbackVesting(false)
+        .accounts({
+          vestingSchedule,
+          traderStats: traderStatsPda,
+          claimVault,
+          rewardMint: t2eMint.publicKey,
+          destination: rewardVault,
+          vaultAuthority: vaultAuthorityPda,
+          adminSet: null,
+          adminAction: null,
+          config: configPda,
+          admin: program.provider.publicKey,
+          tokenProgram: new web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        })
+        .rpc();
+    try {
+      await clawback();
+      assert.fail("vesting of a trader in good standing should not be clawed back");
+    } catch (err) {
+      assert.equal(err.error?.errorCode?.code, "TraderNotBanned");
+    }
+
+    await program.methods.banTrader().accounts(banAccounts).rpc();
+    let traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(traderStats.banned);
+
+    // Clawback cancels the rest of the schedule; vested amounts stay releasable.
+    const before = await program.account.vestingSchedule.fetch(vestingSchedule);
+    await clawback();
+    const schedule = await program.account.vestingSchedule.fetch(vestingSchedule);
+    assert(schedule.total.lte(before.total));
+    assert(schedule.duration.eq(new BN(0)));
+    assert(schedule.released.eq(before.released));
+
+    try {
+      await program.methods
+        .recordTrade(new BN(5_000), new BN(100), new BN(0), { spot: {} }, null, null)
+        .accounts({
+          traderStats: traderStatsPda,
+          pairVolume: null,
+          config: configPda,
+          season: null,
+          seasonStats: null,
+          marketStats: null,
+          registeredMarket: null,
+          leaderboard: null,
+          delegate: null,
+          trader: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("a banned trader should not be able to record trades");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "TraderBanned");
+    }
+
+    const txHash = await program.methods.unbanTrader().accounts(banAccounts).rpc();
+    traderStats = await program.account.traderStats.fetch(traderStatsPda);
+    assert(!traderStats.banned);
+    console.log(`✅ Trader banned and unbanned: ${txHash}`);
+  });
+
+  it("Requires trades on registered markets once the registry is in use", async () => {
+    const market = web3.Keypair.generate().publicKey;
+    const [registeredMarket] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), market.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerMarket(market, 20_000)
+      .accounts({
+        registeredMarket,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    const account = await program.account.market.fetch(registeredMarket);
+    assert(account.volumeMultiplierBps === 20_000);
+
+    try {
+      await program.methods
+        .recordTrade(new BN(5_000), new BN(100), new BN(0), { spot: {} }, null, null)
+        .accounts({
+          traderStats: traderStatsPda,
+          pairVolume: null,
+          config: configPda,
+          season: null,
+          seasonStats: null,
+          marketStats: null,
+          registeredMarket: null,
+          leaderboard: null,
+          delegate: null,
+          trader: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("a trade without a registered market should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "MarketNotRegistered");
+    }
+
+    const txHash = await program.methods
+      .deregisterMarket()
+      .accounts({ registeredMarket, config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    const config = await program.account.config.fetch(configPda);
+    assert(config.registeredMarkets === 0);
+    console.log(`✅ Market registered and deregistered: ${txHash}`);
+  });
+
+  it("Keeps a live-ranked leaderboard sorted on each trade", async () => {
+    const board = new web3.Keypair();
+    await program.methods
+      .initialize(web3.PublicKey.default)
+      .accounts({ leaderboard: board.publicKey })
+      .preInstructions([
+        await program.account.leaderboard.createInstruction(board, leaderboardSpace(4)),
+      ])
+      .signers([board])
+      .rpc();
+
+    const trader = web3.Keypair.generate();
+    const [stats] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_stats"), trader.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initTraderStats()
+      .accounts({
+        traderStats: stats,
+        trader: trader.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .preInstructions([
+        web3.SystemProgram.transfer({
+          fromPubkey: program.provider.publicKey,
+          toPubkey: trader.publicKey,
+          lamports: web3.LAMPORTS_PER_SOL / 10,
+        }),
+      ])
+      .signers([trader])
+      .rpc();
+    const recordTrade = () =>
+      program.methods
+        .recordTrade(new BN(5_000), new BN(100), new BN(0), { spot: {} }, null, null)
+        .accounts({
+          traderStats: stats,
+          pairVolume: null,
+          config: configPda,
+          season: null,
+          seasonStats: null,
+          marketStats: null,
+          registeredMarket: null,
+          leaderboard: board.publicKey,
+          delegate: null,
+          trader: trader.publicKey,
+        })
+        .signers([trader])
+        .rpc();
+
+    try {
+      await recordTrade();
+      assert.fail("trades should only update live-ranked leaderboards");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "LiveRankingDisabled");
+    }
+
+    await program.methods
+      .setLiveRanking(true)
+      .accounts({ leaderboard: board.publicKey, config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    const txHash = await recordTrade();
+
+    const entries = await fetchBoardEntries(board.publicKey);
+    assert(entries.length === 1);
+    assert(entries[0].trader.equals(trader.publicKey));
+    assert(entries[0].score.gt(new BN(0)));
+    console.log(`✅ Live ranking updated by a trade: ${txHash}`);
+  });
+
+  it("Tracks daily quest progress from recorded trades", async () => {
+    const questId = new BN(1);
+    const [quest] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("quest"), questId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .createQuest(questId, { volume: {} }, new BN(10_000), new BN(100), new BN(24 * 60 * 60), new BN(0))
+      .accounts({
+        quest,
+        rewardVault,
+        config: configPda,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const trader = web3.Keypair.generate();
+    const [stats] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_stats"), trader.publicKey.toBuffer()],
+      program.programId
+    );
+    const [questProgress] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("quest_progress"), quest.toBuffer(), trader.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initTraderStats()
+      .accounts({
+        traderStats: stats,
+        trader: trader.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .preInstructions([
+        web3.SystemProgram.transfer({
+          fromPubkey: program.provider.publicKey,
+          toPubkey: trader.publicKey,
+          lamports: web3.LAMPORTS_PER_SOL / 10,
+        }),
+      ])
+      .signers([trader])
+      .rpc();
+    await program.methods
+      .initQuestProgress()
+      .accounts({
+        questProgress,
+        quest,
+        trader: trader.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+
+    const txHash = await program.methods
+      .recordTrade(new BN(5_000), new BN(100), new BN(0), { spot: {} }, null, null)
+      .accounts({
+        traderStats: stats,
+        pairVolume: null,
+        config: configPda,
+        season: null,
+        seasonStats: null,
+        marketStats: null,
+        registeredMarket: null,
+        leaderboard: null,
+        delegate: null,
+        trader: trader.publicKey,
+      })
+      .remainingAccounts([
+        { pubkey: quest, isSigner: false, isWritable: false },
+        { pubkey: questProgress, isSigner: false, isWritable: true },
+      ])
+      .signers([trader])
+      .rpc();
+
+    const progress = await program.account.questProgress.fetch(questProgress);
+    assert(progress.progress.eq(new BN(5_000)));
+    assert(!progress.claimed);
+    console.log(`✅ Quest progress advanced by a trade: ${txHash}`);
+  });
+
+  it("Lets a delegate key record trades until revoked", async () => {
+    const trader = web3.Keypair.generate();
+    const hotKey = web3.Keypair.generate();
+    const [stats] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader_stats"), trader.publicKey.toBuffer()],
+      program.programId
+    );
+    const [delegate] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), trader.publicKey.toBuffer(), hotKey.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initTraderStats()
+      .accounts({
+        traderStats: stats,
+        trader: trader.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .preInstructions([
+        web3.SystemProgram.transfer({
+          fromPubkey: program.provider.publicKey,
+          toPubkey: trader.publicKey,
+          lamports: web3.LAMPORTS_PER_SOL / 10,
+        }),
+      ])
+      .signers([trader])
+      .rpc();
+    await program.methods
+      .authorizeDelegate(hotKey.publicKey)
+      .accounts({ delegate, trader: trader.publicKey, systemProgram: web3.SystemProgram.programId })
+      .signers([trader])
+      .rpc();
+
+    const recordTrade = (delegateAccount: web3.PublicKey | null) =>
+      program.methods
+        .recordTrade(new BN(5_000), new BN(100), new BN(0), { spot: {} }, null, null)
+        .accounts({
+          traderStats: stats,
+          pairVolume: null,
+          config: configPda,
+          season: null,
+          seasonStats: null,
+          marketStats: null,
+          registeredMarket: null,
+          leaderboard: null,
+          delegate: delegateAccount,
+          trader: hotKey.publicKey,
+        })
+        .signers([hotKey])
+        .rpc();
+    const txHash = await recordTrade(delegate);
+    const account = await program.account.traderStats.fetch(stats);
+    assert(account.totalVolume.eq(new BN(5_000)));
+
+    await program.methods
+      .revokeDelegate()
+      .accounts({ delegate, trader: trader.publicKey })
+      .signers([trader])
+      .rpc();
+    try {
+      await recordTrade(null);
+      assert.fail("a revoked delegate should not record trades");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "Unauthorized");
+    }
+    console.log(`✅ Trade recorded by a delegate key: ${txHash}`);
+  });
+
+  it("Rejects trade attestations not signed by the oracle", async () => {
+    const oracle = web3.Keypair.generate();
+    const impostor = web3.Keypair.generate();
+    await program.methods
+      .setTradeOracle(oracle.publicKey)
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    const config = await program.account.config.fetch(configPda);
+    assert(config.tradeOracle.equals(oracle.publicKey));
+
+    const attestation = {
+      trader: program.provider.publicKey,
+      volume: new BN(1000),
+      executionTime: new BN(100),
+      pnl: new BN(10),
+      venue: { spot: {} },
+      attestedAt: new BN(Math.floor(Date.now() / 1000) - 60),
+    };
+    const message = program.coder.types.encode("TradeAttestation", attestation);
+
+    try {
+      await program.methods
+        .recordAttestedTrade(attestation)
+        .accounts({
+          traderStats: traderStatsPda,
+          config: configPda,
+          instructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions([
+          web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: impostor.secretKey,
+            message,
+          }),
+        ])
+        .rpc();
+      assert.fail("an impostor's attestation should be rejected");
+    } catch (err) {
+      assert(err.error?.errorCode?.code === "InvalidAttestation");
+    }
+
+    const txHash = await program.methods
+      .setTradeOracle(web3.PublicKey.default)
+      .accounts({ config: configPda, admin: program.provider.publicKey })
+      .rpc();
+    console.log(`✅ Trade oracle disabled: ${txHash}`);
   });
 
   it("Pauses leaderboard rewards in case of emergency", async () => {
@@ -173,6 +4074,9 @@ describe("Trade-to-Earn Leaderboard Token ($T2E)", () => {
       .adminPauseRewards(true)
       .accounts({
         leaderboard: leaderboardAccount.publicKey,
+        adminSet: null,
+        adminAction: null,
+        config: configPda,
         admin: program.provider.publicKey,
       })
       .rpc();
@@ -183,7 +4087,7 @@ describe("Trade-to-Earn Leaderboard Token ($T2E)", () => {
     const leaderboard = await program.account.leaderboard.fetch(
       leaderboardAccount.publicKey
     );
-    assert(leaderboard.emergencyPause === true);
+    assert(leaderboard.emergencyPause === 1);
   });
 
   it("Resumes leaderboard rewards", async () => {
@@ -191,6 +4095,9 @@ describe("Trade-to-Earn Leaderboard Token ($T2E)", () => {
       .adminPauseRewards(false)
       .accounts({
         leaderboard: leaderboardAccount.publicKey,
+        adminSet: null,
+        adminAction: null,
+        config: configPda,
         admin: program.provider.publicKey,
       })
       .rpc();
@@ -201,6 +4108,6 @@ describe("Trade-to-Earn Leaderboard Token ($T2E)", () => {
     const leaderboard = await program.account.leaderboard.fetch(
       leaderboardAccount.publicKey
     );
-    assert(leaderboard.emergencyPause === false);
+    assert(leaderboard.emergencyPause === 0);
   });
 });